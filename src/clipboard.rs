@@ -0,0 +1,97 @@
+//! System clipboard access with a fallback for sessions that have none.
+//!
+//! Headless machines and SSH sessions without a display server can't open the system
+//! clipboard. There, copies are written to a file instead, and the status message says
+//! where, so the text can still be picked up with `cat` or `scp`. Copies can hold secrets,
+//! so the file lives in a per-user directory and only its owner can read it.
+
+use arboard::Clipboard;
+use color_eyre::{eyre::eyre, Result};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub trait ClipboardBackend {
+    fn get_text(&mut self) -> Result<String>;
+    fn set_text(&mut self, text: &str) -> Result<()>;
+    /// File copied text goes to when this isn't the system clipboard.
+    fn fallback_path(&self) -> Option<&Path> {
+        None
+    }
+}
+
+struct Arboard(Clipboard);
+
+impl ClipboardBackend for Arboard {
+    fn get_text(&mut self) -> Result<String> {
+        Ok(self.0.get_text()?)
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        Ok(self.0.set_text(text)?)
+    }
+}
+
+struct FileFallback {
+    path: PathBuf,
+}
+
+impl ClipboardBackend for FileFallback {
+    fn get_text(&mut self) -> Result<String> {
+        Err(eyre!("no clipboard available in this session"))
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        let dir = self.path.parent().ok_or_else(|| eyre!("{} has no parent directory", self.path.display()))?;
+        fs::create_dir_all(dir)?;
+        // A fresh owner-only file renamed into place, so a symlink planted at the path is
+        // replaced rather than written through
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        file.write_all(text.as_bytes())?;
+        file.persist(&self.path)?;
+        Ok(())
+    }
+
+    fn fallback_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
+
+/// The per-user runtime directory where there is one (cleared at logout), else the user's
+/// cache directory. The shared temp dir is only a last resort.
+fn fallback_file() -> PathBuf {
+    dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("dev-tools")
+        .join("clipboard.txt")
+}
+
+/// The system clipboard, or the file fallback when it can't be opened.
+pub fn open() -> Box<dyn ClipboardBackend> {
+    match Clipboard::new() {
+        Ok(clipboard) => Box::new(Arboard(clipboard)),
+        Err(e) => {
+            tracing::warn!("clipboard unavailable, falling back to a file: {}", e);
+            Box::new(FileFallback { path: fallback_file() })
+        }
+    }
+}
+
+/// Copies `text`, returning `message` on success (noting the file when the fallback was
+/// used) or a description of the failure.
+pub fn try_copy(text: &str, message: String) -> Result<String, String> {
+    let mut clipboard = open();
+    match clipboard.set_text(text) {
+        Ok(()) => Ok(match clipboard.fallback_path() {
+            Some(path) => format!("{} (no clipboard, written to {})", message, path.display()),
+            None => message,
+        }),
+        Err(e) => Err(format!("Failed to copy to clipboard: {}", e)),
+    }
+}
+
+/// Like [`try_copy`], for callers that show success and failure on the same status line.
+pub fn copy(text: &str, message: String) -> String {
+    try_copy(text, message).unwrap_or_else(|failure| failure)
+}
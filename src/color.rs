@@ -0,0 +1,140 @@
+//! Terminal color capability detection.
+//!
+//! Support is guessed once at startup from `$COLORTERM` and `$TERM`, or forced with
+//! `--color=truecolor|256|16`. After each frame is drawn, [`adapt_buffer`] rewrites any RGB
+//! or indexed colors the terminal can't show into the nearest color it can.
+
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+use std::env;
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum ColorSupport {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+static SUPPORT: OnceLock<ColorSupport> = OnceLock::new();
+
+impl ColorSupport {
+    fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "truecolor" | "24bit" | "rgb" => Some(Self::TrueColor),
+            "256" | "ansi256" => Some(Self::Ansi256),
+            "16" | "ansi" | "basic" => Some(Self::Ansi16),
+            _ => None,
+        }
+    }
+
+    fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default().to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return Self::TrueColor;
+        }
+        let term = env::var("TERM").unwrap_or_default().to_lowercase();
+        if term.contains("256color") || term.contains("direct") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
+/// Fixes the color support for the session, honouring `override_flag` when it names a level.
+pub fn init(override_flag: Option<&str>) {
+    let support = match override_flag {
+        Some(flag) => ColorSupport::parse(flag).unwrap_or_else(|| {
+            tracing::warn!("unknown --color value '{}', detecting instead", flag);
+            ColorSupport::detect()
+        }),
+        None => ColorSupport::detect(),
+    };
+    tracing::debug!("color support: {:?}", support);
+    let _ = SUPPORT.set(support);
+}
+
+pub fn support() -> ColorSupport {
+    *SUPPORT.get_or_init(ColorSupport::detect)
+}
+
+/// The 16 ANSI colors with the RGB values xterm uses for them.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// RGB value of an xterm 256-color palette index.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (CUBE_LEVELS[(i / 36) as usize], CUBE_LEVELS[(i / 6 % 6) as usize], CUBE_LEVELS[(i % 6) as usize])
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).unsigned_abs();
+    d(r1, r2).pow(2) + d(g1, g2).pow(2) + d(b1, b2).pow(2)
+}
+
+fn nearest_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, candidate)| distance(rgb, *candidate))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+fn nearest_indexed(rgb: (u8, u8, u8)) -> Color {
+    let index = (16..=255u8)
+        .min_by_key(|&i| distance(rgb, indexed_rgb(i)))
+        .unwrap_or(16);
+    Color::Indexed(index)
+}
+
+/// The closest color to `color` that a terminal with `support` can display.
+pub fn downgrade(color: Color, support: ColorSupport) -> Color {
+    match (color, support) {
+        (_, ColorSupport::TrueColor) => color,
+        (Color::Rgb(r, g, b), ColorSupport::Ansi256) => nearest_indexed((r, g, b)),
+        (Color::Rgb(r, g, b), ColorSupport::Ansi16) => nearest_ansi16((r, g, b)),
+        (Color::Indexed(i), ColorSupport::Ansi16) => nearest_ansi16(indexed_rgb(i)),
+        _ => color,
+    }
+}
+
+/// Downgrades every cell of a drawn frame to what the terminal supports.
+pub fn adapt_buffer(buffer: &mut Buffer) {
+    let support = support();
+    if support == ColorSupport::TrueColor {
+        return;
+    }
+    for cell in buffer.content.iter_mut() {
+        cell.fg = downgrade(cell.fg, support);
+        cell.bg = downgrade(cell.bg, support);
+    }
+}
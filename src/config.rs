@@ -0,0 +1,229 @@
+//! User settings read from `config.toml` in the platform config dir.
+//!
+//! Loading is best-effort: a missing or malformed file yields the defaults so the tools
+//! always start.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub ui: UiConfig,
+    pub json: JsonConfig,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Whether moving past the last item of a list lands on the first (and vice versa),
+    /// in the main menu and the JSON tree alike.
+    pub wrap_navigation: bool,
+    /// Event poll timeout while something needs checking between keys, such as a watched
+    /// file or a pending debounce.
+    pub poll_interval_ms: u64,
+    /// Event poll timeout when nothing but a key press can change the screen.
+    pub idle_poll_interval_ms: u64,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            wrap_navigation: true,
+            poll_interval_ms: 100,
+            idle_poll_interval_ms: 2000,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct JsonConfig {
+    /// Key fragments whose values are hidden while masking is on. Matching ignores case,
+    /// `_` and `-`, so `apikey` also covers `api_key` and `apiKey`.
+    pub sensitive_keys: Vec<String>,
+    /// Extension given to temp files opened in the editor, so it picks JSON tooling.
+    pub temp_file_suffix: String,
+    /// Tree nodes shallower than this start expanded; deeper ones start collapsed.
+    pub auto_expand_depth: usize,
+    /// Save the open document and view on exit and reopen them on the next launch.
+    pub save_session: bool,
+    /// View the JSON viewer opens in. Updated to the last-used view on exit.
+    pub default_view: DefaultView,
+    /// Indentation of pretty-printed JSON: `"2"`, `"4"` or `"tab"`. Updated when cycled in
+    /// the viewer.
+    pub indent: Indent,
+    /// Name of the top-level type in generated Rust structs and TypeScript interfaces.
+    /// Updated when a different name is entered for TypeScript.
+    pub root_type_name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultView {
+    #[default]
+    Raw,
+    Tree,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum Indent {
+    #[default]
+    #[serde(rename = "2")]
+    TwoSpaces,
+    #[serde(rename = "4")]
+    FourSpaces,
+    #[serde(rename = "tab")]
+    Tab,
+}
+
+impl Indent {
+    pub fn next(self) -> Self {
+        match self {
+            Indent::TwoSpaces => Indent::FourSpaces,
+            Indent::FourSpaces => Indent::Tab,
+            Indent::Tab => Indent::TwoSpaces,
+        }
+    }
+
+    /// What one level of nesting is indented by.
+    pub fn text(self) -> &'static str {
+        match self {
+            Indent::TwoSpaces => "  ",
+            Indent::FourSpaces => "    ",
+            Indent::Tab => "\t",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Indent::TwoSpaces => "2 spaces",
+            Indent::FourSpaces => "4 spaces",
+            Indent::Tab => "tabs",
+        }
+    }
+
+    /// The setting's value in `config.toml`.
+    pub fn config_value(self) -> &'static str {
+        match self {
+            Indent::TwoSpaces => "2",
+            Indent::FourSpaces => "4",
+            Indent::Tab => "tab",
+        }
+    }
+}
+
+impl Default for JsonConfig {
+    fn default() -> Self {
+        Self {
+            sensitive_keys: ["password", "passwd", "secret", "token", "apikey", "authorization", "privatekey"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            temp_file_suffix: ".json".to_string(),
+            auto_expand_depth: 2,
+            save_session: false,
+            default_view: DefaultView::Raw,
+            indent: Indent::TwoSpaces,
+            root_type_name: "Root".to_string(),
+        }
+    }
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("dev-tools"))
+}
+
+fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let Some(path) = config_path() else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                tracing::warn!("ignoring invalid {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// Sets `[section] key = value` in `config.toml`. Only that value is touched: the user's
+/// comments, layout and key order stay as they wrote them, and nothing is written when the
+/// value is already set. Best-effort: failures are logged, and a file that doesn't parse is
+/// left untouched.
+pub fn store(section: &str, key: &str, value: toml_edit::Value) {
+    let Some(path) = config_path() else {
+        return;
+    };
+    let mut document = match fs::read_to_string(&path) {
+        Ok(contents) => match contents.parse::<toml_edit::DocumentMut>() {
+            Ok(document) => document,
+            Err(e) => {
+                tracing::warn!("not updating invalid {}: {}", path.display(), e);
+                return;
+            }
+        },
+        Err(_) => toml_edit::DocumentMut::new(),
+    };
+    if !set(&mut document, section, key, value) {
+        return;
+    }
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, document.to_string()));
+    if let Err(e) = result {
+        tracing::warn!("failed to write {}: {}", path.display(), e);
+    }
+}
+
+/// Sets `[section] key = value` in `document`, returning whether that changed it.
+fn set(document: &mut toml_edit::DocumentMut, section: &str, key: &str, mut value: toml_edit::Value) -> bool {
+    let Some(section) = document.entry(section).or_insert(toml_edit::table()).as_table_like_mut() else {
+        return false;
+    };
+    if let Some(current) = section.get(key).and_then(toml_edit::Item::as_value) {
+        // Keep the spacing and any comment after the old value
+        *value.decor_mut() = current.decor().clone();
+        if current.to_string() == value.to_string() {
+            return false;
+        }
+    }
+    section.insert(key, toml_edit::Item::Value(value));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "# my settings\n[ui]\nwrap_navigation = true # handy\n\n[json]\nindent = \"4\"\ndefault_view = \"raw\" # on start\n";
+
+    #[test]
+    fn set_changes_only_the_value() {
+        let mut document: toml_edit::DocumentMut = CONFIG.parse().unwrap();
+        assert!(set(&mut document, "json", "default_view", "tree".into()));
+        assert_eq!(document.to_string(), CONFIG.replace("\"raw\"", "\"tree\""));
+    }
+
+    #[test]
+    fn set_leaves_an_unchanged_value_alone() {
+        let mut document: toml_edit::DocumentMut = CONFIG.parse().unwrap();
+        assert!(!set(&mut document, "json", "default_view", "raw".into()));
+        assert_eq!(document.to_string(), CONFIG);
+    }
+
+    #[test]
+    fn set_adds_missing_sections() {
+        let mut document = toml_edit::DocumentMut::new();
+        assert!(set(&mut document, "json", "indent", "tab".into()));
+        assert_eq!(document.to_string(), "[json]\nindent = \"tab\"\n");
+    }
+}
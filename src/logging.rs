@@ -0,0 +1,34 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+
+/// Location of the debug log users can attach to bug reports.
+pub fn log_file_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("dev-tools").join("dev-tools.log"))
+}
+
+/// Routes `tracing` output to the log file. stdout/stderr belong to the TUI while the
+/// alternate screen is active, so if the file can't be opened logging is simply disabled.
+pub fn init(verbose: bool) {
+    let Some(path) = log_file_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        if fs::create_dir_all(parent).is_err() {
+            return;
+        }
+    }
+    let Ok(file) = File::create(&path) else {
+        return;
+    };
+
+    let level = if verbose { LevelFilter::DEBUG } else { LevelFilter::INFO };
+    let _ = tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .with_max_level(level)
+        .try_init();
+
+    tracing::info!("logging to {} at level {}", path.display(), level);
+}
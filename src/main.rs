@@ -1,5 +1,8 @@
-use color_eyre::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout},
@@ -7,20 +10,39 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, Paragraph},
     Terminal,
 };
-use std::io::Stdout;
+use std::io::{IsTerminal, Read, Stdout};
+use unicode_width::UnicodeWidthStr;
 
+mod clipboard;
+mod color;
+mod config;
+mod logging;
 mod modules;
+mod ui;
 
 enum InputMode {
     Normal,
     Editing,
 }
 
+/// Entries of the main menu: name and one-line description.
+const PROGRAMS: &[(&str, &str)] = &[
+    ("JSON Utils", "JSON viewer, formatter, and validator"),
+    ("Base64 Tools", "Base64 encode/decode utilities"),
+    ("String Utils", "String manipulation tools"),
+    ("File Tools", "File operations and utilities"),
+    ("Hash Tools", "MD5/SHA checksums for text and files"),
+    ("UUID Tools", "UUID v4/v7 generator and inspector"),
+    ("Time Tools", "Unix timestamp and datetime converter"),
+    ("Regex Tester", "Live regex matching with capture groups"),
+];
+
 struct MainMenu {
     input: String,
     cursor_position: usize,
     input_mode: InputMode,
     selected: usize,
+    wrap_navigation: bool,
 }
 
 impl MainMenu {
@@ -30,9 +52,20 @@ impl MainMenu {
             cursor_position: 0,
             input_mode: InputMode::Normal,
             selected: 0,
+            wrap_navigation: config::Config::load().ui.wrap_navigation,
         }
     }
 
+    /// Programs whose name contains the filter text, ignoring case, in menu order.
+    fn filter<'a>(&self, programs: &[(&'a str, &'a str)]) -> Vec<(&'a str, &'a str)> {
+        let query = self.input.to_lowercase();
+        programs
+            .iter()
+            .filter(|(name, _)| name.to_lowercase().contains(&query))
+            .copied()
+            .collect()
+    }
+
     fn move_cursor_left(&mut self) {
         let cursor_moved_left = self.cursor_position.saturating_sub(1);
         self.cursor_position = self.clamp_cursor(cursor_moved_left);
@@ -43,8 +76,23 @@ impl MainMenu {
         self.cursor_position = self.clamp_cursor(cursor_moved_right);
     }
 
+    /// Byte offset of the char the cursor is on, for `String` operations.
+    fn byte_index(&self) -> usize {
+        self.input
+            .char_indices()
+            .map(|(i, _)| i)
+            .nth(self.cursor_position)
+            .unwrap_or(self.input.len())
+    }
+
+    /// Terminal columns taken by the input before the cursor; wide chars such as emoji take two.
+    fn cursor_display_column(&self) -> u16 {
+        self.input[..self.byte_index()].width() as u16
+    }
+
     fn enter_char(&mut self, new_char: char) {
-        self.input.insert(self.cursor_position, new_char);
+        let index = self.byte_index();
+        self.input.insert(index, new_char);
         self.move_cursor_right();
         self.selected = 0;
     }
@@ -62,48 +110,162 @@ impl MainMenu {
         }
     }
 
+    /// Clamps a cursor position, counted in chars, to the end of the input.
     fn clamp_cursor(&self, new_cursor_pos: usize) -> usize {
-        new_cursor_pos.clamp(0, self.input.len())
+        new_cursor_pos.clamp(0, self.input.chars().count())
+    }
+
+    /// Start of the word before the cursor, skipping any whitespace first.
+    fn previous_word_start(&self) -> usize {
+        let chars: Vec<char> = self.input.chars().collect();
+        let mut position = self.clamp_cursor(self.cursor_position);
+        while position > 0 && chars[position - 1].is_whitespace() {
+            position -= 1;
+        }
+        while position > 0 && !chars[position - 1].is_whitespace() {
+            position -= 1;
+        }
+        position
+    }
+
+    /// End of the word after the cursor, skipping any whitespace first.
+    fn next_word_end(&self) -> usize {
+        let chars: Vec<char> = self.input.chars().collect();
+        let mut position = self.clamp_cursor(self.cursor_position);
+        while position < chars.len() && chars[position].is_whitespace() {
+            position += 1;
+        }
+        while position < chars.len() && !chars[position].is_whitespace() {
+            position += 1;
+        }
+        position
+    }
+
+    fn move_word_left(&mut self) {
+        self.cursor_position = self.previous_word_start();
+    }
+
+    fn move_word_right(&mut self) {
+        self.cursor_position = self.next_word_end();
+    }
+
+    fn delete_word(&mut self) {
+        let start = self.previous_word_start();
+        let end = self.clamp_cursor(self.cursor_position);
+        if start < end {
+            let before = self.input.chars().take(start);
+            let after = self.input.chars().skip(end);
+            self.input = before.chain(after).collect();
+            self.cursor_position = start;
+            self.selected = 0;
+        }
+    }
+
+    fn move_cursor_to_start(&mut self) {
+        self.cursor_position = 0;
+    }
+
+    fn move_cursor_to_end(&mut self) {
+        self.cursor_position = self.clamp_cursor(usize::MAX);
+    }
+
+    /// Keeps the highlight on a visible row after the filtered list shrinks.
+    fn clamp_selection(&mut self, len: usize) {
+        self.selected = self.selected.min(len.saturating_sub(1));
     }
 
     fn next_item(&mut self, items_len: usize) {
         if items_len > 0 {
-            self.selected = (self.selected + 1) % items_len;
+            self.selected = if self.selected + 1 < items_len {
+                self.selected + 1
+            } else if self.wrap_navigation {
+                0
+            } else {
+                items_len - 1
+            };
         }
     }
 
     fn previous_item(&mut self, items_len: usize) {
         if items_len > 0 {
-            self.selected = if self.selected == 0 {
+            self.selected = if self.selected > 0 {
+                self.selected - 1
+            } else if self.wrap_navigation {
                 items_len - 1
             } else {
-                self.selected - 1
+                0
             };
         }
     }
 }
 
+/// Installs color_eyre with a panic hook that leaves the alternate screen and raw mode
+/// before the report is printed, so a crash doesn't leave the shell unusable.
+fn install_hooks() -> Result<()> {
+    let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default().into_hooks();
+    eyre_hook.install()?;
+    let panic_hook = panic_hook.into_panic_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // The JSON viewer captures the mouse; an uncaptured terminal ignores this
+        let _ = crossterm::execute!(std::io::stdout(), crossterm::event::DisableMouseCapture);
+        ratatui::restore();
+        tracing::error!("panic: {}", info);
+        panic_hook(info);
+    }));
+    Ok(())
+}
+
 fn main() -> Result<()> {
-    color_eyre::install()?;
+    install_hooks()?;
+    let verbose = std::env::args().any(|arg| arg == "--verbose" || arg == "-v");
+    logging::init(verbose);
+    let color_flag = std::env::args().find_map(|arg| arg.strip_prefix("--color=").map(str::to_string));
+    color::init(color_flag.as_deref());
+    // A file argument (or `-` for stdin) skips the menu. The JSON viewer is the only tool that
+    // takes a document, so every file goes there, and it shows the parse error when the file
+    // isn't JSON.
+    if let Some(source) = std::env::args().skip(1).find(|arg| arg == "-" || !arg.starts_with('-')) {
+        // Read before the terminal switches over, so a bad path is reported plainly
+        let text = read_input(&source)?;
+        let app_result = modules::json_utils::run_json_utils_with_input(text);
+        let _ = crossterm::execute!(std::io::stdout(), crossterm::event::DisableMouseCapture);
+        ratatui::restore();
+        return app_result;
+    }
     let mut terminal = ratatui::init();
     let app_result = run_main_menu(&mut terminal);
+    // A tool that bailed out with an error may have left mouse capture on
+    let _ = crossterm::execute!(std::io::stdout(), crossterm::event::DisableMouseCapture);
     ratatui::restore();
     app_result
 }
 
+/// Contents of the file at `source`, or of stdin for `-`.
+fn read_input(source: &str) -> Result<String> {
+    if source != "-" {
+        return std::fs::read_to_string(source).wrap_err_with(|| format!("Can't read {}", source));
+    }
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Err(eyre!("'-' reads stdin, which is the terminal - pipe input in, e.g. `cat file.json | t -`"));
+    }
+    let mut text = String::new();
+    stdin.read_to_string(&mut text).wrap_err("Can't read stdin")?;
+    Ok(text)
+}
+
 fn run_main_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
     let mut menu = MainMenu::new();
-    let all_programs = vec![
-        ("JSON Utils", "JSON viewer, formatter, and validator"),
-        ("Base64 Tools", "Base64 encode/decode utilities"),
-        ("String Utils", "String manipulation tools"),
-        ("File Tools", "File operations and utilities"),
-    ];
 
     loop {
         terminal.draw(|frame| {
+            // Three 3-line blocks around a list that needs at least a few rows
+            if ui::render_too_small(frame, 40, 12) {
+                color::adapt_buffer(frame.buffer_mut());
+                return;
+            }
             let area = frame.area();
-            
+
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
@@ -122,7 +284,7 @@ fn run_main_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()
 
             let input_title = match menu.input_mode {
                 InputMode::Normal => "Filter Programs (Press 'i' to search, 'q' to quit)",
-                InputMode::Editing => "Filter Programs (Press 'Esc' to stop searching)",
+                InputMode::Editing => "Filter Programs (Esc: stop searching, ctrl+←/→: word, ctrl+w: delete word, ctrl+a/e: start/end)",
             };
             let input_block = Block::default().title(input_title).borders(Borders::ALL);
             let input_paragraph = Paragraph::new(menu.input.as_str())
@@ -135,22 +297,13 @@ fn run_main_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()
 
             if matches!(menu.input_mode, InputMode::Editing) {
                 frame.set_cursor_position((
-                    chunks[1].x + menu.cursor_position as u16 + 1,
+                    chunks[1].x + menu.cursor_display_column() + 1,
                     chunks[1].y + 1,
                 ));
             }
 
-            let filtered_programs: Vec<(&str, &str)> = all_programs
-                .iter()
-                .filter(|(name, _desc)| {
-                    if menu.input.is_empty() {
-                        true
-                    } else {
-                        name.to_lowercase().contains(&menu.input.to_lowercase())
-                    }
-                })
-                .copied()
-                .collect();
+            let filtered_programs = menu.filter(PROGRAMS);
+            menu.clamp_selection(filtered_programs.len());
 
             let program_list: Vec<ListItem> = filtered_programs
                 .iter()
@@ -180,9 +333,14 @@ fn run_main_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()
                 .style(Style::default().fg(Color::Gray))
                 .alignment(Alignment::Center);
             frame.render_widget(help, chunks[3]);
+            color::adapt_buffer(frame.buffer_mut());
         })?;
 
         if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                tracing::debug!("menu key: {:?} {:?}", key.code, key.modifiers);
+            }
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
             match menu.input_mode {
                 InputMode::Normal => match key.code {
                     KeyCode::Char('q') if key.kind == KeyEventKind::Press => break,
@@ -190,51 +348,55 @@ fn run_main_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()
                         menu.input_mode = InputMode::Editing;
                     }
                     KeyCode::Up | KeyCode::Char('k') if key.kind == KeyEventKind::Press => {
-                        let filtered_count = all_programs
-                            .iter()
-                            .filter(|(name, _)| {
-                                if menu.input.is_empty() {
-                                    true
-                                } else {
-                                    name.to_lowercase().contains(&menu.input.to_lowercase())
-                                }
-                            })
-                            .count();
+                        let filtered_count = menu.filter(PROGRAMS).len();
                         menu.previous_item(filtered_count);
                     }
                     KeyCode::Down | KeyCode::Char('j') if key.kind == KeyEventKind::Press => {
-                        let filtered_count = all_programs
-                            .iter()
-                            .filter(|(name, _)| {
-                                if menu.input.is_empty() {
-                                    true
-                                } else {
-                                    name.to_lowercase().contains(&menu.input.to_lowercase())
-                                }
-                            })
-                            .count();
+                        let filtered_count = menu.filter(PROGRAMS).len();
                         menu.next_item(filtered_count);
                     }
                     KeyCode::Enter if key.kind == KeyEventKind::Press => {
-                        let filtered_programs: Vec<_> = all_programs
-                            .iter()
-                            .filter(|(name, _)| {
-                                if menu.input.is_empty() {
-                                    true
-                                } else {
-                                    name.to_lowercase().contains(&menu.input.to_lowercase())
-                                }
-                            })
-                            .collect();
-                        
+                        let filtered_programs = menu.filter(PROGRAMS);
+
                         if menu.selected < filtered_programs.len() {
                             let selected_program = filtered_programs[menu.selected].0;
+                            tracing::info!("launching {}", selected_program);
                             match selected_program {
                                 "JSON Utils" => {
                                     ratatui::restore();
                                     modules::json_utils::run_json_utils()?;
                                     *terminal = ratatui::init();
                                 }
+                                "Base64 Tools" => {
+                                    ratatui::restore();
+                                    modules::base64_utils::run_base64_utils()?;
+                                    *terminal = ratatui::init();
+                                }
+                                "File Tools" => {
+                                    ratatui::restore();
+                                    modules::file_utils::run_file_utils()?;
+                                    *terminal = ratatui::init();
+                                }
+                                "Hash Tools" => {
+                                    ratatui::restore();
+                                    modules::hash_utils::run_hash_utils()?;
+                                    *terminal = ratatui::init();
+                                }
+                                "UUID Tools" => {
+                                    ratatui::restore();
+                                    modules::uuid_utils::run_uuid_utils()?;
+                                    *terminal = ratatui::init();
+                                }
+                                "Time Tools" => {
+                                    ratatui::restore();
+                                    modules::time_utils::run_time_utils()?;
+                                    *terminal = ratatui::init();
+                                }
+                                "Regex Tester" => {
+                                    ratatui::restore();
+                                    modules::regex_utils::run_regex_utils()?;
+                                    *terminal = ratatui::init();
+                                }
                                 _ => {
                                     // TODO: Implement other programs
                                 }
@@ -247,18 +409,39 @@ fn run_main_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()
                     KeyCode::Esc if key.kind == KeyEventKind::Press => {
                         menu.input_mode = InputMode::Normal;
                     }
-                    KeyCode::Char(c) if key.kind == KeyEventKind::Press => {
+                    KeyCode::Char('w') if key.kind == KeyEventKind::Press && ctrl => {
+                        menu.delete_word();
+                    }
+                    KeyCode::Char('a') if key.kind == KeyEventKind::Press && ctrl => {
+                        menu.move_cursor_to_start();
+                    }
+                    KeyCode::Char('e') if key.kind == KeyEventKind::Press && ctrl => {
+                        menu.move_cursor_to_end();
+                    }
+                    KeyCode::Char(c) if key.kind == KeyEventKind::Press && !ctrl => {
                         menu.enter_char(c);
                     }
                     KeyCode::Backspace if key.kind == KeyEventKind::Press => {
                         menu.delete_char();
                     }
+                    KeyCode::Left if key.kind == KeyEventKind::Press && ctrl => {
+                        menu.move_word_left();
+                    }
+                    KeyCode::Right if key.kind == KeyEventKind::Press && ctrl => {
+                        menu.move_word_right();
+                    }
                     KeyCode::Left if key.kind == KeyEventKind::Press => {
                         menu.move_cursor_left();
                     }
                     KeyCode::Right if key.kind == KeyEventKind::Press => {
                         menu.move_cursor_right();
                     }
+                    KeyCode::Home if key.kind == KeyEventKind::Press => {
+                        menu.move_cursor_to_start();
+                    }
+                    KeyCode::End if key.kind == KeyEventKind::Press => {
+                        menu.move_cursor_to_end();
+                    }
                     _ => {}
                 },
             }
@@ -267,3 +450,97 @@ fn run_main_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> MainMenu {
+        MainMenu {
+            input: String::new(),
+            cursor_position: 0,
+            input_mode: InputMode::Editing,
+            selected: 0,
+            wrap_navigation: false,
+        }
+    }
+
+    fn names(menu: &MainMenu) -> Vec<&'static str> {
+        menu.filter(PROGRAMS).into_iter().map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn filter_matches_names_case_insensitively() {
+        let cases: &[(&str, &[&str])] = &[
+            ("", &[
+                "JSON Utils", "Base64 Tools", "String Utils", "File Tools", "Hash Tools", "UUID Tools", "Time Tools",
+                "Regex Tester",
+            ]),
+            ("json", &["JSON Utils"]),
+            ("JSON", &["JSON Utils"]),
+            ("tOoLs", &["Base64 Tools", "File Tools", "Hash Tools", "UUID Tools", "Time Tools"]),
+            ("util", &["JSON Utils", "String Utils"]),
+            ("e t", &["File Tools", "Time Tools"]),
+            // Only names are searched, not descriptions
+            ("checksum", &[]),
+            ("zzz", &[]),
+        ];
+        for (input, expected) in cases {
+            let mut menu = menu();
+            input.chars().for_each(|c| menu.enter_char(c));
+            assert_eq!(names(&menu), *expected, "filter {:?}", input);
+        }
+    }
+
+    #[test]
+    fn clamp_selection_keeps_the_selection_in_range() {
+        let mut menu = menu();
+        menu.selected = 5;
+        menu.clamp_selection(8);
+        assert_eq!(menu.selected, 5);
+        menu.clamp_selection(6);
+        assert_eq!(menu.selected, 5);
+        menu.clamp_selection(2);
+        assert_eq!(menu.selected, 1);
+        menu.clamp_selection(0);
+        assert_eq!(menu.selected, 0);
+    }
+
+    #[test]
+    fn selection_stays_on_a_row_when_the_filter_narrows() {
+        let mut menu = menu();
+        // As left on a later row before typing narrowed the list
+        menu.selected = 5;
+        "json".chars().for_each(|c| menu.enter_char(c));
+        let filtered = menu.filter(PROGRAMS);
+        menu.clamp_selection(filtered.len());
+        assert_eq!(filtered.get(menu.selected).map(|(name, _)| *name), Some("JSON Utils"));
+    }
+
+    #[test]
+    fn pasting_multibyte_text_keeps_cursor_on_char_boundaries() {
+        let mut menu = menu();
+        // (char, cursor in chars, byte index, display column) after each char
+        let expected = [('c', 1, 1, 1), ('a', 2, 2, 2), ('f', 3, 3, 3), ('é', 4, 5, 4), ('🚀', 5, 9, 6)];
+        for (c, cursor, byte, column) in expected {
+            menu.enter_char(c);
+            assert_eq!(menu.cursor_position, cursor);
+            assert_eq!(menu.byte_index(), byte);
+            assert_eq!(menu.cursor_display_column(), column);
+        }
+        assert_eq!(menu.input, "café🚀");
+
+        menu.delete_char();
+        assert_eq!(menu.input, "café");
+        assert_eq!((menu.cursor_position, menu.byte_index(), menu.cursor_display_column()), (4, 5, 4));
+        menu.delete_char();
+        assert_eq!(menu.input, "caf");
+        assert_eq!((menu.cursor_position, menu.byte_index(), menu.cursor_display_column()), (3, 3, 3));
+
+        // Inserting in the middle lands before the char the cursor is on
+        menu.move_cursor_left();
+        menu.enter_char('🚀');
+        assert_eq!(menu.input, "ca🚀f");
+        assert_eq!((menu.cursor_position, menu.byte_index(), menu.cursor_display_column()), (3, 6, 4));
+    }
+}
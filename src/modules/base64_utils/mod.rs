@@ -0,0 +1,219 @@
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use color_eyre::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use std::time::Duration;
+
+use crate::clipboard;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Encode,
+    Decode,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Alphabet {
+    Standard,
+    UrlSafe,
+}
+
+impl Alphabet {
+    fn label(self) -> &'static str {
+        match self {
+            Alphabet::Standard => "standard",
+            Alphabet::UrlSafe => "URL-safe",
+        }
+    }
+
+    /// Encodes with padding; decodes with or without it, since both are common in the wild.
+    fn engine(self) -> GeneralPurpose {
+        let alphabet = match self {
+            Alphabet::Standard => &alphabet::STANDARD,
+            Alphabet::UrlSafe => &alphabet::URL_SAFE,
+        };
+        let config = GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+        GeneralPurpose::new(alphabet, config)
+    }
+}
+
+pub struct Base64Utils {
+    input: String,
+    output: String,
+    mode: Mode,
+    alphabet: Alphabet,
+    /// Set when the input couldn't be decoded; rendered in place of the output.
+    decode_error: Option<String>,
+    error_message: String,
+}
+
+impl Base64Utils {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            output: String::new(),
+            mode: Mode::Encode,
+            alphabet: Alphabet::Standard,
+            decode_error: None,
+            error_message: String::new(),
+        }
+    }
+
+    fn convert(&mut self) {
+        self.decode_error = None;
+        self.output.clear();
+        if self.input.is_empty() {
+            return;
+        }
+        let engine = self.alphabet.engine();
+        match self.mode {
+            Mode::Encode => self.output = engine.encode(self.input.as_bytes()),
+            Mode::Decode => {
+                // Wrapped base64 (PEM bodies, email) is split across lines
+                let compact: String = self.input.chars().filter(|c| !c.is_whitespace()).collect();
+                match engine.decode(compact) {
+                    Ok(bytes) => {
+                        self.output = match String::from_utf8(bytes) {
+                            Ok(text) => text,
+                            Err(e) => {
+                                let bytes = e.into_bytes();
+                                let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                                format!("(binary, {} bytes) {}", bytes.len(), hex.join(" "))
+                            }
+                        }
+                    }
+                    Err(e) => self.decode_error = Some(format!("Invalid {} base64: {}", self.alphabet.label(), e)),
+                }
+            }
+        }
+    }
+
+    pub fn paste_from_clipboard(&mut self) -> Result<()> {
+        let mut clipboard = clipboard::open();
+        match clipboard.get_text() {
+            Ok(text) => {
+                self.input = text;
+                self.error_message.clear();
+                self.convert();
+            }
+            Err(e) => {
+                self.error_message = format!("Failed to get clipboard: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn copy_to_clipboard(&mut self) -> Result<()> {
+        if !self.output.is_empty() {
+            self.error_message = clipboard::copy(&self.output, "Copied result to clipboard".to_string());
+        }
+        Ok(())
+    }
+
+    fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            Mode::Encode => Mode::Decode,
+            Mode::Decode => Mode::Encode,
+        };
+        self.convert();
+    }
+
+    fn toggle_alphabet(&mut self) {
+        self.alphabet = match self.alphabet {
+            Alphabet::Standard => Alphabet::UrlSafe,
+            Alphabet::UrlSafe => Alphabet::Standard,
+        };
+        self.convert();
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Min(1), Constraint::Length(3)])
+            .split(area);
+
+        let mode = match self.mode {
+            Mode::Encode => "encode",
+            Mode::Decode => "decode",
+        };
+        let input_title = format!(
+            "Base64 Tools [{}, {}] - 'p': paste, 'm': encode/decode, 'u': standard/URL-safe, 'c': copy, 'q': quit",
+            mode,
+            self.alphabet.label()
+        );
+        let input_text = if self.input.is_empty() {
+            "Press 'p' to paste text from the clipboard"
+        } else {
+            self.input.as_str()
+        };
+        let input = Paragraph::new(input_text)
+            .block(Block::default().title(input_title).borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(Color::White));
+        frame.render_widget(input, chunks[0]);
+
+        let (output_text, output_color) = match self.decode_error {
+            Some(ref error) => (error.as_str(), Color::Red),
+            None => (self.output.as_str(), Color::Green),
+        };
+        let output = Paragraph::new(output_text)
+            .block(Block::default().title("Result").borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(output_color));
+        frame.render_widget(output, chunks[1]);
+
+        let status_color = if self.error_message.starts_with("Copied") {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        let status = Paragraph::new(self.error_message.as_str())
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(status_color));
+        frame.render_widget(status, chunks[2]);
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Result<bool> {
+        if let Event::Key(key) = event {
+            if key.kind != KeyEventKind::Press {
+                return Ok(true);
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                KeyCode::Char('p') => self.paste_from_clipboard()?,
+                KeyCode::Char('m') => self.toggle_mode(),
+                KeyCode::Char('u') => self.toggle_alphabet(),
+                KeyCode::Char('c') => self.copy_to_clipboard()?,
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+}
+
+pub fn run_base64_utils() -> Result<()> {
+    let mut terminal = ratatui::init();
+    let mut base64_utils = Base64Utils::new();
+
+    loop {
+        terminal.draw(|frame| {
+            base64_utils.render(frame, frame.area());
+            crate::color::adapt_buffer(frame.buffer_mut());
+        })?;
+
+        if event::poll(Duration::from_millis(100))? {
+            let event = event::read()?;
+            if !base64_utils.handle_event(event)? {
+                break;
+            }
+        }
+    }
+
+    ratatui::restore();
+    Ok(())
+}
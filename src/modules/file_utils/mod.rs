@@ -0,0 +1,238 @@
+use chrono::{DateTime, Local};
+use color_eyre::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::modules::json_utils::format_bytes;
+
+struct Entry {
+    name: String,
+    path: PathBuf,
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+pub struct FileUtils {
+    current_dir: PathBuf,
+    entries: Vec<Entry>,
+    selected: usize,
+    show_hidden: bool,
+    error_message: String,
+    /// Contents of a JSON file chosen with Enter, handed to the JSON viewer by the run loop.
+    pending_json: Option<String>,
+}
+
+impl FileUtils {
+    pub fn new() -> Self {
+        let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let mut file_utils = Self {
+            current_dir,
+            entries: Vec::new(),
+            selected: 0,
+            show_hidden: false,
+            error_message: String::new(),
+            pending_json: None,
+        };
+        file_utils.refresh();
+        file_utils
+    }
+
+    /// Re-reads the current directory: directories first, then files, each sorted by name.
+    fn refresh(&mut self) {
+        self.entries.clear();
+        match fs::read_dir(&self.current_dir) {
+            Ok(read_dir) => {
+                for dir_entry in read_dir.flatten() {
+                    let name = dir_entry.file_name().to_string_lossy().into_owned();
+                    if !self.show_hidden && name.starts_with('.') {
+                        continue;
+                    }
+                    // Follows symlinks so a link to a directory can be entered
+                    let metadata = fs::metadata(dir_entry.path()).ok();
+                    self.entries.push(Entry {
+                        name,
+                        path: dir_entry.path(),
+                        is_dir: metadata.as_ref().is_some_and(|m| m.is_dir()),
+                        size: metadata.as_ref().map_or(0, |m| m.len()),
+                        modified: metadata.and_then(|m| m.modified().ok()),
+                    });
+                }
+                self.entries
+                    .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+            }
+            Err(e) => {
+                self.error_message = format!("Failed to read {}: {}", self.current_dir.display(), e);
+            }
+        }
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+    }
+
+    fn change_dir(&mut self, dir: PathBuf) {
+        self.current_dir = dir;
+        self.selected = 0;
+        self.error_message.clear();
+        self.refresh();
+    }
+
+    fn open_selected(&mut self) {
+        let Some(entry) = self.entries.get(self.selected) else {
+            return;
+        };
+        if entry.is_dir {
+            let path = entry.path.clone();
+            self.change_dir(path);
+        } else if entry.path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            match fs::read_to_string(&entry.path) {
+                Ok(text) => self.pending_json = Some(text),
+                Err(e) => self.error_message = format!("Failed to read {}: {}", entry.name, e),
+            }
+        } else {
+            self.error_message = format!("{} is not a JSON file", entry.name);
+        }
+    }
+
+    /// Moves to the parent directory with the directory just left still highlighted.
+    fn go_up(&mut self) {
+        let Some(parent) = self.current_dir.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let left = self.current_dir.file_name().map(|name| name.to_string_lossy().into_owned());
+        self.change_dir(parent);
+        if let Some(index) = left.and_then(|left| self.entries.iter().position(|entry| entry.name == left)) {
+            self.selected = index;
+        }
+    }
+
+    fn toggle_hidden(&mut self) {
+        let selected_name = self.entries.get(self.selected).map(|entry| entry.name.clone());
+        self.show_hidden = !self.show_hidden;
+        self.refresh();
+        if let Some(index) = selected_name.and_then(|name| self.entries.iter().position(|entry| entry.name == name)) {
+            self.selected = index;
+        }
+    }
+
+    fn next_item(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + 1) % self.entries.len();
+        }
+    }
+
+    fn previous_item(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = if self.selected > 0 {
+                self.selected - 1
+            } else {
+                self.entries.len() - 1
+            };
+        }
+    }
+
+    fn selected_details(&self) -> String {
+        let Some(entry) = self.entries.get(self.selected) else {
+            return "Directory is empty".to_string();
+        };
+        let modified = entry
+            .modified
+            .map(|time| DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        if entry.is_dir {
+            format!("{}/  directory  modified {}", entry.name, modified)
+        } else {
+            format!("{}  {}  modified {}", entry.name, format_bytes(entry.size as usize), modified)
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(3), Constraint::Length(3)])
+            .split(area);
+
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                if entry.is_dir {
+                    ListItem::new(format!("{}/", entry.name)).style(Style::default().fg(Color::Cyan))
+                } else {
+                    ListItem::new(entry.name.as_str()).style(Style::default().fg(Color::White))
+                }
+            })
+            .collect();
+        let title = format!(
+            "File Tools - {} - ↑/↓ j/k: navigate, Enter: open, Backspace: up, '.': hidden files ({}), 'q': quit",
+            self.current_dir.display(),
+            if self.show_hidden { "shown" } else { "hidden" }
+        );
+        let list = List::new(items)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .highlight_style(Style::default().bg(Color::Blue).fg(Color::White));
+        let mut state = ListState::default().with_selected((!self.entries.is_empty()).then_some(self.selected));
+        frame.render_stateful_widget(list, chunks[0], &mut state);
+
+        let details = Paragraph::new(self.selected_details())
+            .block(Block::default().title("Details").borders(Borders::ALL))
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(details, chunks[1]);
+
+        let status = Paragraph::new(self.error_message.as_str())
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(Color::Red));
+        frame.render_widget(status, chunks[2]);
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Result<bool> {
+        if let Event::Key(key) = event {
+            if key.kind != KeyEventKind::Press {
+                return Ok(true);
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                KeyCode::Down | KeyCode::Char('j') => self.next_item(),
+                KeyCode::Up | KeyCode::Char('k') => self.previous_item(),
+                KeyCode::Enter => self.open_selected(),
+                KeyCode::Backspace => self.go_up(),
+                KeyCode::Char('.') => self.toggle_hidden(),
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+}
+
+pub fn run_file_utils() -> Result<()> {
+    let mut terminal = ratatui::init();
+    let mut file_utils = FileUtils::new();
+
+    loop {
+        if let Some(text) = file_utils.pending_json.take() {
+            ratatui::restore();
+            crate::modules::json_utils::run_json_utils_with_input(text)?;
+            terminal = ratatui::init();
+        }
+
+        terminal.draw(|frame| {
+            file_utils.render(frame, frame.area());
+            crate::color::adapt_buffer(frame.buffer_mut());
+        })?;
+
+        if event::poll(Duration::from_millis(100))? {
+            let event = event::read()?;
+            if !file_utils.handle_event(event)? {
+                break;
+            }
+        }
+    }
+
+    ratatui::restore();
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use color_eyre::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use md5::Md5;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs;
+use std::time::Duration;
+
+use crate::clipboard;
+
+#[derive(PartialEq)]
+enum InputMode {
+    Normal,
+    EditingPath,
+}
+
+pub struct HashUtils {
+    source: String,
+    byte_len: usize,
+    digests: Vec<(&'static str, String)>,
+    selected: usize,
+    input_mode: InputMode,
+    path_input: String,
+    error_message: String,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl HashUtils {
+    pub fn new() -> Self {
+        Self {
+            source: String::new(),
+            byte_len: 0,
+            digests: Vec::new(),
+            selected: 0,
+            input_mode: InputMode::Normal,
+            path_input: String::new(),
+            error_message: String::new(),
+        }
+    }
+
+    fn compute(&mut self, source: String, bytes: &[u8]) {
+        self.source = source;
+        self.byte_len = bytes.len();
+        self.digests = vec![
+            ("MD5", to_hex(&Md5::digest(bytes))),
+            ("SHA-1", to_hex(&Sha1::digest(bytes))),
+            ("SHA-256", to_hex(&Sha256::digest(bytes))),
+            ("SHA-512", to_hex(&Sha512::digest(bytes))),
+        ];
+        self.selected = 0;
+        self.error_message.clear();
+    }
+
+    pub fn paste_from_clipboard(&mut self) -> Result<()> {
+        let mut clipboard = clipboard::open();
+        match clipboard.get_text() {
+            Ok(text) => self.compute("clipboard text".to_string(), text.as_bytes()),
+            Err(e) => {
+                self.error_message = format!("Failed to get clipboard: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn hash_file(&mut self) {
+        let path = self.path_input.trim().to_string();
+        match fs::read(&path) {
+            Ok(bytes) => self.compute(format!("file {}", path), &bytes),
+            Err(e) => {
+                self.error_message = format!("Failed to read {}: {}", path, e);
+            }
+        }
+    }
+
+    pub fn copy_selected_to_clipboard(&mut self) -> Result<()> {
+        if let Some((name, digest)) = self.digests.get(self.selected) {
+            self.error_message = clipboard::copy(digest, format!("Copied {} digest to clipboard", name));
+        }
+        Ok(())
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(1), Constraint::Length(3)])
+            .split(area);
+
+        let (input_title, input_text, input_style) = match self.input_mode {
+            InputMode::EditingPath => (
+                "File Path (Enter to hash, Esc to cancel)",
+                self.path_input.clone(),
+                Style::default().fg(Color::Yellow),
+            ),
+            InputMode::Normal if self.digests.is_empty() => (
+                "Input",
+                "Press 'p' to hash clipboard text or 'f' to hash a file".to_string(),
+                Style::default().fg(Color::Cyan),
+            ),
+            InputMode::Normal => (
+                "Input",
+                format!("{} ({} bytes)", self.source, self.byte_len),
+                Style::default().fg(Color::Gray),
+            ),
+        };
+        let input = Paragraph::new(input_text)
+            .block(Block::default().title(input_title).borders(Borders::ALL))
+            .style(input_style);
+        frame.render_widget(input, chunks[0]);
+
+        if self.input_mode == InputMode::EditingPath {
+            frame.set_cursor_position((
+                chunks[0].x + self.path_input.chars().count() as u16 + 1,
+                chunks[0].y + 1,
+            ));
+        }
+
+        let items: Vec<ListItem> = self
+            .digests
+            .iter()
+            .enumerate()
+            .map(|(i, (name, digest))| {
+                let style = if i == self.selected {
+                    Style::default().bg(Color::Blue).fg(Color::White)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                ListItem::new(format!("{:<8} {}", name, digest)).style(style)
+            })
+            .collect();
+        let list = List::new(items).block(
+            Block::default()
+                .title("Hash Tools - 'p': paste, 'f': file, 'c': copy, ↑/↓ j/k: navigate, 'q': quit")
+                .borders(Borders::ALL),
+        );
+        frame.render_widget(list, chunks[1]);
+
+        let status_color = if self.error_message.starts_with("Copied") {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        let status = Paragraph::new(self.error_message.as_str())
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(status_color));
+        frame.render_widget(status, chunks[2]);
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Result<bool> {
+        if let Event::Key(key) = event {
+            if key.kind != KeyEventKind::Press {
+                return Ok(true);
+            }
+            match self.input_mode {
+                InputMode::EditingPath => match key.code {
+                    KeyCode::Esc => self.input_mode = InputMode::Normal,
+                    KeyCode::Enter => {
+                        self.input_mode = InputMode::Normal;
+                        self.hash_file();
+                    }
+                    KeyCode::Backspace => {
+                        self.path_input.pop();
+                    }
+                    KeyCode::Char(c) => self.path_input.push(c),
+                    _ => {}
+                },
+                InputMode::Normal => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                    KeyCode::Char('p') => self.paste_from_clipboard()?,
+                    KeyCode::Char('f') => self.input_mode = InputMode::EditingPath,
+                    KeyCode::Char('c') => self.copy_selected_to_clipboard()?,
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.selected = self.selected.saturating_sub(1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.selected = (self.selected + 1).min(self.digests.len().saturating_sub(1));
+                    }
+                    _ => {}
+                },
+            }
+        }
+        Ok(true)
+    }
+}
+
+pub fn run_hash_utils() -> Result<()> {
+    let mut terminal = ratatui::init();
+    let mut hash_utils = HashUtils::new();
+
+    loop {
+        terminal.draw(|frame| {
+            hash_utils.render(frame, frame.area());
+            crate::color::adapt_buffer(frame.buffer_mut());
+        })?;
+
+        if event::poll(Duration::from_millis(100))? {
+            let event = event::read()?;
+            if !hash_utils.handle_event(event)? {
+                break;
+            }
+        }
+    }
+
+    ratatui::restore();
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! Canonical JSON following the JSON Canonicalization Scheme (RFC 8785): object keys sorted by
+//! their UTF-16 code units, no insignificant whitespace, minimal string escaping, and
+//! floating-point numbers written the way ECMAScript's `Number.prototype.toString` does.
+//!
+//! One deliberate deviation: integers that serde_json holds exactly as `i64`/`u64` are
+//! written with all their digits. JCS routes every number through an IEEE double, so
+//! integers beyond 2^53 would lose precision there; here they stay as written. Documents
+//! whose integers fit in a double (any interoperable JSON) canonicalize identically.
+//!
+//! Doubles must be read exactly for this to hold, which is why serde_json is built with
+//! `float_roundtrip`: its default parser can land one unit in the last place off.
+
+use serde_json::{Number, Value};
+
+/// ECMAScript `Number::toString` for a finite double (RFC 8785 section 3.2.2.3).
+fn write_double(f: f64, out: &mut String) {
+    if f == 0.0 {
+        // Covers -0, which JCS writes as 0
+        out.push('0');
+        return;
+    }
+    if f < 0.0 {
+        out.push('-');
+    }
+    // `{:e}` gives the shortest digits that round-trip, as ECMAScript requires
+    let formatted = format!("{:e}", f.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap_or(0) + 1;
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', (-n) as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push('e');
+        out.push(if n - 1 < 0 { '-' } else { '+' });
+        out.push_str(&(n - 1).abs().to_string());
+    }
+}
+
+fn write_number(n: &Number, out: &mut String) {
+    match n.as_f64() {
+        Some(f) if n.is_f64() => write_double(f, out),
+        _ => out.push_str(&n.to_string()),
+    }
+}
+
+fn write(value: &Value, out: &mut String) {
+    match value {
+        Value::Number(n) => write_number(n, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(obj) => {
+            let mut entries: Vec<_> = obj.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, child)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::to_string(key).unwrap_or_default());
+                out.push(':');
+                write(child, out);
+            }
+            out.push('}');
+        }
+        // serde_json already escapes exactly what JCS requires: `"`, `\`, and control
+        // characters, using the short forms where they exist and lowercase `\u00xx` otherwise
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+pub fn to_canonical_string(value: &Value) -> String {
+    let mut out = String::new();
+    write(value, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn canonical(text: &str) -> String {
+        to_canonical_string(&serde_json::from_str(text).unwrap())
+    }
+
+    #[test]
+    fn numbers_follow_ecmascript() {
+        // Vectors from RFC 8785 appendix B
+        assert_eq!(canonical("1e21"), "1e+21");
+        assert_eq!(canonical("1e20"), "100000000000000000000");
+        assert_eq!(canonical("1e-7"), "1e-7");
+        assert_eq!(canonical("0.000001"), "0.000001");
+        assert_eq!(canonical("-0.0"), "0");
+        assert_eq!(canonical("5e-324"), "5e-324");
+        assert_eq!(canonical("1.7976931348623157e308"), "1.7976931348623157e+308");
+        assert_eq!(canonical("333333333.33333329"), "333333333.3333333");
+        assert_eq!(canonical("[1.0, 1.5, -4.50]"), "[1,1.5,-4.5]");
+    }
+
+    #[test]
+    fn keys_sort_by_utf16_code_units() {
+        let value = json!({
+            "\u{20ac}": "Euro Sign",
+            "\r": "Carriage Return",
+            "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\u{1f600}": "Emoji: Grinning Face",
+            "\u{80}": "Control",
+            "\u{f6}": "Latin Small Letter O With Diaeresis"
+        });
+        assert_eq!(
+            to_canonical_string(&value),
+            "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\
+             \"\u{20ac}\":\"Euro Sign\",\"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+        );
+    }
+
+    #[test]
+    fn strings_use_minimal_escapes() {
+        let value = json!({"s": "\u{1}\u{1f}\t\n\"\\/é\u{7f}"});
+        assert_eq!(to_canonical_string(&value), r#"{"s":"\u0001\u001f\t\n\"\\/é"#.to_string() + "\u{7f}\"}");
+    }
+
+    #[test]
+    fn key_order_and_whitespace_do_not_matter() {
+        let a = canonical(r#"{ "b": [1, {"y": 2, "x": 1}], "a": null }"#);
+        assert_eq!(a, r#"{"a":null,"b":[1,{"x":1,"y":2}]}"#);
+        assert_eq!(a, to_canonical_string(&json!({"b": [1, {"x": 1, "y": 2}], "a": null})));
+    }
+}
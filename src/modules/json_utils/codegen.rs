@@ -0,0 +1,362 @@
+//! Type definitions inferred from a sample document, as Rust serde structs or TypeScript
+//! interfaces.
+//!
+//! Inference walks the whole value and merges what it sees: integers and floats widen to
+//! floats, a `null` next to anything makes it nullable, object fields missing from some
+//! samples become optional, and values with genuinely different types fall back to an
+//! untyped value. Objects with exactly the same fields share one generated type.
+
+use serde_json::Value;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Shape {
+    /// Only `null`s or empty arrays were seen, so there is nothing to infer from.
+    Unknown,
+    Bool,
+    Integer,
+    Float,
+    String,
+    Array(Box<Shape>),
+    Object(Vec<Field>),
+    Nullable(Box<Shape>),
+    /// Incompatible types at the same position.
+    Mixed,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Field {
+    pub name: String,
+    pub shape: Shape,
+    /// Missing from at least one of the objects merged into this shape.
+    pub optional: bool,
+}
+
+pub fn infer(value: &Value) -> Shape {
+    match value {
+        Value::Null => Shape::Nullable(Box::new(Shape::Unknown)),
+        Value::Bool(_) => Shape::Bool,
+        Value::Number(n) if n.is_f64() => Shape::Float,
+        Value::Number(_) => Shape::Integer,
+        Value::String(_) => Shape::String,
+        Value::Array(items) => {
+            let element = items.iter().map(infer).reduce(merge).unwrap_or(Shape::Unknown);
+            Shape::Array(Box::new(element))
+        }
+        Value::Object(obj) => Shape::Object(
+            obj.iter()
+                .map(|(name, child)| Field {
+                    name: name.clone(),
+                    shape: infer(child),
+                    optional: false,
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// The narrowest shape that describes values of both `a` and `b`.
+pub fn merge(a: Shape, b: Shape) -> Shape {
+    match (a, b) {
+        (Shape::Nullable(a), Shape::Nullable(b)) => Shape::Nullable(Box::new(merge(*a, *b))),
+        (Shape::Nullable(a), b) | (b, Shape::Nullable(a)) => Shape::Nullable(Box::new(merge(*a, b))),
+        (Shape::Unknown, other) | (other, Shape::Unknown) => other,
+        (Shape::Integer, Shape::Float) | (Shape::Float, Shape::Integer) => Shape::Float,
+        (Shape::Array(a), Shape::Array(b)) => Shape::Array(Box::new(merge(*a, *b))),
+        (Shape::Object(a), Shape::Object(b)) => Shape::Object(merge_fields(a, b)),
+        (a, b) if a == b => a,
+        _ => Shape::Mixed,
+    }
+}
+
+fn merge_fields(a: Vec<Field>, mut b: Vec<Field>) -> Vec<Field> {
+    let mut merged = Vec::new();
+    for field in a {
+        match b.iter().position(|other| other.name == field.name) {
+            Some(i) => {
+                let other = b.remove(i);
+                merged.push(Field {
+                    name: field.name,
+                    shape: merge(field.shape, other.shape),
+                    optional: field.optional || other.optional,
+                });
+            }
+            None => merged.push(Field { optional: true, ..field }),
+        }
+    }
+    merged.extend(b.into_iter().map(|field| Field { optional: true, ..field }));
+    merged
+}
+
+/// Splits a key into lowercase words at separators and lower-to-upper case changes.
+fn words(key: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in key.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+pub fn pascal_case(key: &str) -> String {
+    let name: String = words(key)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect();
+    match name.chars().next() {
+        None => "Field".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("N{}", name),
+        Some(_) => name,
+    }
+}
+
+/// Crude singular for naming the element type of an array field: `users` -> `User`.
+pub fn singular(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies").filter(|stem| !stem.is_empty()) {
+        format!("{}y", stem)
+    } else if name.len() > 1 && name.ends_with('s') && !name.ends_with("ss") {
+        name[..name.len() - 1].to_string()
+    } else {
+        format!("{}Item", name)
+    }
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while", "yield",
+];
+
+fn rust_field_name(key: &str) -> String {
+    let name = words(key).join("_");
+    let name = match name.chars().next() {
+        None => "field".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("_{}", name),
+        Some(_) => name,
+    };
+    if RUST_KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// Allocates unique type names and collects struct definitions as they are generated.
+struct Definitions {
+    names: Vec<String>,
+    bodies: Vec<String>,
+    /// Fields each struct was generated from, so an object seen again with exactly the same
+    /// fields reuses the struct instead of getting a numbered copy.
+    fields: Vec<Vec<Field>>,
+}
+
+impl Definitions {
+    fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            bodies: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    fn existing(&self, fields: &[Field]) -> Option<&str> {
+        self.fields.iter().position(|seen| seen == fields).map(|i| self.names[i].as_str())
+    }
+
+    fn reserve(&mut self, wanted: &str, fields: &[Field]) -> usize {
+        let mut name = wanted.to_string();
+        let mut suffix = 2;
+        while self.names.contains(&name) {
+            name = format!("{}{}", wanted, suffix);
+            suffix += 1;
+        }
+        self.names.push(name);
+        self.bodies.push(String::new());
+        self.fields.push(fields.to_vec());
+        self.names.len() - 1
+    }
+}
+
+fn rust_type(shape: &Shape, name: &str, defs: &mut Definitions) -> String {
+    match shape {
+        Shape::Unknown | Shape::Mixed => "serde_json::Value".to_string(),
+        Shape::Bool => "bool".to_string(),
+        Shape::Integer => "i64".to_string(),
+        Shape::Float => "f64".to_string(),
+        Shape::String => "String".to_string(),
+        Shape::Nullable(inner) => format!("Option<{}>", rust_type(inner, name, defs)),
+        Shape::Array(element) => format!("Vec<{}>", rust_type(element, &singular(name), defs)),
+        Shape::Object(fields) => {
+            if let Some(existing) = defs.existing(fields) {
+                return existing.to_string();
+            }
+            let index = defs.reserve(name, fields);
+            let mut body = format!(
+                "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct {} {{\n",
+                defs.names[index]
+            );
+            let mut field_names: Vec<String> = Vec::new();
+            for field in fields {
+                // Keys like `user-id` and `user_id` would both become `user_id`
+                let wanted = rust_field_name(&field.name);
+                let mut field_name = wanted.clone();
+                let mut suffix = 2;
+                while field_names.contains(&field_name) {
+                    field_name = format!("{}_{}", wanted, suffix);
+                    suffix += 1;
+                }
+                let mut ty = rust_type(&field.shape, &pascal_case(&field.name), defs);
+                if field.optional && !matches!(field.shape, Shape::Nullable(_)) {
+                    ty = format!("Option<{}>", ty);
+                }
+                let mut attributes = Vec::new();
+                if field_name.trim_start_matches("r#") != field.name {
+                    attributes.push(format!("rename = {}", serde_json::to_string(&field.name).unwrap_or_default()));
+                }
+                // Left out when absent, so a missing field stays missing rather than becoming null
+                if field.optional {
+                    attributes.push("default, skip_serializing_if = \"Option::is_none\"".to_string());
+                }
+                if !attributes.is_empty() {
+                    body.push_str(&format!("    #[serde({})]\n", attributes.join(", ")));
+                }
+                body.push_str(&format!("    pub {}: {},\n", field_name, ty));
+                field_names.push(field_name);
+            }
+            body.push_str("}\n");
+            defs.bodies[index] = body;
+            defs.names[index].clone()
+        }
+    }
+}
+
+/// Serde structs describing `value`, with the top-level type named `root_name`.
+pub fn rust_structs(value: &Value, root_name: &str) -> String {
+    let root_name = pascal_case(root_name);
+    let mut defs = Definitions::new();
+    let root = rust_type(&infer(value), &root_name, &mut defs);
+
+    let mut out = String::from("use serde::{Deserialize, Serialize};\n\n");
+    if root != root_name {
+        out.push_str(&format!("pub type {} = {};\n\n", root_name, root));
+    }
+    out.push_str(&defs.bodies.join("\n"));
+    out
+}
+
+/// Whether `key` can be written as a TypeScript property name without quotes.
+fn is_ts_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+fn ts_type(shape: &Shape, name: &str, defs: &mut Definitions) -> String {
+    match shape {
+        Shape::Unknown | Shape::Mixed => "unknown".to_string(),
+        Shape::Bool => "boolean".to_string(),
+        Shape::Integer | Shape::Float => "number".to_string(),
+        Shape::String => "string".to_string(),
+        Shape::Nullable(inner) => format!("{} | null", ts_type(inner, name, defs)),
+        Shape::Array(element) => {
+            let element_type = ts_type(element, &singular(name), defs);
+            if element_type.contains(' ') {
+                format!("({})[]", element_type)
+            } else {
+                format!("{}[]", element_type)
+            }
+        }
+        Shape::Object(fields) => {
+            if let Some(existing) = defs.existing(fields) {
+                return existing.to_string();
+            }
+            let index = defs.reserve(name, fields);
+            let mut body = format!("export interface {} {{\n", defs.names[index]);
+            for field in fields {
+                let property = if is_ts_identifier(&field.name) {
+                    field.name.clone()
+                } else {
+                    serde_json::to_string(&field.name).unwrap_or_default()
+                };
+                let ty = ts_type(&field.shape, &pascal_case(&field.name), defs);
+                let optional = if field.optional { "?" } else { "" };
+                body.push_str(&format!("  {}{}: {};\n", property, optional, ty));
+            }
+            body.push_str("}\n");
+            defs.bodies[index] = body;
+            defs.names[index].clone()
+        }
+    }
+}
+
+/// TypeScript interfaces describing `value`, with the top-level type named `root_name`.
+pub fn typescript_interfaces(value: &Value, root_name: &str) -> String {
+    let root_name = pascal_case(root_name);
+    let mut defs = Definitions::new();
+    let root = ts_type(&infer(value), &root_name, &mut defs);
+
+    let mut out = String::new();
+    if root != root_name {
+        out.push_str(&format!("export type {} = {};\n\n", root_name, root));
+    }
+    out.push_str(&defs.bodies.join("\n"));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn element(value: Value) -> Shape {
+        match infer(&value) {
+            Shape::Array(element) => *element,
+            other => panic!("expected an array shape, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn integers_and_floats_widen_to_float() {
+        assert_eq!(element(json!([1, 2.5])), Shape::Float);
+    }
+
+    #[test]
+    fn different_types_are_mixed() {
+        assert_eq!(element(json!([1, "a"])), Shape::Mixed);
+    }
+
+    #[test]
+    fn null_makes_nullable() {
+        assert_eq!(element(json!([null, 1])), Shape::Nullable(Box::new(Shape::Integer)));
+        assert_eq!(element(json!([null])), Shape::Nullable(Box::new(Shape::Unknown)));
+    }
+
+    #[test]
+    fn fields_missing_from_some_elements_are_optional() {
+        let Shape::Object(fields) = element(json!([{"id": 1, "name": "a"}, {"id": 2}, {"id": 3, "tag": true}])) else {
+            panic!("expected an object shape");
+        };
+        let optional: Vec<(&str, bool)> = fields.iter().map(|field| (field.name.as_str(), field.optional)).collect();
+        assert_eq!(optional, [("id", false), ("name", true), ("tag", true)]);
+    }
+}
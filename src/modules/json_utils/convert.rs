@@ -0,0 +1,467 @@
+//! Conversions between JSON and other structured text formats.
+//!
+//! YAML and TOML map onto JSON directly through serde. CSV becomes an array with one object
+//! per row, keyed by the header row. XML has no native notion of arrays, types or
+//! attribute/child ordering, so the mapping is lossy. The rules used in both directions are:
+//!
+//! - The document becomes an object with a single key, the root element's name.
+//! - Attributes become keys prefixed with `@` (`<a id="1"/>` → `{"a": {"@id": "1"}}`).
+//! - An element containing only text becomes a string (`<a>hi</a>` → `{"a": "hi"}`).
+//! - Text in an element that also has attributes or children is stored under `#text`.
+//! - An empty element with no attributes becomes `null`.
+//! - Repeated sibling elements with the same name are collected into an array.
+//! - All text and attribute values stay strings; no number/bool inference is done.
+//!
+//! Going back to XML, the same rules apply in reverse. Scalars are written as text,
+//! `null` as an empty element, and arrays as repeated elements named after their key.
+//! A document that isn't an object with exactly one key is wrapped in `<root>`, with
+//! top-level array items written as `<item>`. Keys that aren't valid XML names have
+//! their invalid characters replaced by `_`.
+
+use color_eyre::{eyre::eyre, Result};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+struct XmlFrame {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl XmlFrame {
+    fn new(name: String, start: &BytesStart) -> Result<Self> {
+        let mut fields = Map::new();
+        for attr in start.attributes() {
+            let attr = attr?;
+            let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+            let value = attr.unescape_value()?.to_string();
+            fields.insert(format!("@{}", key), Value::String(value));
+        }
+        Ok(Self {
+            name,
+            fields,
+            text: String::new(),
+        })
+    }
+
+    fn into_value(self) -> Value {
+        let text = self.text.trim();
+        if self.fields.is_empty() {
+            if text.is_empty() {
+                Value::Null
+            } else {
+                Value::String(text.to_string())
+            }
+        } else {
+            let mut fields = self.fields;
+            if !text.is_empty() {
+                fields.insert("#text".to_string(), Value::String(text.to_string()));
+            }
+            Value::Object(fields)
+        }
+    }
+}
+
+fn insert_child(fields: &mut Map<String, Value>, name: String, value: Value) {
+    match fields.get_mut(&name) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            fields.insert(name, value);
+        }
+    }
+}
+
+pub fn xml_to_value(input: &str) -> Result<Value> {
+    let mut reader = Reader::from_str(input);
+    reader.config_mut().trim_text(true);
+
+    // The bottom frame collects the root element and is never closed by the document
+    let mut stack = vec![XmlFrame {
+        name: String::new(),
+        fields: Map::new(),
+        text: String::new(),
+    }];
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).to_string();
+                stack.push(XmlFrame::new(name, &start)?);
+            }
+            Event::Empty(start) => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).to_string();
+                let frame = XmlFrame::new(name, &start)?;
+                let parent = stack.last_mut().ok_or_else(|| eyre!("Unbalanced XML"))?;
+                let name = frame.name.clone();
+                insert_child(&mut parent.fields, name, frame.into_value());
+            }
+            Event::End(_) => {
+                if stack.len() < 2 {
+                    return Err(eyre!("Unexpected closing tag"));
+                }
+                let frame = stack.pop().ok_or_else(|| eyre!("Unbalanced XML"))?;
+                let parent = stack.last_mut().ok_or_else(|| eyre!("Unbalanced XML"))?;
+                let name = frame.name.clone();
+                insert_child(&mut parent.fields, name, frame.into_value());
+            }
+            Event::Text(text) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.text.push_str(&text.unescape()?);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.text.push_str(&String::from_utf8_lossy(&data.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if stack.len() != 1 {
+        return Err(eyre!("Unclosed element <{}>", stack[stack.len() - 1].name));
+    }
+    let root = stack.pop().map(|frame| frame.fields).unwrap_or_default();
+    if root.is_empty() {
+        return Err(eyre!("No root element found"));
+    }
+    Ok(Value::Object(root))
+}
+
+fn xml_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':') { c } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn write_element(out: &mut String, name: &str, value: &Value, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let name = xml_name(name);
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                write_element(out, &name, item, depth);
+            }
+        }
+        Value::Object(fields) => {
+            let mut attributes = String::new();
+            let mut text = None;
+            let mut children = Vec::new();
+            for (key, child) in fields {
+                if let Some(attr) = key.strip_prefix('@') {
+                    attributes.push_str(&format!(" {}=\"{}\"", xml_name(attr), escape(scalar_text(child))));
+                } else if key == "#text" {
+                    text = Some(scalar_text(child));
+                } else {
+                    children.push((key, child));
+                }
+            }
+
+            if children.is_empty() {
+                match text {
+                    Some(text) => out.push_str(&format!("{}<{}{}>{}</{}>\n", indent, name, attributes, escape(text), name)),
+                    None => out.push_str(&format!("{}<{}{}/>\n", indent, name, attributes)),
+                }
+            } else {
+                out.push_str(&format!("{}<{}{}>\n", indent, name, attributes));
+                if let Some(text) = text {
+                    out.push_str(&format!("{}  {}\n", indent, escape(text)));
+                }
+                for (key, child) in children {
+                    write_element(out, key, child, depth + 1);
+                }
+                out.push_str(&format!("{}</{}>\n", indent, name));
+            }
+        }
+        Value::Null => out.push_str(&format!("{}<{}/>\n", indent, name)),
+        scalar => out.push_str(&format!("{}<{}>{}</{}>\n", indent, name, escape(scalar_text(scalar)), name)),
+    }
+}
+
+pub fn value_to_xml(value: &Value) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    match value {
+        Value::Object(fields) if fields.len() == 1 && !fields.values().all(Value::is_array) => {
+            if let Some((name, root)) = fields.iter().next() {
+                write_element(&mut out, name, root, 0);
+            }
+        }
+        Value::Array(_) => {
+            out.push_str("<root>\n");
+            write_element(&mut out, "item", value, 1);
+            out.push_str("</root>\n");
+        }
+        other => write_element(&mut out, "root", other, 0),
+    }
+    out
+}
+
+/// A stream of several `---`-separated documents, as in a set of Kubernetes manifests,
+/// becomes an array with one element per document.
+pub fn yaml_to_value(input: &str) -> Result<Value> {
+    let mut documents = serde_yaml::Deserializer::from_str(input)
+        .map(Value::deserialize)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(match documents.len() {
+        0 => Value::Null,
+        1 => documents.remove(0),
+        _ => Value::Array(documents),
+    })
+}
+
+pub fn value_to_yaml(value: &Value) -> Result<String> {
+    Ok(serde_yaml::to_string(value)?)
+}
+
+pub fn toml_to_value(input: &str) -> Result<Value> {
+    let value: toml::Value = toml::from_str(input)?;
+    Ok(serde_json::to_value(value)?)
+}
+
+/// Serializes to TOML, which needs a table at the top and has no `null`.
+pub fn value_to_toml(value: &Value) -> Result<String> {
+    if !value.is_object() {
+        return Err(eyre!("TOML needs an object at the top level, not {}", kind(value)));
+    }
+    if let Some(path) = first_null(value, String::new()) {
+        return Err(eyre!("TOML has no null, found one at {}", path));
+    }
+    Ok(toml::to_string_pretty(value)?)
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// jq path of the first `null` in document order.
+fn first_null(value: &Value, path: String) -> Option<String> {
+    match value {
+        Value::Null => Some(if path.is_empty() { ".".to_string() } else { path }),
+        Value::Object(fields) => fields.iter().find_map(|(key, child)| {
+            let key = if super::is_jq_identifier(key) { key.clone() } else { serde_json::to_string(key).unwrap_or_default() };
+            first_null(child, format!("{}.{}", path, key))
+        }),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .find_map(|(i, child)| first_null(child, format!("{}[{}]", path, i))),
+        _ => None,
+    }
+}
+
+/// Writes an array of objects as CSV. The header is the union of the rows' keys in the
+/// order they first appear, so rows missing a key get an empty cell. Strings are written
+/// as-is, `null` as an empty cell, and nested arrays and objects as compact JSON.
+pub fn value_to_csv(value: &Value) -> Result<String> {
+    let Value::Array(items) = value else {
+        return Err(eyre!("CSV needs an array of objects, not {}", kind(value)));
+    };
+    let mut rows = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        match item {
+            Value::Object(row) => rows.push(row),
+            other => return Err(eyre!("item [{}] is {}, not an object", i, kind(other))),
+        }
+    }
+
+    let mut headers: Vec<&str> = Vec::new();
+    for row in &rows {
+        for key in row.keys() {
+            if !headers.contains(&key.as_str()) {
+                headers.push(key);
+            }
+        }
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&headers)?;
+    for row in rows {
+        writer.write_record(headers.iter().map(|key| match row.get(*key) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        }))?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Parses CSV with a header row into an array of objects. With `infer_types`, cells that
+/// look like booleans or numbers become them and empty cells become `null`; numbers with
+/// leading zeros (zip codes, ids) stay strings either way. Cells beyond the header are keyed
+/// `column_N`.
+pub fn csv_to_value(input: &str, infer_types: bool) -> Result<Value> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(input.as_bytes());
+    let headers: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+    if headers.is_empty() {
+        return Err(eyre!("no header row"));
+    }
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let mut row = Map::new();
+        for (i, cell) in record.iter().enumerate() {
+            let key = headers.get(i).cloned().unwrap_or_else(|| format!("column_{}", i + 1));
+            let value = if infer_types { infer_cell(cell) } else { Value::String(cell.to_string()) };
+            row.insert(key, value);
+        }
+        rows.push(Value::Object(row));
+    }
+    Ok(Value::Array(rows))
+}
+
+fn infer_cell(cell: &str) -> Value {
+    let trimmed = cell.trim();
+    match trimmed {
+        "" => return Value::Null,
+        "true" | "TRUE" | "True" => return Value::Bool(true),
+        "false" | "FALSE" | "False" => return Value::Bool(false),
+        _ => {}
+    }
+    let digits = trimmed.trim_start_matches('-');
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    if !leading_zero {
+        if let Ok(n) = trimmed.parse::<i64>() {
+            return Value::from(n);
+        }
+        if let Some(n) = trimmed.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+            if trimmed.chars().all(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+                return Value::Number(n);
+            }
+        }
+    }
+    Value::String(cell.to_string())
+}
+
+/// Whether `input` opens like a JSON object or array, so was most likely meant as JSON.
+pub fn looks_like_json(input: &str) -> bool {
+    input.trim_start_matches('\u{feff}').trim_start().starts_with(['{', '['])
+}
+
+/// YAML reading of `input` as a mapping or sequence. Almost any plain text is a valid YAML
+/// scalar, so only those count; otherwise a sentence pasted by mistake would "succeed" as a
+/// string.
+pub fn yaml_container(input: &str) -> Option<Value> {
+    yaml_to_value(input).ok().filter(|value| value.is_object() || value.is_array())
+}
+
+/// Tries the non-JSON structured formats in order, returning the first that parses.
+///
+/// YAML accepts most near-JSON (`{a: 1}`, trailing commas, single quotes), so input that
+/// looks like JSON is never read as YAML: a typo in it should show the JSON error. TOML is
+/// still tried, since its tables open with `[`, but it's strict enough not to take JSON.
+pub fn detect_structured(input: &str) -> Option<(&'static str, Value)> {
+    if !looks_like_json(input) {
+        if let Some(value) = yaml_container(input) {
+            return Some(("YAML", value));
+        }
+    }
+    if let Ok(value) = toml_to_value(input) {
+        return Some(("TOML", value));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn attributes_become_at_keys() {
+        let value = xml_to_value(r#"<a id="1" kind="x"/>"#).unwrap();
+        assert_eq!(value, json!({"a": {"@id": "1", "@kind": "x"}}));
+    }
+
+    #[test]
+    fn text_beside_attributes_goes_under_text_key() {
+        let value = xml_to_value(r#"<a lang="en">hi</a>"#).unwrap();
+        assert_eq!(value, json!({"a": {"@lang": "en", "#text": "hi"}}));
+        assert_eq!(xml_to_value("<a>hi</a>").unwrap(), json!({"a": "hi"}));
+        assert_eq!(xml_to_value("<a></a>").unwrap(), json!({"a": null}));
+    }
+
+    #[test]
+    fn repeated_elements_become_an_array() {
+        let value = xml_to_value("<list><item>1</item><item>2</item><item>3</item><other/></list>").unwrap();
+        assert_eq!(value, json!({"list": {"item": ["1", "2", "3"], "other": null}}));
+    }
+
+    #[test]
+    fn xml_round_trips() {
+        let value = json!({
+            "catalog": {
+                "@version": "2",
+                "book": [
+                    {"@id": "b1", "title": "First", "note": {"@lang": "en", "#text": "a & b"}},
+                    {"@id": "b2", "title": "Second", "empty": null}
+                ]
+            }
+        });
+        let xml = value_to_xml(&value);
+        assert_eq!(xml_to_value(&xml).unwrap(), value);
+    }
+
+    #[test]
+    fn yaml_round_trips() {
+        let documents = [
+            json!({"name": "app", "replicas": 3, "ratio": 0.25, "enabled": true, "owner": null}),
+            json!({"nested": {"list": [1, "two", {"three": [3.5, false]}], "empty_list": [], "empty_map": {}}}),
+            // Strings YAML would otherwise read as other types must come back as strings
+            json!({"quoted": ["yes", "no", "null", "1.0", "0x1F", "~", "", " padded ", "multi\nline", "a: b", "- dash"]}),
+            json!([{"id": 1}, {"id": 2, "tags": ["x"]}]),
+            json!("just a string"),
+            json!(-42),
+        ];
+        for document in documents {
+            let yaml = value_to_yaml(&document).unwrap();
+            assert_eq!(yaml_to_value(&yaml).unwrap(), document, "via YAML:\n{}", yaml);
+        }
+    }
+
+    #[test]
+    fn yaml_stream_becomes_an_array() {
+        let value = yaml_to_value("a: 1\n---\nb: 2\n").unwrap();
+        assert_eq!(value, json!([{"a": 1}, {"b": 2}]));
+    }
+
+    #[test]
+    fn near_json_is_not_taken_for_yaml() {
+        for typo in ["{a: 1}", "{\"a\": 1,}", "['a', 'b']", "[1, 2,]"] {
+            assert!(detect_structured(typo).is_none(), "{}", typo);
+            assert!(looks_like_json(typo));
+        }
+        assert_eq!(yaml_container("{a: 1}"), Some(json!({"a": 1})));
+        assert_eq!(detect_structured("a: 1\nb: [x]").unwrap(), ("YAML", json!({"a": 1, "b": ["x"]})));
+        assert_eq!(detect_structured("[server]\nport = 80").unwrap(), ("TOML", json!({"server": {"port": 80}})));
+        assert!(detect_structured("just a sentence").is_none());
+    }
+}
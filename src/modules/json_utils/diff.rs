@@ -0,0 +1,129 @@
+//! Structural diff between two documents.
+//!
+//! Objects are compared key by key and arrays index by index, so an insertion in the
+//! middle of an array shows up as changes to every later element. Numbers compare by
+//! value (`1` equals `1.0`).
+
+use serde_json::Value;
+
+use super::canonical;
+use super::is_jq_identifier;
+
+pub enum Change {
+    Added(Value),
+    Removed(Value),
+    Changed(Value, Value),
+}
+
+/// Equality as the diff sees it, with numbers compared by value.
+pub fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(_), Value::Number(_)) => canonical::to_canonical_string(a) == canonical::to_canonical_string(b),
+        _ => a == b,
+    }
+}
+
+fn key_path(path: &str, key: &str) -> String {
+    if is_jq_identifier(key) {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}.{}", path, serde_json::to_string(key).unwrap_or_default())
+    }
+}
+
+fn walk(path: &str, old: &Value, new: &Value, changes: &mut Vec<(String, Change)>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, old_child) in a {
+                let child_path = key_path(path, key);
+                match b.get(key) {
+                    Some(new_child) => walk(&child_path, old_child, new_child, changes),
+                    None => changes.push((child_path, Change::Removed(old_child.clone()))),
+                }
+            }
+            for (key, new_child) in b {
+                if !a.contains_key(key) {
+                    changes.push((key_path(path, key), Change::Added(new_child.clone())));
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let child_path = format!("{}[{}]", path, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(old_child), Some(new_child)) => walk(&child_path, old_child, new_child, changes),
+                    (Some(old_child), None) => changes.push((child_path, Change::Removed(old_child.clone()))),
+                    (None, Some(new_child)) => changes.push((child_path, Change::Added(new_child.clone()))),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if same(old, new) => {}
+        _ => changes.push((path.to_string(), Change::Changed(old.clone(), new.clone()))),
+    }
+}
+
+/// Every difference from `old` to `new`, keyed by jq path, in document order.
+pub fn diff(old: &Value, new: &Value) -> Vec<(String, Change)> {
+    let mut changes = Vec::new();
+    walk("", old, new, &mut changes);
+    for (path, _) in &mut changes {
+        if path.is_empty() || path.starts_with('[') {
+            path.insert(0, '.');
+        }
+    }
+    changes
+}
+
+/// One line per change: `+ path: value`, `- path: value` or `~ path: old -> new`.
+pub fn render(changes: &[(String, Change)]) -> String {
+    let compact = |value: &Value| serde_json::to_string(value).unwrap_or_default();
+    changes
+        .iter()
+        .map(|(path, change)| match change {
+            Change::Added(value) => format!("+ {}: {}", path, compact(value)),
+            Change::Removed(value) => format!("- {}: {}", path, compact(value)),
+            Change::Changed(old, new) => format!("~ {}: {} -> {}", path, compact(old), compact(new)),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rendered(old: Value, new: Value) -> String {
+        render(&diff(&old, &new))
+    }
+
+    #[test]
+    fn objects_are_compared_key_by_key() {
+        assert_eq!(
+            rendered(json!({"a": 1, "b": {"c": true}, "gone": 0}), json!({"a": 2, "b": {"c": true}, "new": [1]})),
+            "~ .a: 1 -> 2\n- .gone: 0\n+ .new: [1]"
+        );
+        assert_eq!(rendered(json!({"a key": 1}), json!({"a key": 2})), "~ .\"a key\": 1 -> 2");
+    }
+
+    #[test]
+    fn arrays_are_compared_index_by_index() {
+        assert_eq!(rendered(json!([1, 2]), json!([1, 3, 4])), "~ .[1]: 2 -> 3\n+ .[2]: 4");
+        assert_eq!(rendered(json!({"a": [1, 2, 3]}), json!({"a": [1]})), "- .a[1]: 2\n- .a[2]: 3");
+        // An insertion shifts every later element
+        assert_eq!(rendered(json!([1, 2]), json!([0, 1, 2])), "~ .[0]: 1 -> 0\n~ .[1]: 2 -> 1\n+ .[2]: 2");
+    }
+
+    #[test]
+    fn numbers_compare_by_value() {
+        assert!(diff(&json!({"n": 1, "m": [1e2]}), &json!({"n": 1.0, "m": [100]})).is_empty());
+        assert_eq!(rendered(json!(1), json!("1")), "~ .: 1 -> \"1\"");
+    }
+
+    #[test]
+    fn a_type_change_is_one_change() {
+        assert_eq!(rendered(json!({"a": [1]}), json!({"a": {"0": 1}})), "~ .a: [1] -> {\"0\":1}");
+        assert!(diff(&json!({"a": [1, {"b": null}]}), &json!({"a": [1, {"b": null}]})).is_empty());
+    }
+}
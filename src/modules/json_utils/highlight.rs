@@ -0,0 +1,133 @@
+//! Syntax coloring for the raw view.
+//!
+//! serde_json's pretty output puts at most one key and one scalar on a line, so a line can be
+//! tokenized on its own without tracking nesting: a string followed by `:` is a key,
+//! anything else is a value or punctuation. The same holds for the minified form, which is
+//! one long line; only the part scrolled into view is colored.
+
+use ratatui::prelude::*;
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+const KEY: Color = Color::Cyan;
+const STRING: Color = Color::Green;
+const NUMBER: Color = Color::Yellow;
+const BOOLEAN: Color = Color::Magenta;
+const NULL: Color = Color::Red;
+const PUNCTUATION: Color = Color::DarkGray;
+
+/// Byte ranges of the tokens in `text` with their colors, or `None` for whitespace.
+fn tokens(text: &str) -> impl Iterator<Item = (Range<usize>, Option<Color>)> + '_ {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        if i >= bytes.len() {
+            return None;
+        }
+        let start = i;
+        let color = match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(bytes.len());
+                Some(if text[i..].trim_start().starts_with(':') { KEY } else { STRING })
+            }
+            b'{' | b'}' | b'[' | b']' | b',' | b':' => {
+                i += 1;
+                Some(PUNCTUATION)
+            }
+            byte if byte.is_ascii_whitespace() => {
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                None
+            }
+            _ => {
+                while i < bytes.len() && !b"{}[],: \t\"".contains(&bytes[i]) {
+                    i += 1;
+                }
+                // Anything unrecognized is advanced over a whole char so slicing stays valid
+                if i == start {
+                    i += text[start..].chars().next().map_or(1, char::len_utf8);
+                }
+                Some(match &text[start..i] {
+                    "true" | "false" => BOOLEAN,
+                    "null" => NULL,
+                    _ => NUMBER,
+                })
+            }
+        };
+        Some((start..i, color))
+    })
+}
+
+fn span(text: &str, range: Range<usize>, color: Option<Color>) -> Span<'_> {
+    match color {
+        Some(color) => Span::styled(&text[range], Style::default().fg(color)),
+        None => Span::raw(&text[range]),
+    }
+}
+
+/// One line of JSON text split into colored spans.
+pub fn line(text: &str) -> Line<'_> {
+    Line::from(tokens(text).map(|(range, color)| span(text, range, color)).collect::<Vec<_>>())
+}
+
+/// Like [`line`], with `marks` (ascending, non-overlapping byte ranges) styled over the
+/// token colors, for search matches.
+pub fn line_with_marks<'a>(text: &'a str, marks: &[(Range<usize>, Style)]) -> Line<'a> {
+    let mut spans = Vec::new();
+    let mut marks = marks.iter().peekable();
+    for (range, color) in tokens(text) {
+        let base = color.map_or_else(Style::default, |color| Style::default().fg(color));
+        let mut at = range.start;
+        while at < range.end {
+            // Drop marks that ended before this point
+            while marks.next_if(|(mark, _)| mark.end <= at).is_some() {}
+            let (end, style) = match marks.peek() {
+                Some((mark, style)) if mark.start <= at => (mark.end.min(range.end), base.patch(*style)),
+                Some((mark, _)) => (mark.start.min(range.end), base),
+                None => (range.end, base),
+            };
+            spans.push(Span::styled(&text[at..end], style));
+            at = end;
+        }
+    }
+    Line::from(spans)
+}
+
+/// The part of the single line `text` from display column `skip` on, `width` columns wide,
+/// colored. Tokens cut by the edges keep their color.
+pub fn window(text: &str, skip: usize, width: usize) -> Line<'_> {
+    let (mut start, mut end) = (text.len(), text.len());
+    let mut column = 0;
+    for (i, c) in text.char_indices() {
+        if column >= skip && start == text.len() {
+            start = i;
+        }
+        if column >= skip + width {
+            end = i;
+            break;
+        }
+        column += c.width().unwrap_or(0);
+    }
+    let spans: Vec<Span> = tokens(text)
+        .skip_while(|(range, _)| range.end <= start)
+        .take_while(|(range, _)| range.start < end)
+        .map(|(range, color)| span(text, range.start.max(start)..range.end.min(end), color))
+        .collect();
+    Line::from(spans)
+}
+
+/// `text` as lines, colored only from `visible.start` to `visible.end`. Lines outside are
+/// left plain: they still count towards wrapping and scrolling but aren't drawn.
+pub fn lines(text: &str, visible: Range<usize>) -> Text<'_> {
+    let lines: Vec<Line> = text
+        .lines()
+        .enumerate()
+        .map(|(i, content)| if visible.contains(&i) { line(content) } else { Line::from(content) })
+        .collect();
+    Text::from(lines)
+}
@@ -0,0 +1,174 @@
+//! Key hints shown in view titles, rendered through the keymap so they name the keys
+//! actually bound. Actions sharing an entry are shown as `key/key: description`.
+
+use super::keymap::Action;
+
+pub type Hints = &'static [(&'static [Action], &'static str)];
+
+/// Raw view of a valid document.
+pub const VALID_RAW: Hints = &[
+    (&[Action::Paste], "paste"),
+    (&[Action::OpenFile], "open"),
+    (&[Action::OpenEditor], "editor"),
+    (&[Action::ToggleView], "tree"),
+    (&[Action::InsertMode], "type"),
+    (&[Action::Search], "search"),
+    (&[Action::ToggleLenientMode], "lenient (JSON5)"),
+    (&[Action::CycleIndent], "indent"),
+    (&[Action::ToggleMinified], "minified"),
+    (&[Action::ToggleAnnotations], "annotate"),
+    (&[Action::VisualSelect], "select"),
+    (&[Action::Undo, Action::Redo], "undo/redo"),
+    (&[Action::SortKeys], "sort keys"),
+    (&[Action::SortArraysBy], "sort arrays by key"),
+    (&[Action::ExtractValues], "extract values by key"),
+    (&[Action::CopyRustStructs, Action::CopyTypeScript], "Rust structs/TypeScript"),
+    (&[Action::Canonicalize], "canonical JSON"),
+    (&[Action::FindRepeated], "repeated subtrees"),
+    (&[Action::CompareFile, Action::CompareClipboard], "diff with file/clipboard"),
+    (&[Action::PatchFromClipboard, Action::ApplyPatch], "JSON Patch from/apply clipboard"),
+    (&[Action::CopyLeaves, Action::CopyLeavesWithPaths], "copy leaf values (with paths)"),
+    (&[Action::Flatten], "flatten"),
+    (&[Action::UnwrapStringified], "unwrap JSON strings"),
+    (&[Action::ToggleMask], "mask secrets"),
+    (&[Action::CopyRedacted], "copy redacted"),
+    (&[Action::ToXml, Action::ToYaml, Action::ToToml], "to XML/YAML/TOML"),
+    (&[Action::PasteXml, Action::PasteYaml, Action::PasteToml], "paste XML/YAML/TOML"),
+    (&[Action::PasteCsv, Action::PasteCsvStrings], "paste CSV typed/as strings"),
+    (&[Action::Copy], "copy"),
+    (&[Action::CopyMinified], "copy minified"),
+    (&[Action::ExportFile, Action::ExportMinified], "write pretty/minified"),
+    (&[Action::ExportCsv], "export CSV"),
+    (&[Action::LoadSchema], "JSON Schema"),
+    (&[Action::NavUp, Action::NavDown], "scroll"),
+    (&[Action::PageUp, Action::PageDown, Action::ScrollTop, Action::ScrollBottom], "page"),
+    (&[Action::ShowStats], "stats"),
+    (&[Action::Help], "help"),
+    (&[Action::Quit], "quit"),
+];
+
+/// Minified raw view.
+pub const MINIFIED: Hints = &[
+    (&[Action::Paste], "paste"),
+    (&[Action::OpenFile], "open"),
+    (&[Action::OpenEditor], "editor"),
+    (&[Action::ToggleView], "tree"),
+    (&[Action::ToggleMinified], "pretty"),
+    (&[Action::Copy], "copy"),
+    (&[Action::CopyMinified], "copy minified"),
+    (&[Action::ExportFile, Action::ExportMinified], "write pretty/minified"),
+    (&[Action::ScrollLeft, Action::ScrollRight], "scroll"),
+    (&[Action::Quit], "quit"),
+];
+
+/// While a temp file is out for editing.
+pub const TEMP_FILE: Hints = &[
+    (&[Action::CopyTempPath], "copy path"),
+    (&[Action::RevealTempFile], "reveal"),
+    (&[Action::ForgetTempFile], "delete temp file"),
+    (&[Action::Paste], "paste"),
+    (&[Action::OpenFile], "open"),
+    (&[Action::OpenEditor], "editor"),
+    (&[Action::ToggleView], "tree view"),
+    (&[Action::Quit], "quit"),
+];
+
+/// Raw view with nothing valid loaded.
+pub const EMPTY_RAW: Hints = &[
+    (&[Action::Paste], "paste"),
+    (&[Action::OpenFile], "open"),
+    (&[Action::InsertMode], "type"),
+    (&[Action::ValidateOnly], "validate only"),
+    (&[Action::PasteXml, Action::PasteYaml, Action::PasteToml], "paste XML/YAML/TOML"),
+    (&[Action::PasteCsv, Action::PasteCsvStrings], "paste CSV typed/as strings"),
+    (&[Action::ToggleFragmentMode], "fragment mode"),
+    (&[Action::ToggleLenientMode], "lenient (JSON5)"),
+    (&[Action::OpenEditor], "editor"),
+    (&[Action::ToggleView], "tree view"),
+    (&[Action::Help], "help"),
+    (&[Action::Quit], "quit"),
+];
+
+/// Visual selection before the start is marked.
+pub const VISUAL_MOVE: Hints = &[
+    (&[Action::ScrollLeft, Action::NavDown, Action::NavUp, Action::ScrollRight], "move cursor"),
+    (&[Action::VisualSelect], "mark start"),
+    (&[Action::Copy], "copy character"),
+    (&[Action::Quit], "cancel"),
+];
+
+/// Visual selection once the start is marked.
+pub const VISUAL_EXTEND: Hints = &[
+    (&[Action::ScrollLeft, Action::NavDown, Action::NavUp, Action::ScrollRight], "extend"),
+    (&[Action::VisualSelect], "clear mark"),
+    (&[Action::Copy], "copy selection"),
+    (&[Action::Quit], "cancel"),
+];
+
+/// Tree view.
+pub const TREE: Hints = &[
+    (&[Action::Paste], "paste"),
+    (&[Action::OpenFile], "open"),
+    (&[Action::OpenEditor], "editor"),
+    (&[Action::ToggleView], "raw"),
+    (&[Action::Copy], "copy"),
+    (&[Action::CopyMinified], "copy minified"),
+    (&[Action::ExportFile, Action::ExportMinified], "write pretty/minified"),
+    (&[Action::ExportCsv], "export CSV"),
+    (&[Action::ToXml, Action::ToYaml, Action::ToToml], "to XML/YAML/TOML"),
+    (&[Action::Flatten], "flatten"),
+    (&[Action::UnwrapStringified], "unwrap JSON strings"),
+    (&[Action::LoadSchema], "JSON Schema"),
+    (&[Action::ToggleNode], "expand"),
+    (&[Action::Search], "search"),
+    (&[Action::GoToPath], "go to path"),
+    (&[Action::Bookmark], "bookmark"),
+    (&[Action::PrevBookmark, Action::NextBookmark], "prev/next bookmark"),
+    (&[Action::CopyValue, Action::CopySubtreeMinified], "copy subtree pretty/minified"),
+    (&[Action::CopyPath], "copy path"),
+    (&[Action::CopyJqPath], "copy jq path"),
+    (&[Action::MoveElementDown, Action::MoveElementUp], "move element"),
+    (&[Action::EditValue], "edit value"),
+    (&[Action::DeleteNode], "delete"),
+    (&[Action::Undo, Action::Redo], "undo/redo"),
+    (&[Action::SortKeys], "sort keys"),
+    (&[Action::ExpandAll, Action::CollapseAll], "expand/collapse all"),
+    (&[Action::ExpandToDepth], "expand to depth"),
+    (&[Action::ExpandLevel, Action::CollapseLevel], "level deeper/shallower"),
+    (&[Action::ExpandSubtree, Action::CollapseSubtree], "expand/collapse subtree"),
+    (&[Action::ToggleCopyOnSelect], "copy-on-select"),
+    (&[Action::CycleNumberBase], "number base"),
+    (&[Action::ToggleSplit], "split"),
+    (&[Action::ToggleMask], "mask secrets"),
+    (&[Action::NavUp, Action::NavDown], "navigate"),
+    (&[Action::ShowStats], "stats"),
+    (&[Action::Help], "help"),
+    (&[Action::Quit], "quit"),
+];
+
+/// Converted output.
+pub const CONVERTED: Hints = &[
+    (&[Action::Copy], "copy"),
+    (&[Action::ToggleView], "back to raw"),
+    (&[Action::NavUp, Action::NavDown], "scroll"),
+    (&[Action::Quit], "quit"),
+];
+
+/// Flattened `path = value` listing.
+pub const FLAT: Hints = &[
+    (&[Action::Copy], "copy shown lines"),
+    (&[Action::Search], "filter"),
+    (&[Action::SortKeys], "sort by value/path"),
+    (&[Action::ToggleView], "back to raw"),
+    (&[Action::NavUp, Action::NavDown], "scroll"),
+    (&[Action::Quit], "quit"),
+];
+
+/// Side-by-side diff.
+pub const DIFF: Hints = &[
+    (&[Action::NavUp, Action::NavDown], "prev/next change"),
+    (&[Action::ScrollLeft, Action::ScrollRight], "scroll sideways"),
+    (&[Action::Copy], "copy"),
+    (&[Action::ToggleView], "back to raw"),
+    (&[Action::Quit], "quit"),
+];
@@ -0,0 +1,52 @@
+//! Undo and redo over document states.
+//!
+//! A snapshot is taken before each change to the document. It keeps the shown value as well
+//! as the text, because some changes (sorting keys, sorting arrays) only change the view and
+//! leave `raw_input` as pasted.
+
+use serde_json::Value;
+use std::collections::VecDeque;
+
+/// Undo steps kept; the oldest is dropped beyond this.
+const MAX_UNDO: usize = 50;
+
+#[derive(Clone, PartialEq)]
+pub struct Snapshot {
+    pub raw_input: String,
+    /// The document as shown, or `None` when it didn't parse.
+    pub value: Option<Value>,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl History {
+    /// Records the state about to be changed. A new change abandons anything undone.
+    pub fn record(&mut self, snapshot: Snapshot) {
+        self.redo.clear();
+        if self.undo.back() == Some(&snapshot) {
+            return;
+        }
+        if self.undo.len() == MAX_UNDO {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(snapshot);
+    }
+
+    /// The state before `current`, which becomes redoable.
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// The state `undo` last left, with `current` going back on the undo stack.
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+        Some(next)
+    }
+}
@@ -0,0 +1,491 @@
+//! Key bindings for the JSON viewer, overridable from `keymap.toml` in the config dir.
+//!
+//! The file maps action names to one key chord or a list of them, e.g.
+//!
+//! ```toml
+//! paste = "p"
+//! copy = ["c", "ctrl+y"]
+//! nav_down = ["down", "j"]
+//! ```
+//!
+//! Actions left out of the file keep their default keys. Entries with an unknown action
+//! or an unparsable chord are skipped and reported as warnings. View titles name whatever
+//! keys are bound, so they follow the file too.
+//!
+//! A few default keys are shared by an action that only applies in some states and the
+//! key's ordinary action, e.g. `e` edits a tree leaf in place but creates the temp file
+//! elsewhere; the viewer picks between them. A key given to an action in the file does
+//! only that.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Quit,
+    Paste,
+    OpenFile,
+    ValidateOnly,
+    PasteXml,
+    PasteYaml,
+    PasteToml,
+    PasteCsv,
+    PasteCsvStrings,
+    OpenEditor,
+    CreateTempFile,
+    EditValue,
+    ToggleView,
+    ToggleMinified,
+    ToggleAnnotations,
+    ToXml,
+    ToYaml,
+    ToToml,
+    Copy,
+    CopyMinified,
+    CopyLeaves,
+    CopyLeavesWithPaths,
+    Flatten,
+    UnwrapStringified,
+    NavUp,
+    NavDown,
+    ScrollLeft,
+    ScrollRight,
+    PageUp,
+    PageDown,
+    ScrollTop,
+    ScrollBottom,
+    ToggleNode,
+    Search,
+    GoToPath,
+    NextMatch,
+    PrevMatch,
+    Bookmark,
+    NextBookmark,
+    PrevBookmark,
+    CopyPath,
+    CopyJqPath,
+    CopySubtreeMinified,
+    CopyValue,
+    MoveElementUp,
+    MoveElementDown,
+    DeleteNode,
+    CycleNumberBase,
+    ToggleFragmentMode,
+    ToggleLenientMode,
+    CycleIndent,
+    VisualSelect,
+    InsertMode,
+    ToggleSplit,
+    ToggleMask,
+    CopyRedacted,
+    Undo,
+    Redo,
+    SortKeys,
+    SortArraysBy,
+    ExpandAll,
+    CollapseAll,
+    ExpandToDepth,
+    ExpandLevel,
+    CollapseLevel,
+    ExpandSubtree,
+    CollapseSubtree,
+    ToggleCopyOnSelect,
+    ExtractValues,
+    CopyRustStructs,
+    CopyTypeScript,
+    Canonicalize,
+    FindRepeated,
+    CompareFile,
+    CompareClipboard,
+    PatchFromClipboard,
+    ApplyPatch,
+    ToggleWrap,
+    ExportFile,
+    ExportMinified,
+    ExportCsv,
+    LoadSchema,
+    SchemaScrollDown,
+    SchemaScrollUp,
+    CopyTempPath,
+    RevealTempFile,
+    ForgetTempFile,
+    ShowStats,
+    Help,
+}
+
+/// Every action with its name in the keymap file and its default chords.
+const ACTIONS: &[(Action, &str, &[&str])] = &[
+    (Action::Quit, "quit", &["q", "esc"]),
+    (Action::Paste, "paste", &["p"]),
+    (Action::OpenFile, "open_file", &["o"]),
+    (Action::ValidateOnly, "validate_only", &["V"]),
+    (Action::PasteXml, "paste_xml", &["X"]),
+    (Action::PasteYaml, "paste_yaml", &["alt+p"]),
+    (Action::PasteToml, "paste_toml", &["alt+t"]),
+    (Action::PasteCsv, "paste_csv", &[","]),
+    (Action::PasteCsvStrings, "paste_csv_strings", &[";"]),
+    (Action::OpenEditor, "open_editor", &["n"]),
+    (Action::CreateTempFile, "create_temp_file", &["e"]),
+    (Action::EditValue, "edit_value", &["e"]),
+    (Action::ToggleView, "toggle_view", &["t"]),
+    (Action::ToggleMinified, "toggle_minified", &["m"]),
+    (Action::ToggleAnnotations, "toggle_annotations", &["a"]),
+    (Action::ToXml, "to_xml", &["x"]),
+    (Action::ToYaml, "to_yaml", &["g"]),
+    (Action::ToToml, "to_toml", &["T"]),
+    (Action::Copy, "copy", &["c"]),
+    (Action::CopyMinified, "copy_minified", &["C"]),
+    (Action::CopyLeaves, "copy_leaves", &["L"]),
+    (Action::CopyLeavesWithPaths, "copy_leaves_with_paths", &["alt+l"]),
+    (Action::Flatten, "flatten", &["f"]),
+    (Action::UnwrapStringified, "unwrap_stringified", &["U"]),
+    (Action::NavUp, "nav_up", &["up", "k"]),
+    (Action::NavDown, "nav_down", &["down", "j"]),
+    (Action::ScrollLeft, "scroll_left", &["left", "h"]),
+    (Action::ScrollRight, "scroll_right", &["right", "l"]),
+    (Action::PageUp, "page_up", &["pageup"]),
+    (Action::PageDown, "page_down", &["pagedown"]),
+    (Action::ScrollTop, "scroll_top", &["home"]),
+    (Action::ScrollBottom, "scroll_bottom", &["end"]),
+    (Action::ToggleNode, "toggle_node", &["space", "enter"]),
+    (Action::Search, "search", &["/"]),
+    (Action::GoToPath, "go_to_path", &[":"]),
+    (Action::NextMatch, "next_match", &["n"]),
+    (Action::PrevMatch, "prev_match", &["N"]),
+    (Action::Bookmark, "bookmark", &["b"]),
+    (Action::NextBookmark, "next_bookmark", &["]"]),
+    (Action::PrevBookmark, "prev_bookmark", &["["]),
+    (Action::CopyPath, "copy_path", &["y"]),
+    (Action::CopyJqPath, "copy_jq_path", &["alt+y"]),
+    (Action::CopySubtreeMinified, "copy_subtree_minified", &["Y"]),
+    (Action::CopyValue, "copy_value", &["v"]),
+    (Action::MoveElementUp, "move_element_up", &["K"]),
+    (Action::MoveElementDown, "move_element_down", &["J"]),
+    (Action::DeleteNode, "delete_node", &["d", "delete"]),
+    (Action::CycleNumberBase, "cycle_number_base", &["#"]),
+    (Action::ToggleFragmentMode, "toggle_fragment_mode", &["{"]),
+    (Action::ToggleLenientMode, "toggle_lenient_mode", &["r"]),
+    (Action::CycleIndent, "cycle_indent", &["I"]),
+    (Action::VisualSelect, "visual_select", &["v"]),
+    (Action::InsertMode, "insert_mode", &["i"]),
+    (Action::ToggleSplit, "toggle_split", &["|"]),
+    (Action::ToggleMask, "toggle_mask", &["*"]),
+    (Action::CopyRedacted, "copy_redacted", &["R"]),
+    (Action::Undo, "undo", &["u"]),
+    (Action::Redo, "redo", &["ctrl+r"]),
+    (Action::SortKeys, "sort_keys", &["s"]),
+    (Action::SortArraysBy, "sort_arrays_by", &["O"]),
+    (Action::ExpandAll, "expand_all", &["E"]),
+    (Action::CollapseAll, "collapse_all", &["z"]),
+    (Action::ExpandToDepth, "expand_to_depth", &["Z"]),
+    (Action::ExpandLevel, "expand_level", &[">"]),
+    (Action::CollapseLevel, "collapse_level", &["<"]),
+    (Action::ExpandSubtree, "expand_subtree", &["alt+e"]),
+    (Action::CollapseSubtree, "collapse_subtree", &["alt+z"]),
+    (Action::ToggleCopyOnSelect, "toggle_copy_on_select", &["A"]),
+    (Action::ExtractValues, "extract_values", &["F"]),
+    (Action::CopyRustStructs, "copy_rust_structs", &["G"]),
+    (Action::CopyTypeScript, "copy_typescript", &["alt+g"]),
+    (Action::Canonicalize, "canonicalize", &["M"]),
+    (Action::FindRepeated, "find_repeated", &["D"]),
+    (Action::CompareFile, "compare_file", &["="]),
+    (Action::CompareClipboard, "compare_clipboard", &["+"]),
+    (Action::PatchFromClipboard, "patch_from_clipboard", &["alt+d"]),
+    (Action::ApplyPatch, "apply_patch", &["alt+a"]),
+    (Action::ToggleWrap, "toggle_wrap", &["W"]),
+    (Action::ExportFile, "export_file", &["w"]),
+    (Action::ExportMinified, "export_minified", &["ctrl+w"]),
+    (Action::ExportCsv, "export_csv", &["alt+w"]),
+    (Action::LoadSchema, "load_schema", &["S"]),
+    (Action::SchemaScrollDown, "schema_scroll_down", &["alt+j"]),
+    (Action::SchemaScrollUp, "schema_scroll_up", &["alt+k"]),
+    (Action::CopyTempPath, "copy_temp_path", &["P"]),
+    (Action::RevealTempFile, "reveal_temp_file", &["ctrl+o"]),
+    (Action::ForgetTempFile, "forget_temp_file", &["ctrl+x"]),
+    (Action::ShowStats, "show_stats", &["alt+s"]),
+    (Action::Help, "help", &["?"]),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// Parses chords like `p`, `C`, `ctrl+r`, `alt+enter`, `pageup` or `space`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // A bare "+" or a chord ending in "+" binds the plus key itself
+        let key = if text.ends_with('+') {
+            parts.truncate(parts.len().saturating_sub(2));
+            "+"
+        } else {
+            parts.pop()?
+        };
+        for part in parts {
+            modifiers |= match part.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let code = match key.to_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+        Some(Self::normalized(code, modifiers))
+    }
+
+    /// Terminals report `C` as Shift+`c` or plain `C` inconsistently, so shift is folded
+    /// into the character itself for `Char` keys.
+    fn normalized(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => Self {
+                code: KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            _ => Self { code, modifiers },
+        }
+    }
+
+    fn from_event(key: &KeyEvent) -> Self {
+        Self::normalized(key.code, key.modifiers)
+    }
+
+    /// How the chord is written in titles: `'p'` for plain characters, `Ctrl+r`, `PgUp`, `↑`.
+    fn label(&self) -> String {
+        if let (KeyCode::Char(c), true) = (self.code, self.modifiers.is_empty()) {
+            return if c == ' ' { "Space".to_string() } else { format!("'{}'", c) };
+        }
+        let mut label = String::new();
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                label.push_str(name);
+            }
+        }
+        let key = match self.code {
+            KeyCode::Esc => "Esc",
+            KeyCode::Enter => "Enter",
+            KeyCode::Tab => "Tab",
+            KeyCode::BackTab => "Shift+Tab",
+            KeyCode::Backspace => "Backspace",
+            KeyCode::Delete => "Del",
+            KeyCode::Up => "↑",
+            KeyCode::Down => "↓",
+            KeyCode::Left => "←",
+            KeyCode::Right => "→",
+            KeyCode::Home => "Home",
+            KeyCode::End => "End",
+            KeyCode::PageUp => "PgUp",
+            KeyCode::PageDown => "PgDn",
+            KeyCode::Char(' ') => "Space",
+            KeyCode::Char(c) => {
+                label.push(c);
+                return label;
+            }
+            _ => return self.to_string(),
+        };
+        label.push_str(key);
+        label
+    }
+}
+
+impl fmt::Display for KeyChord {
+    /// Writes the chord back in the syntax `parse` accepts.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::BackTab => f.write_str("backtab"),
+            KeyCode::Backspace => f.write_str("backspace"),
+            KeyCode::Delete => f.write_str("delete"),
+            KeyCode::Up => f.write_str("up"),
+            KeyCode::Down => f.write_str("down"),
+            KeyCode::Left => f.write_str("left"),
+            KeyCode::Right => f.write_str("right"),
+            KeyCode::Home => f.write_str("home"),
+            KeyCode::End => f.write_str("end"),
+            KeyCode::PageUp => f.write_str("pageup"),
+            KeyCode::PageDown => f.write_str("pagedown"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+pub struct Keymap {
+    /// Actions on each key, in `ACTIONS` order.
+    bindings: HashMap<KeyChord, Vec<Action>>,
+}
+
+impl Keymap {
+    pub fn defaults() -> Self {
+        let mut bindings = HashMap::new();
+        for (action, _, chords) in ACTIONS {
+            for chord in chords.iter().filter_map(|c| KeyChord::parse(c)) {
+                bindings.entry(chord).or_insert_with(Vec::new).push(*action);
+            }
+        }
+        Self { bindings }
+    }
+
+    pub fn file_path() -> Option<PathBuf> {
+        crate::config::config_dir().map(|dir| dir.join("keymap.toml"))
+    }
+
+    /// Loads the user's keymap on top of the defaults, returning warnings for bad entries.
+    pub fn load() -> (Self, Vec<String>) {
+        let mut keymap = Self::defaults();
+        let Some(path) = Self::file_path() else {
+            return (keymap, Vec::new());
+        };
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return (keymap, Vec::new());
+        };
+
+        let table: toml::Table = match toml::from_str(&contents) {
+            Ok(table) => table,
+            Err(e) => return (keymap, vec![format!("{}: {}", path.display(), e)]),
+        };
+        let warnings = keymap.apply(&table);
+        for warning in &warnings {
+            tracing::warn!("keymap: {}", warning);
+        }
+        (keymap, warnings)
+    }
+
+    fn apply(&mut self, table: &toml::Table) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (name, value) in table {
+            let Some((action, _, _)) = ACTIONS.iter().find(|(_, n, _)| n == name) else {
+                warnings.push(format!("unknown action '{}'", name));
+                continue;
+            };
+
+            let texts: Vec<&str> = match value {
+                toml::Value::String(s) => vec![s.as_str()],
+                toml::Value::Array(items) => items.iter().filter_map(toml::Value::as_str).collect(),
+                _ => {
+                    warnings.push(format!("'{}' must be a key or a list of keys", name));
+                    continue;
+                }
+            };
+            let chords: Vec<KeyChord> = texts.iter().filter_map(|t| KeyChord::parse(t)).collect();
+            if chords.len() != texts.len() || chords.is_empty() {
+                warnings.push(format!("invalid key for '{}', keeping the default", name));
+                continue;
+            }
+
+            for bound in self.bindings.values_mut() {
+                bound.retain(|bound| bound != action);
+            }
+            self.bindings.retain(|_, bound| !bound.is_empty());
+            for chord in chords {
+                self.bindings.insert(chord, vec![*action]);
+            }
+        }
+        warnings
+    }
+
+    /// Every action's name with the keys currently bound to it, in declaration order.
+    pub fn help_entries(&self) -> Vec<(&'static str, Vec<String>)> {
+        ACTIONS
+            .iter()
+            .map(|(action, name, _)| {
+                let mut keys: Vec<String> = self
+                    .bindings
+                    .iter()
+                    .filter(|(_, bound)| bound.contains(action))
+                    .map(|(chord, _)| chord.to_string())
+                    .collect();
+                keys.sort();
+                (*name, keys)
+            })
+            .collect()
+    }
+
+    /// The key shown for `action` in titles: its first default key still bound to it, or
+    /// else the first key the keymap file gave it.
+    fn primary_chord(&self, action: Action) -> Option<KeyChord> {
+        let defaults = ACTIONS
+            .iter()
+            .find(|(a, _, _)| *a == action)
+            .map_or(&[][..], |(_, _, chords)| *chords);
+        let default = defaults
+            .iter()
+            .filter_map(|c| KeyChord::parse(c))
+            .find(|chord| self.bindings.get(chord).is_some_and(|bound| bound.contains(&action)));
+        default.or_else(|| {
+            self.bindings
+                .iter()
+                .filter(|(_, bound)| bound.contains(&action))
+                .map(|(chord, _)| *chord)
+                .min_by_key(|chord| chord.to_string())
+        })
+    }
+
+    /// Key hints for a title, e.g. `'p': paste, ↑/↓: scroll`. Each entry lists actions that
+    /// share a description; their keys are joined with `/`. Entries whose actions are all
+    /// unbound are left out.
+    pub fn hints(&self, entries: &[(&[Action], &str)]) -> String {
+        entries
+            .iter()
+            .filter_map(|(actions, description)| {
+                let keys: Vec<String> = actions
+                    .iter()
+                    .filter_map(|action| self.primary_chord(*action))
+                    .map(|chord| chord.label())
+                    .collect();
+                (!keys.is_empty()).then(|| format!("{}: {}", keys.join("/"), description))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The actions bound to `key`, most often just one.
+    pub fn actions_for(&self, key: &KeyEvent) -> &[Action] {
+        self.bindings.get(&KeyChord::from_event(key)).map_or(&[], Vec::as_slice)
+    }
+}
@@ -0,0 +1,221 @@
+//! Relaxed input for JSONC and JSON5 documents: `//` and `/* */` comments, trailing commas,
+//! unquoted keys, single-quoted strings, and JSON5 number forms (`0x1F`, `+1`, `.5`, `5.`).
+//!
+//! Rather than parsing a dialect, the input is rewritten into strict JSON for serde_json.
+//! Comments and trailing commas become spaces (newlines are kept), so line numbers in parse
+//! errors still point into the text as pasted. Columns do too, up to the first quoted key
+//! or rewritten string or number on a line; those only grow by a few bytes, so a column
+//! further along that line is a little to the right of the actual error. `Infinity` and
+//! `NaN` have no JSON form and are left for serde_json to reject.
+
+/// `input` rewritten as strict JSON.
+pub fn strip(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    // Position in `out` of the last comma seen outside a string, cleared by any other token
+    let mut pending_comma: Option<usize> = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                pending_comma = None;
+                i = copy_string(input, i, quote, &mut out);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    out.push(b' ');
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = input[i + 2..].find("*/").map_or(bytes.len(), |offset| i + 2 + offset + 2);
+                out.extend(bytes[i..end].iter().map(|&byte| if byte == b'\n' { b'\n' } else { b' ' }));
+                i = end;
+            }
+            b',' => {
+                pending_comma = Some(out.len());
+                out.push(b',');
+                i += 1;
+            }
+            b'}' | b']' => {
+                if let Some(comma) = pending_comma.take() {
+                    out[comma] = b' ';
+                }
+                out.push(bytes[i]);
+                i += 1;
+            }
+            b'0'..=b'9' | b'.' | b'+' | b'-' => {
+                pending_comma = None;
+                i = copy_number(input, i, &mut out);
+            }
+            _ if starts_identifier(input, i) => {
+                pending_comma = None;
+                let end = identifier_end(input, i);
+                let word = &input[i..end];
+                if input[end..].trim_start().starts_with(':') {
+                    out.push(b'"');
+                    out.extend_from_slice(word.as_bytes());
+                    out.push(b'"');
+                } else {
+                    out.extend_from_slice(word.as_bytes());
+                }
+                i = end;
+            }
+            byte => {
+                if !byte.is_ascii_whitespace() {
+                    pending_comma = None;
+                }
+                let len = input[i..].chars().next().map_or(1, char::len_utf8);
+                out.extend_from_slice(&bytes[i..i + len]);
+                i += len;
+            }
+        }
+    }
+    // Bytes are only ever copied or replaced by ASCII at char boundaries, so this is UTF-8
+    String::from_utf8(out).unwrap_or_else(|_| input.to_string())
+}
+
+/// Copies the string starting at `start` as a double-quoted string, returning the index
+/// after its closing quote. In a single-quoted string, `\'` becomes `'` and a bare `"` is
+/// escaped; a backslash before a line break (a JSON5 line continuation) is dropped with it.
+fn copy_string(input: &str, start: usize, quote: u8, out: &mut Vec<u8>) -> usize {
+    let bytes = input.as_bytes();
+    out.push(b'"');
+    let mut i = start + 1;
+    while i < bytes.len() && bytes[i] != quote {
+        match bytes[i] {
+            b'\\' => match bytes.get(i + 1) {
+                Some(b'\n') => i += 2,
+                Some(b'\r') => i += if bytes.get(i + 2) == Some(&b'\n') { 3 } else { 2 },
+                Some(b'\'') => {
+                    out.push(b'\'');
+                    i += 2;
+                }
+                Some(&escaped) => {
+                    out.extend_from_slice(&[b'\\', escaped]);
+                    i += 2;
+                }
+                None => i += 1,
+            },
+            b'"' => {
+                out.extend_from_slice(b"\\\"");
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    if i < bytes.len() {
+        out.push(b'"');
+        i += 1;
+    }
+    i
+}
+
+/// Copies the number starting at `start`, returning the index after it. Hex integers are
+/// written in decimal, a leading `+` is dropped and a bare decimal point gets a `0`. Tokens
+/// that aren't JSON5 numbers are copied unchanged for serde_json to report.
+fn copy_number(input: &str, start: usize, out: &mut Vec<u8>) -> usize {
+    let bytes = input.as_bytes();
+    let mut end = start + 1;
+    while end < bytes.len() {
+        let byte = bytes[end];
+        let exponent_sign = matches!(byte, b'+' | b'-') && matches!(bytes[end - 1], b'e' | b'E');
+        if !(byte.is_ascii_alphanumeric() || byte == b'.' || exponent_sign) {
+            break;
+        }
+        end += 1;
+    }
+    let token = &input[start..end];
+    let (sign, magnitude) = match token.as_bytes()[0] {
+        b'-' => ("-", &token[1..]),
+        b'+' => ("", &token[1..]),
+        _ => ("", token),
+    };
+
+    let hex = magnitude.strip_prefix("0x").or_else(|| magnitude.strip_prefix("0X"));
+    let rewritten = match hex.map(|digits| u128::from_str_radix(digits, 16)) {
+        Some(Ok(n)) => format!("{}{}", sign, n),
+        Some(Err(_)) => token.to_string(),
+        None if magnitude.starts_with('.') => format!("{}0{}", sign, magnitude),
+        None => match magnitude.split_once('.') {
+            Some((whole, fraction)) if !whole.is_empty() && !fraction.starts_with(|c: char| c.is_ascii_digit()) => {
+                format!("{}{}.0{}", sign, whole, fraction)
+            }
+            _ => format!("{}{}", sign, magnitude),
+        },
+    };
+    out.extend_from_slice(rewritten.as_bytes());
+    end
+}
+
+/// Whether an ECMAScript identifier (an unquoted JSON5 key) starts at `i`.
+fn starts_identifier(input: &str, i: usize) -> bool {
+    input[i..].chars().next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+}
+
+fn identifier_end(input: &str, start: usize) -> usize {
+    input[start..]
+        .char_indices()
+        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .map_or(input.len(), |(offset, _)| start + offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn parse(input: &str) -> Value {
+        serde_json::from_str(&strip(input)).unwrap_or_else(|e| panic!("{}: {}", strip(input), e))
+    }
+
+    #[test]
+    fn comment_markers_inside_strings_are_kept() {
+        let input = r#"{"url": "http://example.com/*x*/", 'path': '//share', "end": "*/"} // done"#;
+        assert_eq!(parse(input), json!({"url": "http://example.com/*x*/", "path": "//share", "end": "*/"}));
+    }
+
+    #[test]
+    fn trailing_commas_are_dropped_after_comments() {
+        assert_eq!(parse("[1, 2, // two\n]"), json!([1, 2]));
+        assert_eq!(parse("{\"a\": 1, /* last */ }"), json!({"a": 1}));
+        assert_eq!(parse("{\"a\": [1,],\n// nothing after\n}"), json!({"a": [1]}));
+        // A comma followed by another value is kept
+        assert_eq!(parse("[1, /* gap */ 2]"), json!([1, 2]));
+    }
+
+    #[test]
+    fn json5_numbers_are_rewritten() {
+        assert_eq!(
+            parse("[0x1F, 0XfF, -0x10, +1, .5, -.5, 5., 5.e2, 1e+5, -2]"),
+            json!([31, 255, -16, 1, 0.5, -0.5, 5.0, 500.0, 1e5, -2])
+        );
+        // Not numbers, so left for serde_json to reject
+        assert!(serde_json::from_str::<Value>(&strip("[0xZZ]")).is_err());
+        assert!(serde_json::from_str::<Value>(&strip("[Infinity]")).is_err());
+    }
+
+    #[test]
+    fn single_quoted_strings_are_requoted() {
+        assert_eq!(parse(r#"['it\'s "quoted"', 'tab\there']"#), json!(["it's \"quoted\"", "tab\there"]));
+        assert_eq!(parse("{unquoted: 1, $also_2: 'a'}"), json!({"unquoted": 1, "$also_2": "a"}));
+    }
+
+    #[test]
+    fn line_continuations_are_joined() {
+        assert_eq!(parse("['one \\\ntwo', \"three \\\r\nfour\"]"), json!(["one two", "three four"]));
+    }
+
+    #[test]
+    fn error_lines_point_into_the_pasted_text() {
+        let input = "{\n  // note\n  /* a\n     b */\n  'a': 1,\n  b: [1 2],\n}";
+        let error = serde_json::from_str::<Value>(&strip(input)).unwrap_err();
+        assert_eq!(error.line(), 6);
+
+        let stripped = strip(input);
+        assert_eq!(stripped.lines().count(), input.lines().count());
+    }
+}
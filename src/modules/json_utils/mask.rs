@@ -0,0 +1,47 @@
+use serde_json::Value;
+
+pub const MASK: &str = "********";
+
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+pub struct Masker {
+    patterns: Vec<String>,
+}
+
+impl Masker {
+    pub fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns.iter().map(|p| normalize(p)).filter(|p| !p.is_empty()).collect(),
+        }
+    }
+
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        let key = normalize(key);
+        self.patterns.iter().any(|pattern| key.contains(pattern.as_str()))
+    }
+
+    /// Copy of `value` with every sensitive field replaced by the mask, whatever its type.
+    pub fn mask(&self, value: &Value) -> Value {
+        match value {
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .map(|(k, v)| {
+                        let masked = if self.is_sensitive(k) {
+                            Value::String(MASK.to_string())
+                        } else {
+                            self.mask(v)
+                        };
+                        (k.clone(), masked)
+                    })
+                    .collect(),
+            ),
+            Value::Array(arr) => Value::Array(arr.iter().map(|v| self.mask(v)).collect()),
+            other => other.clone(),
+        }
+    }
+}
@@ -1,94 +1,802 @@
 use color_eyre::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent,
+    MouseEventKind,
+};
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
 };
 use serde_json::{self, Value};
-use arboard::Clipboard;
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tempfile::NamedTempFile;
-use notify::{Watcher, RecursiveMode, Result as NotifyResult};
+use notify::{RecommendedWatcher, Watcher, RecursiveMode, Result as NotifyResult};
 use std::sync::mpsc;
-use std::time::Duration;
+use serde::de::IgnoredAny;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+mod canonical;
+mod codegen;
+mod convert;
+mod diff;
+mod highlight;
+mod hints;
+mod history;
+mod jwt;
+mod keymap;
+mod lenient;
+mod mask;
+mod patch;
+mod schema;
+mod search;
+mod session;
+mod stats;
+mod transform;
+mod visual;
+
+use crate::clipboard;
+use crate::config::{self, Config, DefaultView, Indent};
+use history::{History, Snapshot};
+use keymap::{Action, Keymap};
+use mask::Masker;
+use schema::{Schema, Violation};
+use search::{TextSearch, TreeSearch};
+use session::Session;
+use stats::Stats;
+use visual::VisualSelection;
+
+/// How long typing must pause before the buffer is re-parsed in insert mode.
+const TYPING_DEBOUNCE: Duration = Duration::from_millis(200);
+/// How long the selection must rest before copy-on-select writes to the clipboard.
+const COPY_ON_SELECT_DEBOUNCE: Duration = Duration::from_millis(300);
+/// How long a success notice stays on the status line.
+const STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Smallest terminal that fits the bordered view, the prompt and the footer.
+const MIN_TERMINAL_WIDTH: u16 = 30;
+const MIN_TERMINAL_HEIGHT: u16 = 8;
+/// Narrowest terminal that still gets the side-by-side raw and tree layout.
+const MIN_SPLIT_WIDTH: u16 = 100;
+
+/// Lines above the numbered input when showing a parse error in the raw view.
+const ERROR_HEADER_LINES: usize = 2;
+/// Lines kept visible above a line the raw view scrolls to, such as a parse error.
+const SCROLL_CONTEXT_LINES: usize = 3;
+/// Suggested file name in the export prompt.
+const DEFAULT_EXPORT_FILE: &str = "output.json";
+const DEFAULT_CSV_FILE: &str = "output.csv";
+/// Lines the raw view moves per scroll-wheel notch.
+const MOUSE_SCROLL_LINES: usize = 3;
+/// Violations shown at once in the schema panel before it scrolls.
+const SCHEMA_PANEL_ROWS: usize = 6;
 
 #[derive(PartialEq)]
 enum ViewMode {
     Raw,
     Tree,
+    Converted,
+}
+
+/// Something the raw view shows in place of a document that isn't an error.
+#[derive(Clone, Copy, PartialEq)]
+enum Notice {
+    /// The input was checked by validate-only and isn't held as a document.
+    Validated,
+    /// The input is in a watched temp file, waiting for the editor.
+    Editing,
+}
+
+/// Display-only copy of the document with sensitive fields replaced by the mask.
+struct MaskedDocument {
+    value: Value,
+    pretty: String,
+    minified: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PromptKind {
+    SortArraysBy,
+    ExpandToDepth,
+    ExtractValues,
+    ConfirmDelete,
+    ConfirmOverwrite,
+    CompareFile,
+    GoToPath,
+    SearchText,
+    PasteFromFile(ClipboardUse),
+    OpenFile,
+    ExportFile { minified: bool },
+    ExportCsv,
+    LoadSchema,
+    SearchTree,
+    FilterFlat,
+    TypeScriptRoot,
+    EditValue(ValueType),
+}
+
+impl PromptKind {
+    fn label(self) -> &'static str {
+        match self {
+            PromptKind::SortArraysBy => "Sort arrays of objects by key",
+            PromptKind::ExpandToDepth => "Expand tree down to depth",
+            PromptKind::ExtractValues => "Extract all values under key",
+            PromptKind::CompareFile => "Compare with JSON file at path",
+            PromptKind::GoToPath => "Go to path, e.g. users[2].email",
+            PromptKind::SearchText => "Search (empty clears)",
+            PromptKind::SearchTree => "Search keys and values (empty clears)",
+            PromptKind::FilterFlat => "Show lines containing (empty shows all)",
+            PromptKind::TypeScriptRoot => "Name of the top-level TypeScript type",
+            PromptKind::OpenFile => "Open file (Tab completes)",
+            PromptKind::ExportFile { minified: false } => "Write JSON to file",
+            PromptKind::ExportFile { minified: true } => "Write minified JSON to file",
+            PromptKind::ExportCsv => "Write CSV to file (empty copies it instead)",
+            PromptKind::LoadSchema => "Validate against JSON Schema file (empty unloads)",
+            PromptKind::PasteFromFile(_) => "No clipboard available - read from file path ('-' for piped stdin)",
+            PromptKind::ConfirmDelete => "Delete the selected node and everything inside it? Type y",
+            PromptKind::ConfirmOverwrite => "File exists - replace it? Type y",
+            PromptKind::EditValue(ValueType::Auto) => "Edit value as JSON, plain text is a string (Tab: type)",
+            PromptKind::EditValue(ValueType::String) => "Edit value as string (Tab: type)",
+            PromptKind::EditValue(ValueType::Number) => "Edit value as number (Tab: type)",
+            PromptKind::EditValue(ValueType::Boolean) => "Edit value as boolean, true or false (Tab: type)",
+            PromptKind::EditValue(ValueType::Null) => "Edit value: null (Tab: type)",
+        }
+    }
+}
+
+/// What clipboard text is read for, so that text read from a file when there's no
+/// clipboard finishes the same job.
+#[derive(Clone, Copy, PartialEq)]
+enum ClipboardUse {
+    Paste,
+    ValidateOnly,
+    Csv { infer_types: bool },
+    Yaml,
+    Xml,
+    Toml,
+    Compare,
+    Patch,
+    ApplyPatch,
+}
+
+/// Type an edited leaf value is read as, cycled with Tab in the edit prompt.
+#[derive(Clone, Copy, PartialEq)]
+enum ValueType {
+    /// A JSON scalar literal if the input is one, otherwise a string.
+    Auto,
+    String,
+    Number,
+    Boolean,
+    Null,
+}
+
+impl ValueType {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::String(_) => ValueType::String,
+            Value::Number(_) => ValueType::Number,
+            Value::Bool(_) => ValueType::Boolean,
+            Value::Null => ValueType::Null,
+            Value::Object(_) | Value::Array(_) => ValueType::Auto,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            ValueType::Auto => ValueType::String,
+            ValueType::String => ValueType::Number,
+            ValueType::Number => ValueType::Boolean,
+            ValueType::Boolean => ValueType::Null,
+            ValueType::Null => ValueType::Auto,
+        }
+    }
+
+    fn parse(self, input: &str) -> std::result::Result<Value, String> {
+        match self {
+            ValueType::Auto => Ok(match serde_json::from_str::<Value>(input.trim()) {
+                Ok(value) if !value.is_object() && !value.is_array() => value,
+                _ => Value::String(input.to_string()),
+            }),
+            ValueType::String => Ok(Value::String(input.to_string())),
+            ValueType::Number => match serde_json::from_str::<Value>(input.trim()) {
+                Ok(number @ Value::Number(_)) => Ok(number),
+                _ => Err(format!("'{}' is not a number", input.trim())),
+            },
+            ValueType::Boolean => match input.trim().to_ascii_lowercase().as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                other => Err(format!("'{}' is not true or false", other)),
+            },
+            ValueType::Null => Ok(Value::Null),
+        }
+    }
+}
+
+/// A one-line text input shown at the bottom of the screen for commands that need an argument.
+struct Prompt {
+    kind: PromptKind,
+    input: String,
+}
+
+/// A write held back until replacing the existing file is confirmed.
+struct PendingWrite {
+    path: PathBuf,
+    text: String,
+    /// What is being written, for the status message, e.g. `minified JSON`.
+    what: String,
+}
+
+impl Prompt {
+    fn new(kind: PromptKind) -> Self {
+        Self {
+            kind,
+            input: String::new(),
+        }
+    }
+}
+
+/// Base integer leaves are shown in within the tree. Display only; the document keeps decimals.
+#[derive(Clone, Copy, PartialEq)]
+enum NumberBase {
+    Decimal,
+    Hex,
+    Octal,
+    Binary,
+}
+
+impl NumberBase {
+    fn next(self) -> Self {
+        match self {
+            NumberBase::Decimal => NumberBase::Hex,
+            NumberBase::Hex => NumberBase::Octal,
+            NumberBase::Octal => NumberBase::Binary,
+            NumberBase::Binary => NumberBase::Decimal,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            NumberBase::Decimal => "decimal",
+            NumberBase::Hex => "hex",
+            NumberBase::Octal => "octal",
+            NumberBase::Binary => "binary",
+        }
+    }
+
+    /// Integers in this base with the usual prefix; floats are left as written.
+    fn format(self, n: &serde_json::Number) -> String {
+        let Some(magnitude) = n.as_u64().or_else(|| n.as_i64().map(i64::unsigned_abs)) else {
+            return n.to_string();
+        };
+        let sign = if n.as_i64().is_some_and(|i| i < 0) { "-" } else { "" };
+        match self {
+            NumberBase::Hex => format!("{}0x{:x}", sign, magnitude),
+            NumberBase::Octal => format!("{}0o{:o}", sign, magnitude),
+            NumberBase::Binary => format!("{}0b{:b}", sign, magnitude),
+            NumberBase::Decimal => n.to_string(),
+        }
+    }
+}
+
+struct ParseStats {
+    bytes: usize,
+    duration: Duration,
+    nodes: usize,
+}
+
+struct ConvertedOutput {
+    title: String,
+    /// Set for a diff, which is drawn side by side instead of as `text`.
+    diff: Option<DiffView>,
+    /// Set for the flattened listing, which `text` is rendered from as it's sorted and filtered.
+    flat: Option<FlatView>,
+    /// What `c` copies; for a diff, one `+`/`-`/`~` line per change.
+    text: String,
+}
+
+/// Every leaf of the document as a `path = value` line.
+struct FlatView {
+    /// Path and compact JSON value of each leaf, in document order (which, with object keys
+    /// kept sorted, is also path order).
+    entries: Vec<(String, String)>,
+    /// Sorted by value instead, so equal values end up next to each other.
+    by_value: bool,
+    /// Case-insensitive text a line has to contain to be shown; empty shows them all.
+    filter: String,
+}
+
+impl FlatView {
+    /// The lines shown, sorted and filtered as currently set.
+    fn lines(&self) -> Vec<String> {
+        let needle = self.filter.to_lowercase();
+        let mut lines: Vec<&(String, String)> = self
+            .entries
+            .iter()
+            .filter(|(path, value)| {
+                needle.is_empty()
+                    || path.to_lowercase().contains(&needle)
+                    || value.to_lowercase().contains(&needle)
+            })
+            .collect();
+        if self.by_value {
+            lines.sort_by(|a, b| a.1.cmp(&b.1));
+        }
+        lines.iter().map(|(path, value)| format!("{} = {}", path, value)).collect()
+    }
+
+    fn title(&self, shown: usize) -> String {
+        let mut title = if shown == self.entries.len() {
+            format!("Flattened ({} leaves", shown)
+        } else {
+            format!("Flattened ({} of {} leaves", shown, self.entries.len())
+        };
+        if self.by_value {
+            title.push_str(", sorted by value");
+        }
+        if !self.filter.is_empty() {
+            title.push_str(&format!(", filter '{}'", self.filter));
+        }
+        title.push(')');
+        title
+    }
 }
 
+struct DiffView {
+    changes: Vec<(String, diff::Change)>,
+    base_name: String,
+    /// Change the cursor is on, moved with the navigation keys.
+    selected: usize,
+}
+
+/// A row of the tree. Nodes read their value out of the parsed document through `pointer`
+/// rather than holding a copy, and a container's children are only added to `json_tree`
+/// once it is first expanded, so a large document costs about as much as the part that has
+/// been looked at.
 #[derive(Clone)]
 struct JsonTreeNode {
     key: String,
-    value: Value,
+    /// RFC 6901 pointer to the value inside `parsed_value`.
+    pointer: String,
+    /// Object or array, kept so walks over the tree don't resolve every pointer.
+    container: bool,
+    /// A string holding an object or array as JSON text, flagged so it's clear it can be
+    /// unwrapped. Decided when the node is made rather than on every frame.
+    stringified: bool,
     expanded: bool,
+    /// Whether the children follow this node in `json_tree`.
+    loaded: bool,
     depth: usize,
-    #[allow(dead_code)]
     path: String,
+    /// Line of `formatted_json` where this node starts, worked out when first needed.
+    line: Cell<Option<usize>>,
+    /// Minified size of the value, measured the first time the node is drawn.
+    byte_size: Cell<Option<usize>>,
+}
+
+impl JsonTreeNode {
+    fn new(value: &Value, key: String, pointer: String, path: String, depth: usize, expanded: bool) -> Self {
+        let container = value.is_object() || value.is_array();
+        Self {
+            key,
+            pointer,
+            container,
+            stringified: value.as_str().is_some_and(|s| transform::decode_stringified(s).is_some()),
+            expanded: expanded && container,
+            loaded: false,
+            depth,
+            path,
+            line: Cell::new(None),
+            byte_size: Cell::new(None),
+        }
+    }
+
+    fn value<'a>(&self, document: &'a Value) -> &'a Value {
+        document.pointer(&self.pointer).unwrap_or(&Value::Null)
+    }
+
+    fn byte_size(&self, document: &Value) -> usize {
+        if let Some(size) = self.byte_size.get() {
+            return size;
+        }
+        let mut counter = ByteCounter(0);
+        let size = serde_json::to_writer(&mut counter, self.value(document)).map_or(0, |_| counter.0);
+        self.byte_size.set(Some(size));
+        size
+    }
+}
+
+/// Counts what's written to it, for measuring serialized sizes without building the text.
+struct ByteCounter(usize);
+
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 pub struct JsonUtils {
     raw_input: String,
     formatted_json: String,
+    minified_json: String,
+    show_minified: bool,
+    show_annotations: bool,
+    /// Parse and IO errors, and actions that couldn't be carried out.
     error_message: String,
+    /// What the raw view says while there's no document and no error, e.g. where the temp
+    /// file is; replaced whenever new input is loaded.
+    notice: Option<(Notice, String)>,
+    /// Success notice and when it was set; cleared after `STATUS_TIMEOUT`.
+    status: Option<(String, Instant)>,
     is_valid: bool,
     view_mode: ViewMode,
+    converted: Option<ConvertedOutput>,
     json_tree: Vec<JsonTreeNode>,
     selected_node: usize,
     parsed_value: Option<Value>,
     temp_file: Option<NamedTempFile>,
+    temp_file_suffix: String,
+    file_watcher: Option<RecommendedWatcher>,
     file_watcher_rx: Option<mpsc::Receiver<NotifyResult<notify::Event>>>,
     needs_terminal_reinit: bool,
     scroll_offset: usize,
+    horizontal_scroll: usize,
+    error_position: Option<(usize, usize)>,
+    jwt_algorithm: Option<String>,
+    source_format: Option<&'static str>,
+    bookmarks: HashSet<String>,
+    /// Expansion state by node path, kept across re-parses of the same document.
+    expansion_memory: HashMap<String, bool>,
+    auto_expand_depth: usize,
+    indent: Indent,
+    /// Name given to the top-level generated Rust or TypeScript type.
+    root_type_name: String,
+    keymap: Keymap,
+    visual: Option<VisualSelection>,
+    /// Raw view search, whose matches are offsets into `displayed_pretty()`.
+    search: Option<TextSearch>,
+    /// Tree view search, whose matches are indices into `json_tree`.
+    tree_search: Option<TreeSearch>,
+    /// Schema the document is checked against, with the path it was loaded from.
+    schema: Option<(PathBuf, Schema)>,
+    schema_violations: Vec<Violation>,
+    schema_scroll: usize,
+    history: History,
+    raw_view_height: Cell<usize>,
+    /// Where the tree was last drawn, to map mouse clicks back to rows.
+    tree_area: Cell<Rect>,
+    /// First visible node on screen, moved just enough to keep the selection in view.
+    tree_offset: Cell<usize>,
+    /// Rows the tree showed when last drawn, for paging.
+    tree_view_height: Cell<usize>,
+    /// `visible_indices`, kept until the tree's layout or expansion changes.
+    visible_rows: RefCell<Option<Vec<usize>>>,
+    /// Wrapped line count of the raw view at its last rendered width.
+    raw_total_lines: Cell<usize>,
+    /// Rows above each line of the raw view once wrapped, with a hash of the text and width
+    /// they were measured for, so a frame only re-wraps the document after it changes.
+    raw_rows: RefCell<Option<(u64, Vec<usize>)>>,
+    split_view: bool,
+    masker: Masker,
+    masked: Option<MaskedDocument>,
+    typing: bool,
+    last_keystroke: Option<Instant>,
+    save_session: bool,
+    wrap_navigation: bool,
+    show_help: bool,
+    /// Shape summary shown over the view until the next key.
+    stats_overlay: Option<Stats>,
+    number_base: NumberBase,
+    /// Retry failed parses with the input wrapped in `{}` or `[]`.
+    fragment_mode: bool,
+    /// Accept JSON5 and JSONC: comments, trailing commas, unquoted keys, single quotes.
+    lenient_mode: bool,
+    fragment_wrap: Option<&'static str>,
+    /// Last query entered in the go-to-path prompt.
+    path_query: String,
+    poll_interval: Duration,
+    idle_poll_interval: Duration,
+    copy_on_select: bool,
+    pending_copy: Option<Instant>,
+    prompt: Option<Prompt>,
+    pending_write: Option<PendingWrite>,
+    parse_stats: Option<ParseStats>,
 }
 
 impl JsonUtils {
     pub fn new() -> Self {
+        let config = Config::load();
+        let (keymap, keymap_warnings) = Keymap::load();
+        let error_message = if keymap_warnings.is_empty() {
+            String::new()
+        } else {
+            format!("Keymap warnings (defaults used): {}", keymap_warnings.join("; "))
+        };
+
         Self {
             raw_input: String::new(),
             formatted_json: String::new(),
-            error_message: String::new(),
+            minified_json: String::new(),
+            show_minified: false,
+            show_annotations: false,
+            error_message,
+            notice: None,
+            status: None,
             is_valid: false,
-            view_mode: ViewMode::Raw,
+            view_mode: match config.json.default_view {
+                DefaultView::Raw => ViewMode::Raw,
+                DefaultView::Tree => ViewMode::Tree,
+            },
+            converted: None,
             json_tree: Vec::new(),
             selected_node: 0,
             parsed_value: None,
             temp_file: None,
+            temp_file_suffix: config.json.temp_file_suffix.clone(),
+            file_watcher: None,
             file_watcher_rx: None,
             needs_terminal_reinit: false,
             scroll_offset: 0,
+            horizontal_scroll: 0,
+            error_position: None,
+            jwt_algorithm: None,
+            source_format: None,
+            bookmarks: HashSet::new(),
+            expansion_memory: HashMap::new(),
+            auto_expand_depth: config.json.auto_expand_depth,
+            indent: config.json.indent,
+            root_type_name: config.json.root_type_name.clone(),
+            keymap,
+            visual: None,
+            search: None,
+            tree_search: None,
+            schema: None,
+            schema_violations: Vec::new(),
+            schema_scroll: 0,
+            history: History::default(),
+            raw_view_height: Cell::new(0),
+            tree_area: Cell::new(Rect::default()),
+            tree_offset: Cell::new(0),
+            visible_rows: RefCell::new(None),
+            tree_view_height: Cell::new(0),
+            raw_total_lines: Cell::new(0),
+            raw_rows: RefCell::new(None),
+            split_view: false,
+            masker: Masker::new(&config.json.sensitive_keys),
+            masked: None,
+            typing: false,
+            last_keystroke: None,
+            save_session: config.json.save_session,
+            wrap_navigation: config.ui.wrap_navigation,
+            show_help: false,
+            stats_overlay: None,
+            number_base: NumberBase::Decimal,
+            fragment_mode: false,
+            lenient_mode: false,
+            fragment_wrap: None,
+            path_query: String::new(),
+            poll_interval: Duration::from_millis(config.ui.poll_interval_ms),
+            idle_poll_interval: Duration::from_millis(config.ui.idle_poll_interval_ms),
+            copy_on_select: false,
+            pending_copy: None,
+            prompt: None,
+            pending_write: None,
+            parse_stats: None,
         }
     }
 
-    pub fn paste_from_clipboard(&mut self) -> Result<()> {
-        let mut clipboard = Clipboard::new()?;
-        match clipboard.get_text() {
-            Ok(text) => {
-                self.raw_input = text;
-                self.parse_json();
+    /// Forgets what belonged to the previous document before an unrelated one is loaded.
+    fn start_new_document(&mut self) {
+        self.jwt_algorithm = None;
+        self.source_format = None;
+        self.json_tree.clear();
+        self.tree_changed();
+        self.expansion_memory.clear();
+    }
+
+    /// Loads pasted text: a JWT is decoded, and input that isn't JSON but is another
+    /// structured format is converted. Pasting the same document again with only its values
+    /// changed, like a fresh copy of an API response, keeps the tree expanded as it was.
+    fn load_pasted(&mut self, text: String) {
+        self.record_undo();
+        self.remember_expansion();
+        let previous = self.parsed_value.take();
+        let expansion = std::mem::take(&mut self.expansion_memory);
+        self.load_pasted_document(text);
+
+        let same_shape = previous
+            .zip(self.parsed_value.as_ref())
+            .is_some_and(|(old, new)| transform::same_shape(&old, new));
+        if same_shape {
+            self.rebuild_tree(|path, _| expansion.get(path).copied());
+            self.expansion_memory = expansion;
+        }
+    }
+
+    fn load_pasted_document(&mut self, text: String) {
+        self.start_new_document();
+        if let Some(decoded) = jwt::decode(&text) {
+            self.load_value(&decoded.document);
+            self.jwt_algorithm = Some(decoded.algorithm);
+            return;
+        }
+
+        self.raw_input = text;
+        self.parse_json();
+        if self.raw_input.trim().is_empty() {
+            self.error_message = "Clipboard is empty".to_string();
+        } else if !self.is_valid {
+            // Keep the JSON error on screen unless another format claims the input
+            if let Some((format, value)) = convert::detect_structured(&self.raw_input) {
+                tracing::debug!("clipboard detected as {}", format);
+                self.load_value(&value);
+                self.source_format = Some(format);
+            } else if convert::looks_like_json(&self.raw_input) && convert::yaml_container(&self.raw_input).is_some() {
+                let keys = self.keymap.hints(&[(&[Action::PasteYaml], "paste it as YAML")]);
+                self.error_message.push_str(&format!(" (this does read as YAML - {})", keys));
+            }
+        }
+    }
+
+    /// Stands in for the clipboard when there is none: reads `path`, or stdin for `-` when
+    /// it's piped rather than the terminal, and uses its text for `purpose`.
+    fn paste_from_file(&mut self, path: &str, purpose: ClipboardUse) {
+        let result = if path == "-" {
+            if std::io::stdin().is_terminal() {
+                self.error_message = "stdin is the terminal - pipe input in, e.g. `cat file.json | t`".to_string();
+                return;
+            }
+            std::io::read_to_string(std::io::stdin())
+        } else {
+            fs::read_to_string(expand_home(path))
+        };
+        match result {
+            Ok(text) => self.use_pasted(purpose, text),
+            Err(e) => self.error_message = format!("Can't read {}: {}", path, e),
+        }
+    }
+
+    /// Writes the document to `path`, pretty-printed or minified.
+    fn export_to_file(&mut self, path: &str, minified: bool) {
+        let (text, what) = if minified {
+            (self.minified_json.clone(), "minified JSON")
+        } else {
+            (self.formatted_json.clone(), "JSON")
+        };
+        self.write_file(path, text, what.to_string());
+    }
+
+    /// Writes `text` to `path`, asking first when that would replace an existing file.
+    fn write_file(&mut self, path: &str, text: String, what: String) {
+        let path = expand_home(path);
+        let write = PendingWrite { path, text, what };
+        if write.path.is_file() {
+            self.set_status(format!("{} already exists", write.path.display()));
+            self.pending_write = Some(write);
+            self.prompt = Some(Prompt::new(PromptKind::ConfirmOverwrite));
+        } else {
+            self.finish_write(write);
+        }
+    }
+
+    /// Writes out `write`, creating missing parent directories.
+    fn finish_write(&mut self, write: PendingWrite) {
+        let PendingWrite { path, text, what } = write;
+        let result = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, &text));
+        match result {
+            Ok(()) => {
+                let shown = fs::canonicalize(&path).unwrap_or(path);
+                self.set_status(format!("Wrote {} ({}) to {}", what, format_bytes(text.len()), shown.display()));
             }
+            Err(e) => self.error_message = format!("Failed to write {}: {}", path.display(), e),
+        }
+    }
+
+    /// The array CSV export works on: the selected tree node when it's an array, otherwise
+    /// the whole document.
+    fn csv_source(&self) -> Option<(&str, &Value)> {
+        let document = self.document();
+        let selected = self.json_tree.get(self.selected_node).filter(|node| node.value(document).is_array());
+        match selected {
+            Some(node) if self.view_mode == ViewMode::Tree => Some((node.path.as_str(), node.value(document))),
+            _ => self.parsed_value.as_ref().map(|value| ("root", value)),
+        }
+    }
+
+    /// Writes the CSV export to `path`, or copies it when `path` is empty.
+    fn export_csv(&mut self, path: &str) {
+        let Some((source, value)) = self.csv_source() else {
+            return;
+        };
+        let text = match convert::value_to_csv(value) {
+            Ok(text) => text,
             Err(e) => {
-                self.error_message = format!("Failed to get clipboard: {}", e);
-                self.is_valid = false;
-                self.formatted_json.clear();
+                self.error_message = format!("Can't export {} as CSV: {}", source, e);
+                return;
             }
+        };
+        if path.is_empty() {
+            let message = format!("Copied {} as CSV ({})", source, format_bytes(text.len()));
+            self.report(clipboard::try_copy(&text, message));
+            return;
+        }
+        let what = format!("{} as CSV", source);
+        self.write_file(path, text, what);
+    }
+
+    /// Replaces the document with `text`, as if it had been pasted.
+    pub fn load_text(&mut self, text: String) {
+        self.record_undo();
+        self.start_new_document();
+        self.raw_input = text;
+        self.parse_json();
+    }
+
+    /// Reads the clipboard for `purpose` and carries it out. Without a clipboard this asks
+    /// for a file to read instead, and the prompt carries out `purpose` with its text.
+    fn paste_for(&mut self, purpose: ClipboardUse) {
+        let mut clipboard = clipboard::open();
+        if clipboard.fallback_path().is_some() {
+            self.prompt = Some(Prompt::new(PromptKind::PasteFromFile(purpose)));
+            return;
+        }
+        match clipboard.get_text() {
+            Ok(text) => self.use_pasted(purpose, text),
+            Err(e) => self.error_message = format!("Failed to get clipboard: {}", e),
         }
+    }
+
+    fn use_pasted(&mut self, purpose: ClipboardUse, text: String) {
+        match purpose {
+            ClipboardUse::Paste => self.load_pasted(text),
+            ClipboardUse::ValidateOnly => self.validate_only(text),
+            ClipboardUse::Csv { infer_types } => self.load_csv(&text, infer_types),
+            ClipboardUse::Yaml => self.load_as(&text, "YAML", convert::yaml_to_value),
+            ClipboardUse::Xml => self.load_as(&text, "XML", convert::xml_to_value),
+            ClipboardUse::Toml => self.load_as(&text, "TOML", convert::toml_to_value),
+            ClipboardUse::Compare => self.compare_with_pasted(&text),
+            ClipboardUse::Patch => self.patch_from_pasted(&text),
+            ClipboardUse::ApplyPatch => self.apply_pasted_patch(&text),
+        }
+    }
+
+    pub fn paste_from_clipboard(&mut self) -> Result<()> {
+        self.paste_for(ClipboardUse::Paste);
         Ok(())
     }
 
     pub fn copy_to_clipboard(&mut self) -> Result<()> {
-        if self.is_valid && !self.formatted_json.is_empty() {
-            let mut clipboard = Clipboard::new()?;
-            match clipboard.set_text(&self.formatted_json) {
-                Ok(_) => {
-                    self.error_message = "Copied formatted JSON to clipboard".to_string();
-                }
-                Err(e) => {
-                    self.error_message = format!("Failed to copy to clipboard: {}", e);
-                }
+        if self.view_mode == ViewMode::Converted {
+            if let Some(ref converted) = self.converted {
+                self.report(clipboard::try_copy(
+                    &converted.text,
+                    format!("Copied {} to clipboard", converted.title),
+                ));
             }
+            return Ok(());
+        }
+
+        if self.is_valid && !self.formatted_json.is_empty() {
+            self.report(clipboard::try_copy(
+                &self.formatted_json,
+                "Copied formatted JSON to clipboard".to_string(),
+            ));
         }
         Ok(())
     }
@@ -98,15 +806,10 @@ impl JsonUtils {
             if let Some(ref value) = self.parsed_value {
                 match serde_json::to_string(value) {
                     Ok(minified) => {
-                        let mut clipboard = Clipboard::new()?;
-                        match clipboard.set_text(&minified) {
-                            Ok(_) => {
-                                self.error_message = "Copied minified JSON to clipboard".to_string();
-                            }
-                            Err(e) => {
-                                self.error_message = format!("Failed to copy to clipboard: {}", e);
-                            }
-                        }
+                        self.report(clipboard::try_copy(
+                            &minified,
+                            "Copied minified JSON to clipboard".to_string(),
+                        ));
                     }
                     Err(e) => {
                         self.error_message = format!("Failed to minify JSON: {}", e);
@@ -117,20 +820,240 @@ impl JsonUtils {
         Ok(())
     }
 
+    /// Checks the clipboard for well-formed JSON without materializing a `Value` or building
+    /// the tree, for multi-megabyte payloads where only validity matters.
+    pub fn validate_clipboard_only(&mut self) -> Result<()> {
+        self.paste_for(ClipboardUse::ValidateOnly);
+        Ok(())
+    }
+
+    fn validate_only(&mut self, text: String) {
+        self.record_undo();
+        self.start_new_document();
+        self.raw_input = text;
+        self.clear_parsed_state();
+        let input = self.raw_input.strip_prefix('\u{feff}').unwrap_or(&self.raw_input);
+        let started = Instant::now();
+        let result = serde_json::from_str::<IgnoredAny>(input);
+        let elapsed = started.elapsed();
+        tracing::debug!("validate-only over {} bytes took {:?}", input.len(), elapsed);
+        match result {
+            Ok(_) => {
+                let message = format!(
+                    "Valid JSON: {} bytes checked in {:.1?} (validate-only, {})",
+                    input.len(),
+                    elapsed,
+                    self.keymap.hints(&[(&[Action::OpenEditor], "edit"), (&[Action::Paste], "view")])
+                );
+                self.error_message.clear();
+                self.notice = Some((Notice::Validated, message));
+            }
+            Err(e) => self.error_message = format!("Invalid JSON: {}", e),
+        }
+    }
+
+    /// Loads clipboard CSV as an array of row objects, optionally inferring cell types.
+    pub fn paste_csv_from_clipboard(&mut self, infer_types: bool) -> Result<()> {
+        self.paste_for(ClipboardUse::Csv { infer_types });
+        Ok(())
+    }
+
+    /// The document is only replaced once the CSV has parsed, so a failed paste keeps it.
+    fn load_csv(&mut self, text: &str, infer_types: bool) {
+        match convert::csv_to_value(text, infer_types) {
+            Ok(value) => {
+                self.record_undo();
+                self.start_new_document();
+                self.load_value(&value);
+                self.source_format = Some(if infer_types { "CSV" } else { "CSV, as strings" });
+            }
+            Err(e) => self.error_message = format!("Invalid CSV: {}", e),
+        }
+    }
+
+    /// Loads pasted `text` as `format`, read by `parse`. A plain paste only falls back to
+    /// other formats once JSON fails, and to YAML only for mappings and sequences, so the
+    /// format pastes force it and report its own parse error. The document is only replaced
+    /// once the text has parsed.
+    fn load_as(&mut self, text: &str, format: &'static str, parse: fn(&str) -> Result<Value>) {
+        match parse(text) {
+            Ok(value) => {
+                self.record_undo();
+                self.start_new_document();
+                self.load_value(&value);
+                self.source_format = Some(format);
+            }
+            Err(e) => self.error_message = format!("Invalid {}: {}", format, e),
+        }
+    }
+
+    pub fn paste_yaml_from_clipboard(&mut self) -> Result<()> {
+        self.paste_for(ClipboardUse::Yaml);
+        Ok(())
+    }
+
+    pub fn paste_xml_from_clipboard(&mut self) -> Result<()> {
+        self.paste_for(ClipboardUse::Xml);
+        Ok(())
+    }
+
+    pub fn paste_toml_from_clipboard(&mut self) -> Result<()> {
+        self.paste_for(ClipboardUse::Toml);
+        Ok(())
+    }
+
+    /// Copies the RFC 8785 canonical form of the document and shows it in the output view.
+    pub fn copy_canonical(&mut self) -> Result<()> {
+        let Some(ref value) = self.parsed_value else {
+            return Ok(());
+        };
+        let canonical = canonical::to_canonical_string(value);
+        self.report(clipboard::try_copy(&canonical, format!("Copied canonical JSON ({} bytes)", canonical.len())));
+        self.converted = Some(ConvertedOutput {
+            title: "Canonical JSON (RFC 8785)".to_string(),
+            diff: None,
+            flat: None,
+            text: canonical,
+        });
+        self.view_mode = ViewMode::Converted;
+        self.scroll_offset = 0;
+        Ok(())
+    }
+
+    /// Generates serde structs for the document, copies them and shows them in the output view.
+    pub fn copy_rust_structs(&mut self) -> Result<()> {
+        let Some(ref value) = self.parsed_value else {
+            self.error_message = "No valid JSON to generate structs from".to_string();
+            return Ok(());
+        };
+        let code = codegen::rust_structs(value, &self.root_type_name);
+        self.report(clipboard::try_copy(&code, "Copied Rust structs to clipboard".to_string()));
+        self.converted = Some(ConvertedOutput {
+            title: "Rust structs".to_string(),
+            diff: None,
+            flat: None,
+            text: code,
+        });
+        self.view_mode = ViewMode::Converted;
+        self.scroll_offset = 0;
+        Ok(())
+    }
+
+    /// Generates TypeScript interfaces for the document with the top-level type named
+    /// `root_name`, which is remembered for later generations. Copies them and shows them
+    /// in the output view.
+    fn copy_typescript(&mut self, root_name: &str) {
+        let Some(ref value) = self.parsed_value else {
+            self.error_message = "No valid JSON to generate interfaces from".to_string();
+            return;
+        };
+        let code = codegen::typescript_interfaces(value, root_name);
+        self.report(clipboard::try_copy(&code, "Copied TypeScript interfaces to clipboard".to_string()));
+        self.converted = Some(ConvertedOutput {
+            title: "TypeScript interfaces".to_string(),
+            diff: None,
+            flat: None,
+            text: code,
+        });
+        self.view_mode = ViewMode::Converted;
+        self.scroll_offset = 0;
+        if root_name != self.root_type_name {
+            self.root_type_name = root_name.to_string();
+            config::store("json", "root_type_name", root_name.into());
+        }
+    }
+
+    pub fn convert_to_xml(&mut self) {
+        if let Some(ref value) = self.parsed_value {
+            self.converted = Some(ConvertedOutput {
+                title: "XML Output".to_string(),
+                diff: None,
+                flat: None,
+                text: convert::value_to_xml(value),
+            });
+            self.view_mode = ViewMode::Converted;
+            self.scroll_offset = 0;
+        }
+    }
+
+    pub fn convert_to_toml(&mut self) {
+        if let Some(ref value) = self.parsed_value {
+            match convert::value_to_toml(value) {
+                Ok(text) => {
+                    self.converted = Some(ConvertedOutput {
+                        title: "TOML Output".to_string(),
+                        diff: None,
+                        flat: None,
+                        text,
+                    });
+                    self.view_mode = ViewMode::Converted;
+                    self.scroll_offset = 0;
+                }
+                Err(e) => self.error_message = format!("Can't convert to TOML: {}", e),
+            }
+        }
+    }
+
+    pub fn convert_to_yaml(&mut self) {
+        if let Some(ref value) = self.parsed_value {
+            match convert::value_to_yaml(value) {
+                Ok(text) => {
+                    self.converted = Some(ConvertedOutput {
+                        title: "YAML Output".to_string(),
+                        diff: None,
+                        flat: None,
+                        text,
+                    });
+                    self.view_mode = ViewMode::Converted;
+                    self.scroll_offset = 0;
+                }
+                Err(e) => self.error_message = format!("Can't convert to YAML: {}", e),
+            }
+        }
+    }
+
+    /// Replaces the buffer with the pretty-printed form of a value converted from another format.
+    fn load_value(&mut self, value: &Value) {
+        match to_pretty(value, self.indent) {
+            Ok(text) => {
+                self.raw_input = text;
+                self.parse_json();
+            }
+            Err(e) => {
+                self.error_message = format!("Format error: {}", e);
+            }
+        }
+    }
+
+    fn new_temp_file(&self) -> Result<NamedTempFile> {
+        Ok(tempfile::Builder::new()
+            .prefix("dev-tools-")
+            .suffix(&self.temp_file_suffix)
+            .tempfile()?)
+    }
+
+    /// Starts watching `path`, keeping the watcher alive until the next temp file replaces it.
+    fn watch(&mut self, path: &Path) -> Result<mpsc::Receiver<NotifyResult<notify::Event>>> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(path, RecursiveMode::NonRecursive)?;
+        self.file_watcher = Some(watcher);
+        Ok(rx)
+    }
+
     pub fn create_temp_file_for_editing(&mut self) -> Result<()> {
         if self.raw_input.is_empty() {
             self.error_message = "No JSON content to edit".to_string();
             return Ok(());
         }
 
-        let temp_file = NamedTempFile::new()?;
+        let temp_file = self.new_temp_file()?;
         fs::write(temp_file.path(), &self.raw_input)?;
+        let rx = self.watch(temp_file.path())?;
 
-        let (tx, rx) = mpsc::channel();
-        let mut watcher = notify::recommended_watcher(tx)?;
-        watcher.watch(temp_file.path(), RecursiveMode::NonRecursive)?;
-
-        self.error_message = format!("Edit this file: {}\nFile is being watched for changes...", temp_file.path().display());
+        let message = format!("Edit this file: {}\nFile is being watched for changes...", temp_file.path().display());
+        self.error_message.clear();
+        self.notice = Some((Notice::Editing, message));
         
         self.temp_file = Some(temp_file);
         self.file_watcher_rx = Some(rx);
@@ -138,54 +1061,191 @@ impl JsonUtils {
         Ok(())
     }
 
-    pub fn open_in_neovim(&mut self) -> Result<()> {
+    /// Opens the buffer in the user's editor and waits for it to exit, then loads the result.
+    pub fn open_in_editor(&mut self) -> Result<()> {
         if self.raw_input.is_empty() {
             self.error_message = "No JSON content to edit".to_string();
             return Ok(());
         }
 
-        let temp_file = NamedTempFile::new()?;
+        let temp_file = self.new_temp_file()?;
         fs::write(temp_file.path(), &self.raw_input)?;
+        let rx = self.watch(temp_file.path())?;
+        let (program, args) = editor_command();
 
-        let (tx, rx) = mpsc::channel();
-        let mut watcher = notify::recommended_watcher(tx)?;
-        watcher.watch(temp_file.path(), RecursiveMode::NonRecursive)?;
-
+        crossterm::execute!(std::io::stdout(), DisableMouseCapture)?;
         ratatui::restore();
-        
-        let status = Command::new("nvim")
-            .arg(temp_file.path())
-            .status()?;
+        self.needs_terminal_reinit = true;
 
-        if !status.success() {
-            self.error_message = "Failed to open Neovim".to_string();
+        tracing::info!("spawning {} {:?} on {}", program, args, temp_file.path().display());
+        let status = Command::new(&program)
+            .args(&args)
+            .arg(temp_file.path())
+            .status();
+        match status {
+            Ok(status) => {
+                tracing::info!("{} exited with {}", program, status);
+                if !status.success() {
+                    self.error_message = format!("Editor '{}' exited with {}", program, status);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.error_message = format!("Editor '{}' not found, set $EDITOR", program);
+                self.file_watcher = None;
+                return Ok(());
+            }
+            Err(e) => {
+                self.error_message = format!("Failed to start editor '{}': {}", program, e);
+                self.file_watcher = None;
+                return Ok(());
+            }
         }
 
         let updated_content = fs::read_to_string(temp_file.path())?;
         if updated_content != self.raw_input {
+            self.record_undo();
             self.raw_input = updated_content;
             self.parse_json();
         }
 
         self.temp_file = Some(temp_file);
         self.file_watcher_rx = Some(rx);
-        self.needs_terminal_reinit = true;
-        
+
         Ok(())
     }
 
-    pub fn check_file_changes(&mut self) -> Result<()> {
-        if let Some(ref rx) = self.file_watcher_rx {
-            if let Ok(_event) = rx.try_recv() {
+    /// Writes the session file on exit when sessions are enabled. Nothing worth restoring
+    /// (no valid document) clears any earlier session instead.
+    pub fn save_session(&self) {
+        if !self.save_session {
+            return;
+        }
+        if !self.is_valid {
+            Session::clear();
+            return;
+        }
+        Session {
+            raw_input: self.raw_input.clone(),
+            view_mode: if self.view_mode == ViewMode::Tree { "tree" } else { "raw" }.to_string(),
+            scroll_offset: self.scroll_offset,
+            expanded_paths: self
+                .json_tree
+                .iter()
+                .filter(|node| node.expanded)
+                .map(|node| node.path.clone())
+                .collect(),
+            selected_path: self.json_tree.get(self.selected_node).map(|node| node.path.clone()),
+        }
+        .save();
+    }
+
+    /// Remembers the current view as the one to open in next time.
+    fn store_default_view(&self) {
+        let view = if self.view_mode == ViewMode::Tree { "tree" } else { "raw" };
+        config::store("json", "default_view", view.into());
+    }
+
+    /// Restores the last saved session. A session whose document no longer parses is
+    /// dropped, and paths that no longer exist in the tree are ignored.
+    pub fn restore_session(&mut self) {
+        if !self.save_session {
+            return;
+        }
+        let Some(session) = Session::load() else {
+            return;
+        };
+        let startup_message = std::mem::take(&mut self.error_message);
+        self.raw_input = session.raw_input;
+        self.parse_json();
+        self.error_message = startup_message;
+        if !self.is_valid {
+            tracing::warn!("saved session no longer parses, starting clean");
+            Session::clear();
+            self.raw_input.clear();
+            self.error_position = None;
+            self.clear_parsed_state();
+            return;
+        }
+
+        let expanded: HashSet<String> = session.expanded_paths.into_iter().collect();
+        self.rebuild_tree(|path, _| Some(expanded.contains(path)));
+        if let Some(path) = session.selected_path {
+            if let Some(index) = self.find_node(&path) {
+                self.reveal_node(index);
+            }
+        }
+        if session.view_mode == "tree" {
+            self.view_mode = ViewMode::Tree;
+        }
+        let last_line = self.formatted_json.lines().count().saturating_sub(1);
+        self.scroll_offset = session.scroll_offset.min(last_line);
+        if self.error_message.is_empty() {
+            self.set_status("Restored previous session");
+        }
+    }
+
+    fn no_temp_file(&self) -> String {
+        format!("No temp file - {}", self.keymap.hints(&[(&[Action::CreateTempFile], "create one")]))
+    }
+
+    pub fn copy_temp_file_path(&mut self) -> Result<()> {
+        let Some(ref temp_file) = self.temp_file else {
+            self.error_message = self.no_temp_file();
+            return Ok(());
+        };
+        let path = temp_file.path().display().to_string();
+        self.report(clipboard::try_copy(&path, format!("Copied temp file path: {}", path)));
+        Ok(())
+    }
+
+    /// Opens the temp file's directory in the OS file manager.
+    pub fn reveal_temp_file(&mut self) {
+        let Some(dir) = self.temp_file.as_ref().and_then(|f| f.path().parent().map(Path::to_path_buf)) else {
+            self.error_message = self.no_temp_file();
+            return;
+        };
+        match open::that_detached(&dir) {
+            Ok(_) => self.set_status(format!("Opened {}", dir.display())),
+            Err(e) => self.error_message = format!("Failed to open file manager: {}", e),
+        }
+    }
+
+    /// Stops watching the temp file and deletes it; the loaded document is kept.
+    pub fn forget_temp_file(&mut self) {
+        self.file_watcher = None;
+        self.file_watcher_rx = None;
+        if matches!(self.notice, Some((Notice::Editing, _))) {
+            self.notice = None;
+        }
+        match self.temp_file.take() {
+            Some(temp_file) => {
+                let path = temp_file.path().display().to_string();
+                match temp_file.close() {
+                    Ok(_) => self.set_status(format!("Deleted temp file {}", path)),
+                    Err(e) => {
+                        self.error_message = format!("Stopped watching {}, but deleting it failed: {}", path, e)
+                    }
+                }
+            }
+            None => self.error_message = "No temp file to forget".to_string(),
+        }
+    }
+
+    pub fn check_file_changes(&mut self) -> Result<()> {
+        if let Some(ref rx) = self.file_watcher_rx {
+            if let Ok(event) = rx.try_recv() {
+                tracing::debug!("file watcher event: {:?}", event);
                 if let Some(ref temp_file) = self.temp_file {
                     match fs::read_to_string(temp_file.path()) {
                         Ok(content) => {
                             if content != self.raw_input {
+                                self.record_undo();
                                 self.raw_input = content;
                                 self.parse_json();
                             }
                         }
                         Err(e) => {
+                            tracing::warn!("failed to read watched file: {}", e);
                             self.error_message = format!("Failed to read file: {}", e);
                         }
                     }
@@ -196,161 +1256,2096 @@ impl JsonUtils {
     }
 
     fn parse_json(&mut self) {
-        match serde_json::from_str::<Value>(&self.raw_input) {
+        // Some editors prepend a UTF-8 BOM, which serde_json rejects as an invalid token
+        if let Some(stripped) = self.raw_input.strip_prefix('\u{feff}') {
+            self.raw_input = stripped.to_string();
+        }
+
+        self.error_position = None;
+        self.notice = None;
+
+        if self.raw_input.trim().is_empty() {
+            self.error_message = "Input is empty - nothing to parse".to_string();
+            self.clear_parsed_state();
+            return;
+        }
+
+        let started = Instant::now();
+        let source = if self.lenient_mode {
+            lenient::strip(&self.raw_input)
+        } else {
+            self.raw_input.clone()
+        };
+        let mut result = serde_json::from_str::<Value>(&source);
+        self.fragment_wrap = None;
+        if result.is_err() && self.fragment_mode {
+            if let Some((wrap, value)) = wrap_fragment(&source) {
+                self.fragment_wrap = Some(wrap);
+                result = Ok(value);
+            }
+        }
+        match result {
             Ok(value) => {
-                match serde_json::to_string_pretty(&value) {
-                    Ok(formatted) => {
-                        self.formatted_json = formatted;
-                        self.is_valid = true;
-                        self.error_message.clear();
-                        self.parsed_value = Some(value.clone());
-                        self.build_tree(&value);
-                        self.scroll_offset = 0;
-                    }
-                    Err(e) => {
-                        self.error_message = format!("Format error: {}", e);
-                        self.is_valid = false;
-                        self.parsed_value = None;
-                    }
-                }
+                let duration = started.elapsed();
+                tracing::debug!("parsed {} bytes of JSON in {:?}", self.raw_input.len(), duration);
+                self.show_value(value);
+                self.parse_stats = Some(ParseStats {
+                    bytes: self.raw_input.len(),
+                    duration,
+                    nodes: self.parsed_value.as_ref().map_or(0, count_nodes),
+                });
             }
             Err(e) => {
+                tracing::debug!("parse of {} bytes failed: {}", self.raw_input.len(), e);
                 self.error_message = format!("Invalid JSON: {}", e);
+                self.clear_parsed_state();
+                // serde_json reports 1-based positions, with line 0 meaning there is no location
+                if e.line() > 0 {
+                    self.error_position = Some((e.line(), e.column()));
+                    self.scroll_offset = (ERROR_HEADER_LINES + e.line() - 1).saturating_sub(SCROLL_CONTEXT_LINES);
+                }
+            }
+        }
+    }
+
+    /// Regenerates every derived view of the document (text, tree, mask) from `value`.
+    fn show_value(&mut self, value: Value) {
+        match to_pretty(&value, self.indent) {
+            Ok(formatted) => {
+                self.formatted_json = formatted;
+                self.minified_json = serde_json::to_string(&value).unwrap_or_default();
+                self.is_valid = true;
+                self.error_message.clear();
+                self.parsed_value = Some(value);
+                self.build_tree();
+                self.check_schema();
+                self.scroll_offset = 0;
+                self.horizontal_scroll = 0;
+                self.visual = None;
+                self.search = None;
+                if self.masked.is_some() {
+                    self.masked = self.build_masked();
+                }
+            }
+            Err(e) => {
+                self.error_message = format!("Format error: {}", e);
                 self.is_valid = false;
-                self.formatted_json.clear();
                 self.parsed_value = None;
-                self.json_tree.clear();
             }
         }
     }
 
-    fn build_tree(&mut self, value: &Value) {
+    /// Sorts arrays of objects by a field so documents returned in different orders line up.
+    /// The sorted document replaces `raw_input`, so saving, editing and re-parsing keep it.
+    fn sort_arrays_by(&mut self, key: &str) {
+        let Some(mut value) = self.parsed_value.clone() else {
+            self.error_message = "No valid JSON to sort".to_string();
+            return;
+        };
+        self.record_undo();
+        let sorted = transform::sort_arrays_by_key(&mut value, key);
+        self.load_value(&value);
+        self.set_status(format!("Sorted {} array(s) by '{}'", sorted, key));
+    }
+
+    /// Shows the document with every object's keys in alphabetical order, leaving
+    /// `raw_input` as pasted so it can still be re-parsed unsorted.
+    fn sort_keys(&mut self) {
+        let Some(mut value) = self.parsed_value.clone() else {
+            self.error_message = "No valid JSON to sort".to_string();
+            return;
+        };
+        // Objects keep their source order, so there's only something to do when one is out of order
+        if transform::sort_keys(&mut value) == 0 {
+            self.set_status("Keys are already in order");
+            return;
+        }
+        self.record_undo();
+        self.show_value(value);
+        self.set_status("Sorted keys");
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            raw_input: self.raw_input.clone(),
+            value: self.parsed_value.clone(),
+        }
+    }
+
+    /// Saves the document for undo before it changes. An empty buffer has nothing to go
+    /// back to and isn't recorded.
+    fn record_undo(&mut self) {
+        if !self.raw_input.is_empty() {
+            let snapshot = self.snapshot();
+            self.history.record(snapshot);
+        }
+    }
+
+    fn restore_snapshot(&mut self, snapshot: Snapshot) {
+        self.raw_input = snapshot.raw_input;
+        match snapshot.value {
+            Some(value) => {
+                self.error_position = None;
+                self.show_value(value);
+            }
+            None => self.parse_json(),
+        }
+    }
+
+    fn undo(&mut self) {
+        let current = self.snapshot();
+        match self.history.undo(current) {
+            Some(previous) => {
+                self.restore_snapshot(previous);
+                self.set_status("Undone");
+            }
+            None => self.error_message = "Nothing to undo".to_string(),
+        }
+    }
+
+    fn redo(&mut self) {
+        let current = self.snapshot();
+        match self.history.redo(current) {
+            Some(next) => {
+                self.restore_snapshot(next);
+                self.set_status("Redone");
+            }
+            None => self.error_message = "Nothing to redo".to_string(),
+        }
+    }
+
+    /// Lists every value found under `key` anywhere in the document as `path = value` lines.
+    fn extract_values(&mut self, key: &str) {
+        let Some(ref value) = self.parsed_value else {
+            self.error_message = "No valid JSON to extract from".to_string();
+            return;
+        };
+        let source = self.masked.as_ref().map_or(value, |masked| &masked.value);
+        let found = transform::values_at_key(source, key);
+        if found.is_empty() {
+            self.error_message = format!("No values found under '{}'", key);
+            return;
+        }
+        let text = found
+            .iter()
+            .map(|(path, value)| format!("{} = {}", path, serde_json::to_string(value).unwrap_or_default()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.converted = Some(ConvertedOutput {
+            title: format!("Values of '{}' ({})", key, found.len()),
+            diff: None,
+            flat: None,
+            text,
+        });
+        self.view_mode = ViewMode::Converted;
+        self.scroll_offset = 0;
+    }
+
+    /// Lists every leaf of the document as a `path = value` line, empty objects and arrays
+    /// included, in a view that can be sorted by value and filtered.
+    fn flatten_document(&mut self) {
+        let Some(ref value) = self.parsed_value else {
+            return;
+        };
+        let source = self.masked.as_ref().map_or(value, |masked| &masked.value);
+        let entries = transform::flatten(source)
+            .into_iter()
+            .map(|(path, leaf)| (path, serde_json::to_string(leaf).unwrap_or_default()))
+            .collect();
+        self.converted = Some(ConvertedOutput {
+            title: String::new(),
+            diff: None,
+            flat: Some(FlatView {
+                entries,
+                by_value: false,
+                filter: String::new(),
+            }),
+            text: String::new(),
+        });
+        self.refresh_flat_view();
+        self.view_mode = ViewMode::Converted;
+        self.scroll_offset = 0;
+    }
+
+    /// Renders the flattened lines and title again after the sort or filter changed.
+    fn refresh_flat_view(&mut self) {
+        let Some(converted) = self.converted.as_mut() else {
+            return;
+        };
+        let Some(ref flat) = converted.flat else {
+            return;
+        };
+        let lines = flat.lines();
+        converted.title = flat.title(lines.len());
+        converted.text = lines.join("\n");
+        self.scroll_offset = 0;
+    }
+
+    /// Switches the flattened view between document order and sorted by value.
+    fn toggle_flat_sort(&mut self) {
+        if let Some(flat) = self.converted.as_mut().and_then(|converted| converted.flat.as_mut()) {
+            flat.by_value = !flat.by_value;
+        }
+        self.refresh_flat_view();
+    }
+
+    fn filter_flat_view(&mut self, filter: &str) {
+        if let Some(flat) = self.converted.as_mut().and_then(|converted| converted.flat.as_mut()) {
+            flat.filter = filter.to_string();
+        }
+        self.refresh_flat_view();
+        if self.converted.as_ref().is_some_and(|converted| converted.text.is_empty()) {
+            self.error_message = format!("No leaves match '{}'", filter);
+        }
+    }
+
+    /// Lists subtrees that appear more than once, each group with the paths it occurs at.
+    fn find_repeated_subtrees(&mut self) {
+        let Some(ref value) = self.parsed_value else {
+            return;
+        };
+        let groups = transform::repeated_subtrees(value);
+        if groups.is_empty() {
+            self.set_status("No repeated subtrees");
+            return;
+        }
+        let text = groups
+            .iter()
+            .map(|group| {
+                let paths: Vec<String> = group.paths.iter().map(|path| format!("  {}", path)).collect();
+                format!("{} copies, {}:\n{}", group.paths.len(), format_bytes(group.size), paths.join("\n"))
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        self.converted = Some(ConvertedOutput {
+            title: format!("Repeated subtrees ({} groups)", groups.len()),
+            diff: None,
+            flat: None,
+            text,
+        });
+        self.view_mode = ViewMode::Converted;
+        self.scroll_offset = 0;
+    }
+
+    /// Shows what changed going from the JSON on the clipboard to the current document.
+    fn compare_with_clipboard(&mut self) {
+        self.paste_for(ClipboardUse::Compare);
+    }
+
+    fn compare_with_pasted(&mut self, text: &str) {
+        let Some(ref current) = self.parsed_value else {
+            return;
+        };
+        let current = current.clone();
+        match pasted_json(text) {
+            Ok(other) => self.show_diff(&other, current, "clipboard"),
+            Err(e) => self.error_message = e,
+        }
+    }
+
+    /// Generates the JSON Patch that turns the JSON on the clipboard into the current
+    /// document, copies it and shows it in the output view.
+    fn patch_from_clipboard(&mut self) {
+        self.paste_for(ClipboardUse::Patch);
+    }
+
+    fn patch_from_pasted(&mut self, text: &str) {
+        let Some(ref current) = self.parsed_value else {
+            return;
+        };
+        let base = match pasted_json(text) {
+            Ok(base) => base,
+            Err(e) => {
+                self.error_message = e;
+                return;
+            }
+        };
+        let patch = patch::generate(&base, current);
+        let count = patch.as_array().map_or(0, Vec::len);
+        if count == 0 {
+            self.set_status("No differences from clipboard");
+            return;
+        }
+        let text = to_pretty(&patch, self.indent).unwrap_or_default();
+        self.report(clipboard::try_copy(&text, format!("Copied JSON Patch ({} operations)", count)));
+        self.converted = Some(ConvertedOutput {
+            title: format!("JSON Patch from clipboard ({} operations)", count),
+            diff: None,
+            flat: None,
+            text,
+        });
+        self.view_mode = ViewMode::Converted;
+        self.scroll_offset = 0;
+    }
+
+    /// Applies the JSON Patch on the clipboard to the document, as one undoable edit.
+    /// Nothing changes if any operation fails.
+    fn apply_patch_from_clipboard(&mut self) {
+        self.paste_for(ClipboardUse::ApplyPatch);
+    }
+
+    fn apply_pasted_patch(&mut self, text: &str) {
+        let Some(ref current) = self.parsed_value else {
+            return;
+        };
+        let result = pasted_json(text).and_then(|patch| Ok((patch::apply(current, &patch)?, patch.as_array().map_or(0, Vec::len))));
+        match result {
+            Ok((patched, count)) => {
+                let select = self.json_tree.get(self.selected_node).map(|node| node.path.clone()).unwrap_or_default();
+                self.apply_edit(patched, str::to_string, &select);
+                if self.view_mode == ViewMode::Converted {
+                    self.view_mode = ViewMode::Raw;
+                }
+                let undo = self.keymap.hints(&[(&[Action::Undo], "undo")]);
+                self.set_status(format!("Applied {} patch operations from clipboard ({})", count, undo));
+            }
+            Err(e) => self.error_message = format!("Patch not applied: {}", e),
+        }
+    }
+
+
+    /// Shows what changed going from the file at `path` to the current document.
+    fn compare_with_file(&mut self, path: &str) {
+        let Some(ref current) = self.parsed_value else {
+            return;
+        };
+        let path = expand_home(path);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.error_message = format!("Can't read {}: {}", path.display(), e);
+                return;
+            }
+        };
+        let other: Value = match serde_json::from_str(contents.trim_start_matches('\u{feff}')) {
+            Ok(value) => value,
+            Err(e) => {
+                self.error_message = format!("{} is not valid JSON: {}", path.display(), e);
+                return;
+            }
+        };
+        self.show_diff(&other, current.clone(), &path.display().to_string());
+    }
+
+    /// Starts checking the document against the schema at `path`, or stops for an empty path.
+    fn load_schema(&mut self, path: &str) {
+        if path.is_empty() {
+            if self.schema.take().is_some() {
+                self.schema_violations.clear();
+                self.set_status("Schema unloaded");
+            }
+            return;
+        }
+        let path = expand_home(path);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.error_message = format!("Can't read schema {}: {}", path.display(), e);
+                return;
+            }
+        };
+        let schema = serde_json::from_str(contents.trim_start_matches('\u{feff}'))
+            .map_err(|e| format!("not valid JSON: {}", e))
+            .and_then(Schema::compile);
+        match schema {
+            Ok(schema) => {
+                // Unknown keywords are ignored, but saying which keeps a pass from overstating
+                let unchecked = match schema.unchecked() {
+                    [] => String::new(),
+                    [first] => format!(", not checked: {}", first),
+                    [first, rest @ ..] => format!(", not checked: {} and {} more", first, rest.len()),
+                };
+                self.schema = Some((path, schema));
+                self.check_schema();
+                self.set_status(format!("Loaded schema, {} violation(s){}", self.schema_violations.len(), unchecked));
+            }
+            // Kept apart from violations: the document wasn't checked at all
+            Err(e) => self.error_message = format!("Invalid schema {}: {}", path.display(), e),
+        }
+    }
+
+    /// Re-validates the document against the loaded schema, if any.
+    fn check_schema(&mut self) {
+        self.schema_scroll = 0;
+        self.schema_violations = match (&self.schema, &self.parsed_value) {
+            (Some((_, schema)), Some(value)) => schema.validate(value),
+            _ => Vec::new(),
+        };
+    }
+
+    /// Renders the structural diff from `base` to `current` in the output view.
+    fn show_diff(&mut self, base: &Value, current: Value, base_name: &str) {
+        let changes = diff::diff(base, &current);
+        if changes.is_empty() {
+            self.set_status(format!("No differences from {}", base_name));
+            return;
+        }
+        self.converted = Some(ConvertedOutput {
+            title: format!("Diff: {} change(s) from {}", changes.len(), base_name),
+            text: diff::render(&changes),
+            flat: None,
+            diff: Some(DiffView {
+                changes,
+                base_name: base_name.to_string(),
+                selected: 0,
+            }),
+        });
+        self.horizontal_scroll = 0;
+        self.view_mode = ViewMode::Converted;
+        self.scroll_offset = 0;
+    }
+
+    fn submit_prompt(&mut self, prompt: Prompt) {
+        let input = prompt.input.trim();
+        // These take an empty input to switch themselves off
+        if input.is_empty()
+            && !matches!(
+                prompt.kind,
+                PromptKind::SearchText
+                    | PromptKind::SearchTree
+                    | PromptKind::FilterFlat
+                    | PromptKind::LoadSchema
+                    | PromptKind::ExportCsv
+                    | PromptKind::EditValue(_)
+            )
+        {
+            return;
+        }
+        match prompt.kind {
+            PromptKind::SortArraysBy => self.sort_arrays_by(input),
+            PromptKind::ExtractValues => self.extract_values(input),
+            PromptKind::CompareFile => self.compare_with_file(input),
+            PromptKind::SearchText => self.search_text(input),
+            PromptKind::SearchTree => {
+                self.search_tree(input);
+                if input.is_empty() {
+                    self.set_status("Search cleared");
+                } else if self.tree_search.as_ref().is_some_and(TreeSearch::is_empty) {
+                    self.error_message = format!("No matches for '{}'", input);
+                }
+            }
+            PromptKind::FilterFlat => self.filter_flat_view(input),
+            PromptKind::TypeScriptRoot => self.copy_typescript(input),
+            PromptKind::PasteFromFile(purpose) => self.paste_from_file(input, purpose),
+            PromptKind::OpenFile => self.paste_from_file(input, ClipboardUse::Paste),
+            PromptKind::ExportFile { minified } => self.export_to_file(input, minified),
+            PromptKind::ExportCsv => self.export_csv(input),
+            // Untrimmed, as spaces can be part of a string
+            PromptKind::EditValue(value_type) => self.edit_selected_value(value_type, &prompt.input),
+            PromptKind::LoadSchema => self.load_schema(input),
+            PromptKind::GoToPath => {
+                self.path_query = input.to_string();
+                self.go_to_path();
+            }
+            PromptKind::ConfirmOverwrite => match self.pending_write.take() {
+                Some(write) if input.eq_ignore_ascii_case("y") => self.finish_write(write),
+                Some(write) => self.set_status(format!("Left {} as it was", write.path.display())),
+                None => {}
+            },
+            PromptKind::ConfirmDelete => {
+                if input.eq_ignore_ascii_case("y") {
+                    self.delete_selected_node();
+                }
+            }
+            PromptKind::ExpandToDepth => match input.parse() {
+                Ok(depth) => self.expand_to_depth(depth),
+                Err(_) => self.error_message = format!("'{}' is not a depth", input),
+            },
+        }
+    }
+
+    fn handle_prompt_key(&mut self, key: KeyEvent) {
+        let Some(ref mut prompt) = self.prompt else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                if prompt.kind == PromptKind::SearchTree {
+                    self.tree_search = None;
+                }
+                self.prompt = None;
+                self.pending_write = None;
+                return;
+            }
+            KeyCode::Enter => {
+                if let Some(prompt) = self.prompt.take() {
+                    self.submit_prompt(prompt);
+                }
+                return;
+            }
+            KeyCode::Tab => {
+                match prompt.kind {
+                    PromptKind::EditValue(value_type) => prompt.kind = PromptKind::EditValue(value_type.next()),
+                    PromptKind::OpenFile | PromptKind::PasteFromFile(_) => {
+                        let (completed, matches) = complete_path(&prompt.input);
+                        prompt.input = completed;
+                        if matches.len() > 1 {
+                            const SHOWN: usize = 8;
+                            let mut listing = matches[..matches.len().min(SHOWN)].join("  ");
+                            if matches.len() > SHOWN {
+                                listing.push_str(&format!("  (+{} more)", matches.len() - SHOWN));
+                            }
+                            self.set_status(listing);
+                        }
+                    }
+                    _ => {}
+                }
+                return;
+            }
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Char(c) => prompt.input.push(c),
+            _ => return,
+        }
+        // Tree search follows the query as it's typed
+        if let Some(query) = self.prompt.as_ref().filter(|p| p.kind == PromptKind::SearchTree).map(|p| p.input.clone()) {
+            self.search_tree(query.trim());
+        }
+    }
+
+    fn render_prompt(&self, frame: &mut Frame, area: Rect, prompt: &Prompt) {
+        let title = format!("{} (Enter to confirm, Esc to cancel)", prompt.kind.label());
+        let input = Paragraph::new(prompt.input.as_str())
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(input, area);
+        frame.set_cursor_position((area.x + prompt.input.chars().count() as u16 + 1, area.y + 1));
+    }
+
+    fn clear_parsed_state(&mut self) {
+        self.remember_expansion();
+        self.is_valid = false;
+        self.notice = None;
+        self.parse_stats = None;
+        self.masked = None;
+        self.visual = None;
+        self.search = None;
+        self.formatted_json.clear();
+        self.minified_json.clear();
+        self.parsed_value = None;
+        self.json_tree.clear();
+        self.tree_changed();
+        self.tree_search = None;
+        self.schema_violations.clear();
+    }
+
+    /// Records the current tree's expansion so a re-parse of the same document keeps it.
+    fn remember_expansion(&mut self) {
+        for node in &self.json_tree {
+            self.expansion_memory.insert(node.path.clone(), node.expanded);
+        }
+    }
+
+    /// The document the tree shows.
+    fn document(&self) -> &Value {
+        self.parsed_value.as_ref().unwrap_or(&Value::Null)
+    }
+
+    fn node_value(&self, index: usize) -> &Value {
+        self.json_tree[index].value(self.document())
+    }
+
+    fn build_tree(&mut self) {
+        self.tree_search = None;
+        self.rebuild_tree(|_, _| None);
+        // Bookmarks are keyed by path, so they outlive a re-parse as long as the path still exists
+        let bookmarks = std::mem::take(&mut self.bookmarks);
+        self.bookmarks = bookmarks.into_iter().filter(|path| self.find_node(path).is_some()).collect();
+    }
+
+    /// Lays the tree out again from the document, keeping the selection. A node is expanded
+    /// if `expanded` says so, else as it was last seen, else when it's above
+    /// `auto_expand_depth`; only expanded nodes have their children loaded.
+    fn rebuild_tree(&mut self, expanded: impl Fn(&str, usize) -> Option<bool>) {
+        self.remember_expansion();
+        let selected_path = self.json_tree.get(self.selected_node).map(|node| node.path.clone());
         self.json_tree.clear();
+        self.tree_changed();
         self.selected_node = 0;
-        self.build_tree_recursive(value, "", 0, "root");
+        let Some(ref document) = self.parsed_value else {
+            return;
+        };
+        let expanded = |path: &str, depth: usize| {
+            expanded(path, depth)
+                .or_else(|| self.expansion_memory.get(path).copied())
+                .unwrap_or(depth < self.auto_expand_depth)
+        };
+        let mut nodes = Vec::new();
+        push_subtree(document, String::new(), String::new(), "root".to_string(), 0, &expanded, &mut nodes);
+        self.json_tree = nodes;
+        self.tree_changed();
+        if let Some(index) = selected_path.and_then(|path| self.find_node(&path)) {
+            self.selected_node = index;
+        }
+    }
+
+    /// Adds the children of the container at `index` right after it, if they aren't there
+    /// yet. Children that start out expanded bring their own children along.
+    fn load_children(&mut self, index: usize) {
+        let node = &self.json_tree[index];
+        if node.loaded || !node.container {
+            return;
+        }
+        let Some(ref document) = self.parsed_value else {
+            return;
+        };
+        let expanded = |path: &str, depth: usize| {
+            self.expansion_memory.get(path).copied().unwrap_or(depth < self.auto_expand_depth)
+        };
+        let mut children = Vec::new();
+        push_children(&node.pointer, &node.path, node.depth, node.value(document), &expanded, &mut children);
+        let count = children.len();
+        self.json_tree[index].loaded = true;
+        self.json_tree.splice(index + 1..index + 1, children);
+        self.tree_changed();
+        if self.selected_node > index {
+            self.selected_node += count;
+        }
+    }
+
+    /// Index of the node at `path`, loading the children of its ancestors on the way down.
+    /// They aren't expanded; `reveal_node` does that.
+    fn find_node(&mut self, path: &str) -> Option<usize> {
+        let mut index = 0;
+        loop {
+            let node = self.json_tree.get(index)?;
+            if node.path == path {
+                return Some(index);
+            }
+            let depth = node.depth;
+            self.load_children(index);
+            // Keys can contain `.` and `[`, so several children may lead towards `path`;
+            // the longest is the most specific
+            index = (index + 1..self.json_tree.len())
+                .take_while(|&i| self.json_tree[i].depth > depth)
+                .filter(|&i| self.json_tree[i].depth == depth + 1)
+                .filter(|&i| is_path_prefix(&self.json_tree[i].path, path))
+                .max_by_key(|&i| self.json_tree[i].path.len())?;
+        }
+    }
+
+    fn toggle_node(&mut self) {
+        if self.selected_node < self.json_tree.len() {
+            let node = &mut self.json_tree[self.selected_node];
+            if node.container {
+                node.expanded = !node.expanded;
+                self.tree_changed();
+                self.load_children(self.selected_node);
+            }
+        }
+    }
+
+    /// Selects the node at `index`, expanding every collapsed ancestor so it becomes visible.
+    fn reveal_node(&mut self, index: usize) {
+        if index >= self.json_tree.len() {
+            return;
+        }
+        for ancestor in self.ancestor_chain(index) {
+            if ancestor != index {
+                self.json_tree[ancestor].expanded = true;
+            }
+        }
+        self.tree_changed();
+        self.selected_node = index;
+    }
+
+    fn toggle_bookmark(&mut self) {
+        if let Some(node) = self.json_tree.get(self.selected_node) {
+            let path = node.path.clone();
+            if !self.bookmarks.remove(&path) {
+                self.bookmarks.insert(path);
+            }
+        }
+    }
+
+    fn jump_to_bookmark(&mut self, forward: bool) {
+        // Bookmarks under a collapsed node may not be loaded since the tree was laid out
+        for path in self.bookmarks.clone() {
+            self.find_node(&path);
+        }
+        let marked: Vec<usize> = self
+            .json_tree
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| self.bookmarks.contains(&node.path))
+            .map(|(i, _)| i)
+            .collect();
+        if marked.is_empty() {
+            self.error_message = "No bookmarks set".to_string();
+            return;
+        }
+        let target = if forward {
+            marked.iter().find(|&&i| i > self.selected_node).or(marked.first())
+        } else {
+            marked.iter().rev().find(|&&i| i < self.selected_node).or(marked.last())
+        };
+        if let Some(&index) = target {
+            self.reveal_node(index);
+        }
+    }
+
+    fn expand_all(&mut self) {
+        self.rebuild_tree(|_, _| Some(true));
+    }
+
+    /// Collapses every node, leaving only the root visible and selected.
+    fn collapse_all(&mut self) {
+        for node in &mut self.json_tree {
+            node.expanded = false;
+        }
+        self.tree_changed();
+        self.selected_node = 0;
+    }
+
+    /// Expands (`expand`) or collapses the selected node and everything under it.
+    fn set_subtree_expansion(&mut self, expand: bool) {
+        let Some(selected) = self.json_tree.get(self.selected_node).map(|node| node.path.clone()) else {
+            return;
+        };
+        self.rebuild_tree(|path, _| is_path_prefix(&selected, path).then_some(expand));
+    }
+
+    /// Levels of the tree on screen: one below the deepest visible expanded node.
+    fn shown_depth(&self) -> usize {
+        self.visible_indices()
+            .iter()
+            .map(|&index| &self.json_tree[index])
+            .filter(|node| node.expanded)
+            .map(|node| node.depth + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Shows one level more (`deeper`) or less than now, evenly across the tree.
+    fn step_depth(&mut self, deeper: bool) {
+        let current = self.shown_depth();
+        let depth = if deeper {
+            if current >= nesting_depth(self.document()) {
+                self.set_status("Everything is expanded");
+                return;
+            }
+            current + 1
+        } else {
+            current.saturating_sub(1)
+        };
+        self.expand_to_depth(depth);
+        self.set_status(format!("Expanded to depth {}", depth));
+    }
+
+    /// Expands every node above `depth` and collapses the rest, moving the selection up to
+    /// the nearest ancestor that is still visible.
+    fn expand_to_depth(&mut self, depth: usize) {
+        self.rebuild_tree(|_, node_depth| Some(node_depth < depth));
+        if self.selected_node < self.json_tree.len() {
+            let chain = self.ancestor_chain(self.selected_node);
+            if let Some(&visible) = chain.iter().find(|&&i| !self.json_tree[i].expanded) {
+                self.selected_node = visible;
+            }
+        }
+    }
+
+    /// Selects the node at `path_query`, expanding its ancestors. A query that runs past the
+    /// end of the document still selects the deepest node it reaches.
+    fn go_to_path(&mut self) {
+        let query = self.path_query.clone();
+        let query = query.trim();
+        let query = query
+            .strip_prefix("$.")
+            .or_else(|| query.strip_prefix("root."))
+            .or_else(|| query.strip_prefix('.'))
+            .unwrap_or(query);
+        let query = query.strip_prefix('$').unwrap_or(query);
+        if query.is_empty() || query == "root" {
+            self.reveal_node(0);
+            return;
+        }
+
+        let mut prefix = query;
+        loop {
+            if let Some(index) = self.find_node(prefix) {
+                self.reveal_node(index);
+                if prefix.len() < query.len() {
+                    self.set_status(format!("Partial match: {} (nothing at {})", prefix, query));
+                }
+                return;
+            }
+            // Drop the last `.key` or `[index]` segment and try again
+            match prefix.rfind(['.', '[']) {
+                Some(cut) if cut > 0 => prefix = &prefix[..cut],
+                _ => break,
+            }
+        }
+        self.error_message = format!("Path not found: {}", query);
+    }
+
+    /// Highlights every occurrence of `query` in the raw view and scrolls to the first.
+    fn search_text(&mut self, query: &str) {
+        if query.is_empty() {
+            self.search = None;
+            self.set_status("Search cleared");
+            return;
+        }
+        let search = TextSearch::new(self.displayed_pretty(), query);
+        if search.is_empty() {
+            self.error_message = format!("No matches for '{}'", query);
+        }
+        self.search = Some(search);
+        self.scroll_to_match();
+    }
+
+    /// Matches nodes by key or scalar value and selects the first match from the current node
+    /// on, expanding whatever hides it. Array indices aren't keys, and masked values aren't
+    /// searched.
+    fn search_tree(&mut self, query: &str) {
+        if query.is_empty() {
+            self.tree_search = None;
+            return;
+        }
+        let masking = self.masked.is_some();
+        let selected_path = self.json_tree.get(self.selected_node).map(|node| node.path.clone());
+        let mut search = TreeSearch::new(query);
+        let mut selected = 0;
+        let mut ordinal = 0;
+        walk_nodes(self.document(), &mut String::new(), "", &mut |path, key, value| {
+            if selected_path.as_deref() == Some(path) {
+                selected = ordinal;
+            }
+            let text = match value {
+                _ if masking && self.masker.is_sensitive(key) => None,
+                Value::Object(_) | Value::Array(_) => None,
+                Value::String(s) => Some(s.clone()),
+                scalar => Some(scalar.to_string()),
+            };
+            search.check(ordinal, path, key, text.as_deref());
+            ordinal += 1;
+        });
+        search.start_from(selected);
+        self.tree_search = Some(search);
+        self.reveal_tree_match();
+    }
+
+    /// Selects the current tree search match, expanding whatever hides it.
+    fn reveal_tree_match(&mut self) {
+        let path = self.tree_search.as_ref().and_then(TreeSearch::current).map(str::to_string);
+        if let Some(index) = path.and_then(|path| self.find_node(&path)) {
+            self.reveal_node(index);
+        }
+    }
+
+    fn cycle_tree_match(&mut self, forward: bool) {
+        let Some(ref mut search) = self.tree_search else {
+            return;
+        };
+        if forward {
+            search.next();
+        } else {
+            search.previous();
+        }
+        self.reveal_tree_match();
+    }
+
+    fn cycle_match(&mut self, forward: bool) {
+        if let Some(ref mut search) = self.search {
+            if forward {
+                search.next();
+            } else {
+                search.previous();
+            }
+        }
+        self.scroll_to_match();
+    }
+
+    fn scroll_to_match(&mut self) {
+        if let Some(line) = self.search.as_ref().and_then(TextSearch::current_line) {
+            self.scroll_offset = line.saturating_sub(SCROLL_CONTEXT_LINES);
+        }
+    }
+
+    /// Line of `formatted_json` the node at `index` starts on: its parent's line, one for the
+    /// parent's opening bracket, and the lines of the siblings before it.
+    fn node_line(&self, index: usize) -> usize {
+        if let Some(line) = self.json_tree[index].line.get() {
+            return line;
+        }
+        let line = match self.ancestor_chain(index).iter().rev().nth(1) {
+            None => 0,
+            Some(&parent) => {
+                let key = &self.json_tree[index].key;
+                let before: usize = match self.node_value(parent) {
+                    Value::Object(obj) => obj.iter().take_while(|(k, _)| *k != key).map(|(_, v)| pretty_lines(v)).sum(),
+                    Value::Array(items) => {
+                        let position = key.trim_start_matches('[').trim_end_matches(']').parse().unwrap_or(0);
+                        items.iter().take(position).map(pretty_lines).sum()
+                    }
+                    _ => 0,
+                };
+                self.node_line(parent) + 1 + before
+            }
+        };
+        self.json_tree[index].line.set(Some(line));
+        line
+    }
+
+    /// Walks up from `index` to the root, returning the node indices from the root down.
+    fn ancestor_chain(&self, index: usize) -> Vec<usize> {
+        let mut chain = vec![index];
+        let mut depth = self.json_tree[index].depth;
+        for (i, node) in self.json_tree[..index].iter().enumerate().rev() {
+            if depth == 0 {
+                break;
+            }
+            if node.depth < depth {
+                chain.push(i);
+                depth = node.depth;
+            }
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Renders the path of a node in JavaScript accessor style, e.g. `items[0]["display name"]`.
+    /// Unlike `path`, keys that aren't identifiers are quoted, so a key containing a dot
+    /// can't be mistaken for two levels.
+    fn dotted_path(&self, index: usize) -> String {
+        let chain = self.ancestor_chain(index);
+        let mut path = String::new();
+        for pair in chain.windows(2) {
+            let child = &self.json_tree[pair[1]];
+            if self.node_value(pair[0]).is_array() {
+                path.push_str(&child.key);
+            } else if is_jq_identifier(&child.key) {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(&child.key);
+            } else {
+                path.push_str(&format!("[{}]", serde_json::to_string(&child.key).unwrap_or_default()));
+            }
+        }
+        if path.is_empty() {
+            path.push_str("root");
+        }
+        path
+    }
+
+    /// Renders the path of a node as a jq filter, e.g. `.items[0]."display name"`.
+    fn jq_path(&self, index: usize) -> String {
+        let chain = self.ancestor_chain(index);
+        let mut filter = String::new();
+        for pair in chain.windows(2) {
+            let child = &self.json_tree[pair[1]];
+            if self.node_value(pair[0]).is_array() {
+                filter.push_str(&child.key);
+            } else if is_jq_identifier(&child.key) {
+                filter.push('.');
+                filter.push_str(&child.key);
+            } else {
+                let quoted = serde_json::to_string(&child.key).unwrap_or_default();
+                filter.push('.');
+                filter.push_str(&quoted);
+            }
+        }
+        if filter.is_empty() || filter.starts_with('[') {
+            filter.insert(0, '.');
+        }
+        filter
+    }
+
+    /// Replaces the document with an edited value. The buffer is re-serialized so the edit
+    /// is what gets copied or saved, and expansion state carries over by path, with
+    /// `rename` mapping old paths to where their nodes ended up. `select` is the path to
+    /// select afterwards.
+    fn apply_edit(&mut self, value: Value, rename: impl Fn(&str) -> String, select: &str) {
+        let expanded: HashMap<String, bool> = self
+            .json_tree
+            .iter()
+            .map(|node| (rename(&node.path), node.expanded))
+            .collect();
+        let scroll_offset = self.scroll_offset;
+
+        self.record_undo();
+        self.raw_input = to_pretty(&value, self.indent).unwrap_or_default();
+        self.parse_json();
+        self.rebuild_tree(|path, _| expanded.get(path).copied());
+        if let Some(index) = self.find_node(select) {
+            self.reveal_node(index);
+        }
+        self.scroll_offset = scroll_offset;
+    }
+
+    /// Swaps the selected array element with its previous (`up`) or next sibling.
+    fn move_array_element(&mut self, up: bool) {
+        let index = self.selected_node;
+        let Some(&parent) = self.ancestor_chain(index).iter().rev().nth(1) else {
+            return;
+        };
+        let parent_node = &self.json_tree[parent];
+        let Some(len) = self.node_value(parent).as_array().map(Vec::len) else {
+            self.error_message = "Only array elements can be moved".to_string();
+            return;
+        };
+        let Ok(from) = self.json_tree[index].key.trim_start_matches('[').trim_end_matches(']').parse::<usize>() else {
+            return;
+        };
+        let to = if up { from.checked_sub(1) } else { Some(from + 1).filter(|&to| to < len) };
+        let Some(to) = to else {
+            self.error_message = "Element is already at the edge of its array".to_string();
+            return;
+        };
+
+        let child_path = |i: usize| {
+            if parent_node.path == "root" {
+                format!("[{}]", i)
+            } else {
+                format!("{}[{}]", parent_node.path, i)
+            }
+        };
+        let (from_path, to_path) = (child_path(from), child_path(to));
+        let pointer = self.json_tree[parent].pointer.clone();
+        let Some(mut value) = self.parsed_value.clone() else {
+            return;
+        };
+        if let Some(items) = value.pointer_mut(&pointer).and_then(Value::as_array_mut) {
+            items.swap(from, to);
+        }
+        self.apply_edit(
+            value,
+            |path| {
+                rebase_path(path, &from_path, &to_path)
+                    .or_else(|| rebase_path(path, &to_path, &from_path))
+                    .unwrap_or_else(|| path.to_string())
+            },
+            &to_path,
+        );
+    }
+
+    /// Deletes the selected node, asking first when it is a non-empty container.
+    fn request_delete(&mut self) {
+        let Some(node) = self.json_tree.get(self.selected_node) else {
+            return;
+        };
+        if node.depth == 0 {
+            self.error_message = "The root can't be deleted".to_string();
+            return;
+        }
+        let non_empty = match node.value(self.document()) {
+            Value::Object(obj) => !obj.is_empty(),
+            Value::Array(items) => !items.is_empty(),
+            _ => false,
+        };
+        if non_empty {
+            self.prompt = Some(Prompt::new(PromptKind::ConfirmDelete));
+        } else {
+            self.delete_selected_node();
+        }
+    }
+
+    /// Value of the selected node when it's a scalar.
+    fn selected_leaf(&self) -> Option<&Value> {
+        self.json_tree
+            .get(self.selected_node)
+            .map(|node| node.value(self.document()))
+            .filter(|value| !value.is_object() && !value.is_array())
+    }
+
+    /// Replaces the selected leaf with `input` read as `value_type`.
+    fn edit_selected_value(&mut self, value_type: ValueType, input: &str) {
+        let new_value = match value_type.parse(input) {
+            Ok(value) => value,
+            Err(e) => {
+                self.error_message = e;
+                return;
+            }
+        };
+        let Some(mut value) = self.parsed_value.clone() else {
+            return;
+        };
+        let Some(node) = self.json_tree.get(self.selected_node) else {
+            return;
+        };
+        let path = node.path.clone();
+        let pointer = self.json_tree[self.selected_node].pointer.clone();
+        let Some(target) = value.pointer_mut(&pointer) else {
+            return;
+        };
+        *target = new_value;
+        self.apply_edit(value, str::to_string, &path);
+        self.set_status(format!("Set {}", self.jq_path(self.selected_node)));
+    }
+
+    /// Parses strings holding JSON into the values they hold, under the selected node in
+    /// the tree or across the whole document elsewhere, as one undoable edit.
+    fn unwrap_stringified(&mut self) {
+        let Some(mut value) = self.parsed_value.clone() else {
+            return;
+        };
+        let (pointer, path) = match self.json_tree.get(self.selected_node) {
+            Some(node) if self.view_mode == ViewMode::Tree => (node.pointer.clone(), node.path.clone()),
+            _ => (String::new(), String::new()),
+        };
+        let Some(target) = value.pointer_mut(&pointer) else {
+            return;
+        };
+        let count = transform::unwrap_stringified(target);
+        let place = if pointer.is_empty() {
+            "the document".to_string()
+        } else {
+            self.jq_path(self.selected_node)
+        };
+        if count == 0 {
+            self.set_status(format!("No strings holding JSON in {}", place));
+            return;
+        }
+        self.apply_edit(value, str::to_string, &path);
+        self.set_status(format!("Unwrapped {} JSON string(s) in {}", count, place));
+    }
+
+    /// Removes the selected node from its parent and selects the previous sibling, or the
+    /// next one when it was first, or the parent when it was the only child.
+    fn delete_selected_node(&mut self) {
+        let index = self.selected_node;
+        let Some(&parent) = self.ancestor_chain(index).iter().rev().nth(1) else {
+            return;
+        };
+        let Some(mut value) = self.parsed_value.clone() else {
+            return;
+        };
+        let parent_node = &self.json_tree[parent];
+        let node = &self.json_tree[index];
+        let siblings: Vec<usize> = (parent + 1..self.json_tree.len())
+            .take_while(|&i| self.json_tree[i].depth > parent_node.depth)
+            .filter(|&i| self.json_tree[i].depth == node.depth)
+            .collect();
+        let position = siblings.iter().position(|&i| i == index).unwrap_or(0);
+        let deleted_path = node.path.clone();
+
+        let pointer = self.json_tree[parent].pointer.clone();
+        let parent_value = value.pointer_mut(&pointer);
+        // Array elements after the deleted one shift down an index, taking their expansion
+        // state with them
+        let mut shifted_after: Option<(String, usize)> = None;
+        let select = match parent_value {
+            Some(Value::Array(items)) => {
+                items.remove(position);
+                let prefix = if parent_node.path == "root" { String::new() } else { parent_node.path.clone() };
+                let select = if items.is_empty() {
+                    parent_node.path.clone()
+                } else {
+                    format!("{}[{}]", prefix, position.saturating_sub(1))
+                };
+                shifted_after = Some((prefix, position));
+                select
+            }
+            Some(Value::Object(obj)) => {
+                obj.shift_remove(&node.key);
+                let neighbour = if position > 0 {
+                    siblings.get(position - 1)
+                } else {
+                    siblings.get(1)
+                };
+                neighbour.map_or(parent_node.path.clone(), |&i| self.json_tree[i].path.clone())
+            }
+            _ => return,
+        };
+        let rename = |path: &str| {
+            let shifted = shifted_after.as_ref().and_then(|(prefix, position)| {
+                let rest = path.strip_prefix(prefix.as_str())?.strip_prefix('[')?;
+                let (i, tail) = rest.split_once(']')?;
+                let i: usize = i.parse().ok()?;
+                (i > *position).then(|| format!("{}[{}]{}", prefix, i - 1, tail))
+            });
+            shifted.unwrap_or_else(|| path.to_string())
+        };
+        self.apply_edit(value, rename, &select);
+        self.set_status(format!("Deleted {}", deleted_path));
+    }
+
+    /// Copies the selected node's subtree as compact JSON, for pasting into code or URLs.
+    pub fn copy_subtree_minified(&mut self) -> Result<()> {
+        if self.selected_node < self.json_tree.len() {
+            let text = serde_json::to_string(self.node_value(self.selected_node)).unwrap_or_default();
+            self.report(clipboard::try_copy(
+                &text,
+                format!("Copied {} minified ({} bytes)", self.jq_path(self.selected_node), text.len()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Copies the selected node's subtree pretty-printed, or a string as its bare text. This
+    /// is the real value even while masking is on; only `copy_redacted` copies masked text.
+    pub fn copy_node_value_to_clipboard(&mut self) -> Result<()> {
+        if let Some(node) = self.json_tree.get(self.selected_node) {
+            let text = match self.node_value(self.selected_node) {
+                Value::String(s) => s.clone(),
+                other => to_pretty(other, self.indent).unwrap_or_default(),
+            };
+            let path = self.jq_path(self.selected_node);
+            let message = if node.container {
+                format!("Copied {} pretty-printed ({} bytes)", path, text.len())
+            } else {
+                format!("Copied value of {}", path)
+            };
+            self.report(clipboard::try_copy(&text, message));
+        }
+        Ok(())
+    }
+
+    /// Copies every scalar in the document, one per line, optionally prefixed by its path.
+    /// Values are copied as they are even while masking is on.
+    pub fn copy_leaf_values(&mut self, with_paths: bool) -> Result<()> {
+        let Some(ref value) = self.parsed_value else {
+            return Ok(());
+        };
+        let leaves = transform::leaf_values(value);
+        let text = leaves
+            .iter()
+            .map(|(path, leaf)| {
+                let leaf = match leaf {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                if with_paths {
+                    format!("{}\t{}", path, leaf)
+                } else {
+                    leaf
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.report(clipboard::try_copy(&text, format!("Copied {} leaf values", leaves.len())));
+        Ok(())
+    }
+
+    /// Copies the node's path as the tree shows it, e.g. `foo[0].bar`.
+    pub fn copy_path_to_clipboard(&mut self) -> Result<()> {
+        if self.selected_node < self.json_tree.len() {
+            let path = self.dotted_path(self.selected_node);
+            self.report(clipboard::try_copy(&path, format!("Copied path: {}", path)));
+        }
+        Ok(())
+    }
+
+    pub fn copy_jq_path_to_clipboard(&mut self) -> Result<()> {
+        if self.selected_node < self.json_tree.len() {
+            let filter = self.jq_path(self.selected_node);
+            self.report(clipboard::try_copy(&filter, format!("Copied jq path: {}", filter)));
+        }
+        Ok(())
+    }
+
+    fn handle_visual_action(&mut self, action: Action) -> Result<()> {
+        let Some(ref mut visual) = self.visual else {
+            return Ok(());
+        };
+        let text = match self.masked {
+            Some(ref masked) => &masked.pretty,
+            None => &self.formatted_json,
+        };
+        match action {
+            Action::NavUp => visual.move_vertical(text, false),
+            Action::NavDown => visual.move_vertical(text, true),
+            Action::ScrollLeft => visual.move_horizontal(text, false),
+            Action::ScrollRight => visual.move_horizontal(text, true),
+            Action::VisualSelect => {
+                visual.anchor = match visual.anchor {
+                    Some(_) => None,
+                    None => Some(visual.cursor),
+                };
+            }
+            Action::Copy => {
+                let selected = visual.selected_text(text);
+                self.visual = None;
+                self.report(clipboard::try_copy(
+                    &selected,
+                    format!("Copied {} characters to clipboard", selected.chars().count()),
+                ));
+            }
+            Action::Quit => self.visual = None,
+            _ => {}
+        }
+
+        // Keep the cursor line inside the viewport
+        if let Some(ref visual) = self.visual {
+            let line = visual.cursor.0;
+            let height = self.raw_view_height.get().max(1);
+            if line < self.scroll_offset {
+                self.scroll_offset = line;
+            } else if line >= self.scroll_offset + height {
+                self.scroll_offset = line + 1 - height;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the selection one visible row down (`forward`) or up, wrapping around at either
+    /// end when `wrap_navigation` is on.
+    fn step_selection(&mut self, forward: bool) {
+        let visible = self.visible_indices();
+        let Some(last) = visible.len().checked_sub(1) else {
+            return;
+        };
+        let current = self.selected_row(&visible);
+        let target = match (forward, current) {
+            (true, row) if row < last => Some(row + 1),
+            (false, row) if row > 0 => Some(row - 1),
+            _ if self.wrap_navigation => Some(if forward { 0 } else { last }),
+            _ => None,
+        };
+        let Some(target) = target.map(|row| visible[row]) else {
+            return;
+        };
+        drop(visible);
+        self.selected_node = target;
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        if crate::ui::render_too_small(frame, MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT) {
+            return;
+        }
+        let prompt_height = if self.prompt.is_some() { 3 } else { 0 };
+        let footer_height = if self.parse_stats.is_some() { 1 } else { 0 };
+        let status_line = self.status_line();
+        let status_height = if status_line.is_some() { 1 } else { 0 };
+        let schema_height = if self.schema.is_some() {
+            self.schema_violations.len().clamp(1, SCHEMA_PANEL_ROWS) as u16 + 2
+        } else {
+            0
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(1),
+                Constraint::Length(schema_height),
+                Constraint::Length(prompt_height),
+                Constraint::Length(footer_height),
+                Constraint::Length(status_height),
+            ])
+            .split(area);
+
+        self.render_view(frame, chunks[0]);
+        if let Some((ref path, ref schema)) = self.schema {
+            self.render_schema_panel(frame, chunks[1], path, schema);
+        }
+        if let Some(ref prompt) = self.prompt {
+            self.render_prompt(frame, chunks[2], prompt);
+        }
+        if let Some(ref stats) = self.parse_stats {
+            let mut footer_text = format!(
+                " {} · parsed in {:.1?} · {} nodes",
+                format_bytes(stats.bytes),
+                stats.duration,
+                stats.nodes
+            );
+            let raw_shown = self.view_mode == ViewMode::Raw || self.split_view;
+            if let Some(position) = self.scroll_position_label().filter(|_| raw_shown) {
+                footer_text.push_str(&format!(" · {}", position));
+            }
+            let footer = Paragraph::new(footer_text).style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(footer, chunks[3]);
+        }
+        if let Some(line) = status_line {
+            frame.render_widget(Paragraph::new(line), chunks[4]);
+        }
+        if self.show_help {
+            self.render_help(frame, area);
+        }
+        if let Some(ref stats) = self.stats_overlay {
+            self.render_stats(frame, area, stats);
+        }
+    }
+
+    /// Lists schema violations by instance path under a title colored by the outcome.
+    fn render_schema_panel(&self, frame: &mut Frame, area: Rect, path: &Path, schema: &Schema) {
+        let mut name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+        if !schema.unchecked().is_empty() {
+            name.push_str(&format!(" ({} unchecked)", schema.unchecked().len()));
+        }
+        let (title, color, lines) = if !self.is_valid {
+            (format!("Schema {} - no valid JSON to check", name), Color::Yellow, Vec::new())
+        } else if self.schema_violations.is_empty() {
+            let lines = vec![Line::from("The document matches the schema")];
+            let keys = self.keymap.hints(&[(&[Action::LoadSchema], "change")]);
+            (format!("Schema {}: ✓ valid - {}", name, keys), Color::Green, lines)
+        } else {
+            let lines = self
+                .schema_violations
+                .iter()
+                .map(|violation| {
+                    let path = if violation.instance_path.is_empty() { "(root)" } else { &violation.instance_path };
+                    Line::from(vec![
+                        Span::styled(path, Style::default().fg(Color::Cyan)),
+                        Span::raw(format!("  {}", violation.message)),
+                    ])
+                })
+                .collect();
+            let keys = self.keymap.hints(&[
+                (&[Action::SchemaScrollDown, Action::SchemaScrollUp], "scroll"),
+                (&[Action::LoadSchema], "change"),
+            ]);
+            let title = format!("Schema {}: ✗ {} violation(s) - {}", name, self.schema_violations.len(), keys);
+            (title, Color::Red, lines)
+        };
+        let block = Block::default()
+            .title(Span::styled(title, Style::default().fg(color).add_modifier(Modifier::BOLD)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(color));
+        let paragraph = Paragraph::new(lines).block(block).scroll((self.schema_scroll as u16, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    /// The latest success notice, or otherwise an error that the main view isn't already
+    /// showing because the document itself is fine.
+    fn status_line(&self) -> Option<Line<'_>> {
+        if let Some((ref message, _)) = self.status {
+            return Some(Line::styled(format!(" ✓ {}", message), Style::default().fg(Color::Green)));
+        }
+        if self.is_valid && !self.error_message.is_empty() {
+            return Some(Line::styled(format!(" ✗ {}", self.error_message), Style::default().fg(Color::Red)));
+        }
+        if let (true, Some((_, message))) = (self.is_valid, &self.notice) {
+            return Some(Line::styled(format!(" {}", message), Style::default().fg(Color::Yellow)));
+        }
+        None
+    }
+
+    /// Sizes, value counts, nesting and the biggest arrays and strings, in a popup.
+    fn render_stats(&self, frame: &mut Frame, area: Rect, stats: &Stats) {
+        let label = |text: &str| Span::styled(format!("{:<16}", text), Style::default().fg(Color::Cyan));
+        let minified = self.parsed_value.as_ref().map_or(0, |value| serde_json::to_string(value).map_or(0, |s| s.len()));
+        let mut lines = vec![
+            Line::from(vec![
+                label("Size"),
+                Span::raw(format!("{} as pasted, {} minified", format_bytes(self.raw_input.len()), format_bytes(minified))),
+            ]),
+            Line::from(vec![
+                label("Max depth"),
+                Span::raw(format!("{} (at {})", stats.max_depth, stats.deepest_path)),
+            ]),
+            Line::from(vec![
+                label("Containers"),
+                Span::raw(format!("{} objects, {} arrays", stats.objects, stats.arrays)),
+            ]),
+            Line::from(vec![
+                label("Scalars"),
+                Span::raw(format!(
+                    "{} strings, {} numbers, {} booleans, {} nulls",
+                    stats.strings, stats.numbers, stats.booleans, stats.nulls
+                )),
+            ]),
+        ];
+        for (title, entries, unit) in [
+            ("Largest arrays", &stats.largest_arrays, "items"),
+            ("Longest strings", &stats.longest_strings, "chars"),
+        ] {
+            lines.push(Line::from(""));
+            lines.push(Line::styled(title, Style::default().fg(Color::Cyan)));
+            if entries.is_empty() {
+                lines.push(Line::styled("  none", Style::default().fg(Color::DarkGray)));
+            }
+            for (path, size) in entries {
+                lines.push(Line::from(format!("  {:>8} {}  {}", size, unit, path)));
+            }
+        }
+
+        let width = area.width.saturating_sub(4).min(80);
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().title("Statistics - any key to close").borders(Borders::ALL))
+            .style(Style::default().fg(Color::White));
+        frame.render_widget(Clear, popup);
+        frame.render_widget(paragraph, popup);
+    }
+
+    /// Key reference built from the live keymap, so rebound keys show up as configured.
+    fn render_help(&self, frame: &mut Frame, area: Rect) {
+        let toggle = self.keymap.hints(&[(&[Action::ToggleWrap], "toggle")]);
+        let mut lines = vec![
+            Line::from(format!(
+                "Wrap-around navigation: {} ({}ui.wrap_navigation in config.toml sets the default)",
+                if self.wrap_navigation { "on" } else { "off" },
+                if toggle.is_empty() { toggle } else { toggle + ", " }
+            )),
+            Line::from(""),
+        ];
+        // Two entries per row keeps the whole list on screen in an ordinary terminal
+        let entries = self.keymap.help_entries();
+        for pair in entries.chunks(2) {
+            let mut spans = Vec::new();
+            for (name, keys) in pair {
+                let keys = if keys.is_empty() { "(unbound)".to_string() } else { keys.join(", ") };
+                spans.push(Span::styled(format!("{:<22}", name.replace('_', " ")), Style::default().fg(Color::Cyan)));
+                spans.push(Span::raw(format!("{:<26}", keys)));
+            }
+            lines.push(Line::from(spans));
+        }
+
+        let width = area.width.saturating_sub(4).min(100);
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let help = Paragraph::new(lines)
+            .block(Block::default().title("Help - any key to close").borders(Borders::ALL))
+            .style(Style::default().fg(Color::White));
+        frame.render_widget(Clear, popup);
+        frame.render_widget(help, popup);
+    }
+
+    fn render_view(&self, frame: &mut Frame, area: Rect) {
+        // Full screen - either raw or tree view
+        if self.typing {
+            self.render_insert_mode(frame, area);
+            return;
+        }
+        if self.split_view && self.view_mode == ViewMode::Tree && area.width >= MIN_SPLIT_WIDTH {
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(area);
+            self.render_raw_preview(frame, panes[0]);
+            self.render_tree_view(frame, panes[1]);
+            return;
+        }
+        match self.view_mode {
+            ViewMode::Raw => self.render_raw_preview(frame, area),
+            ViewMode::Tree => self.render_tree_view(frame, area),
+            ViewMode::Converted => self.render_converted(frame, area),
+        }
+    }
+
+    fn render_converted(&self, frame: &mut Frame, area: Rect) {
+        let (title, content) = match self.converted {
+            Some(ref converted) => (
+                format!(
+                    "{} - {}",
+                    converted.title,
+                    self.keymap.hints(match (&converted.diff, &converted.flat) {
+                        (Some(_), _) => hints::DIFF,
+                        (_, Some(_)) => hints::FLAT,
+                        _ => hints::CONVERTED,
+                    })
+                ),
+                converted.text.as_str(),
+            ),
+            None => (format!("Converted Output - {}", self.keymap.hints(hints::CONVERTED)), ""),
+        };
+
+        if let Some(view) = self.converted.as_ref().and_then(|converted| converted.diff.as_ref()) {
+            self.render_diff(frame, area, &title, view);
+            return;
+        }
+        let text = Text::styled(content, Style::default().fg(Color::Green));
+        let paragraph = Paragraph::new(text)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll_offset as u16, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    /// The diff on screen, if the output view is showing one.
+    fn diff_view(&self) -> Option<&DiffView> {
+        if self.view_mode != ViewMode::Converted {
+            return None;
+        }
+        self.converted.as_ref().and_then(|converted| converted.diff.as_ref())
+    }
+
+    /// The flattened listing on screen, if the output view is showing one.
+    fn flat_view(&self) -> Option<&FlatView> {
+        if self.view_mode != ViewMode::Converted {
+            return None;
+        }
+        self.converted.as_ref().and_then(|converted| converted.flat.as_ref())
+    }
+
+    /// The base document's side of each change on the left and this document's on the
+    /// right, one row per change so the two columns stay aligned.
+    fn render_diff(&self, frame: &mut Frame, area: Rect, title: &str, view: &DiffView) {
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(inner);
+
+        let compact = |value: &Value| serde_json::to_string(value).unwrap_or_default();
+        let cell = |text: String, color: Color| {
+            let shown: String = text.chars().skip(self.horizontal_scroll).collect();
+            ListItem::new(shown).style(Style::default().fg(color))
+        };
+        let (left, right): (Vec<ListItem>, Vec<ListItem>) = view
+            .changes
+            .iter()
+            .map(|(path, change)| match change {
+                diff::Change::Added(value) => {
+                    (ListItem::new(""), cell(format!("+ {}: {}", path, compact(value)), Color::Green))
+                }
+                diff::Change::Removed(value) => {
+                    (cell(format!("- {}: {}", path, compact(value)), Color::Red), ListItem::new(""))
+                }
+                diff::Change::Changed(old, new) => (
+                    cell(format!("~ {}: {}", path, compact(old)), Color::Yellow),
+                    cell(format!("~ {}: {}", path, compact(new)), Color::Yellow),
+                ),
+            })
+            .unzip();
+
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+        for (items, column, name) in [
+            (left, columns[0], view.base_name.as_str()),
+            (right, columns[1], "this document"),
+        ] {
+            let list = List::new(items)
+                .block(Block::default().title(name).borders(Borders::TOP))
+                .highlight_style(highlight);
+            // Both lists have the same rows and height, so they scroll to the same offset
+            let mut state = ListState::default().with_selected(Some(view.selected));
+            frame.render_stateful_widget(list, column, &mut state);
+        }
+    }
+
+    fn build_masked(&self) -> Option<MaskedDocument> {
+        let value = self.masker.mask(self.parsed_value.as_ref()?);
+        Some(MaskedDocument {
+            pretty: to_pretty(&value, self.indent).unwrap_or_default(),
+            minified: serde_json::to_string(&value).unwrap_or_default(),
+            value,
+        })
+    }
+
+    fn toggle_masking(&mut self) {
+        self.masked = match self.masked {
+            Some(_) => None,
+            None => self.build_masked(),
+        };
+        // Match offsets belong to the text that was displayed before
+        self.search = None;
+    }
+
+    /// Pretty-prints the document and its masked copy again with the current indentation.
+    /// The visual selection is dropped, as its lines no longer line up, and a text search is
+    /// run again over the new text.
+    fn reformat(&mut self) {
+        let Some(ref value) = self.parsed_value else {
+            return;
+        };
+        self.formatted_json = to_pretty(value, self.indent).unwrap_or_default();
+        if self.masked.is_some() {
+            self.masked = self.build_masked();
+        }
+        self.visual = None;
+        if let Some(query) = self.search.as_ref().map(|search| search.query.clone()) {
+            self.search = Some(TextSearch::new(self.displayed_pretty(), &query));
+        }
+    }
+
+    /// Pretty JSON as displayed, which is the masked copy while masking is on.
+    fn displayed_pretty(&self) -> &str {
+        match self.masked {
+            Some(ref masked) => &masked.pretty,
+            None => &self.formatted_json,
+        }
+    }
+
+    pub fn copy_redacted_to_clipboard(&mut self) -> Result<()> {
+        if let Some(masked) = self.build_masked() {
+            self.report(clipboard::try_copy(&masked.pretty, "Copied redacted JSON to clipboard".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Title prefix describing where the current document came from, if not plain JSON.
+    fn document_label(&self) -> String {
+        let mut label = if let Some(ref algorithm) = self.jwt_algorithm {
+            format!("[JWT alg={}, signature UNVERIFIED] ", algorithm)
+        } else if let Some(format) = self.source_format {
+            format!("[converted from {}] ", format)
+        } else {
+            String::new()
+        };
+        if let Some(wrap) = self.fragment_wrap {
+            label.push_str(&format!("[fragment wrapped in {}] ", wrap));
+        }
+        if self.lenient_mode {
+            label.push_str("[lenient: JSON5] ");
+        }
+        if self.masked.is_some() {
+            label.push_str("[secrets masked] ");
+        }
+        label
     }
 
-    fn build_tree_recursive(&mut self, value: &Value, key: &str, depth: usize, path: &str) {
-        let node = JsonTreeNode {
-            key: key.to_string(),
-            value: value.clone(),
-            expanded: depth < 2, // Auto-expand first 2 levels
-            depth,
-            path: path.to_string(),
+    fn render_insert_mode(&self, frame: &mut Frame, area: Rect) {
+        let status = if self.last_keystroke.is_some() {
+            "typing…".to_string()
+        } else if self.is_valid {
+            "✓ valid".to_string()
+        } else if self.raw_input.trim().is_empty() {
+            "empty".to_string()
+        } else {
+            format!("✗ {}", self.error_message)
+        };
+        let title = format!("INSERT ({}) - type JSON, Enter: newline, Esc: done", status);
+        let border_color = if self.last_keystroke.is_some() {
+            Color::Gray
+        } else if self.is_valid {
+            Color::Green
+        } else {
+            Color::Red
         };
-        self.json_tree.push(node);
 
-        if let Some(obj) = value.as_object() {
-            for (k, v) in obj {
-                let new_path = if path == "root" { k.clone() } else { format!("{}.{}", path, k) };
-                self.build_tree_recursive(v, k, depth + 1, &new_path);
+        // Follow the end of the buffer, where typing happens
+        let height = area.height.saturating_sub(2) as usize;
+        let line_count = self.raw_input.split('\n').count();
+        let scroll = line_count.saturating_sub(height);
+
+        let paragraph = Paragraph::new(self.raw_input.as_str())
+            .block(Block::default().title(title).borders(Borders::ALL).border_style(Style::default().fg(border_color)))
+            .scroll((scroll as u16, 0))
+            .style(Style::default().fg(Color::White));
+        frame.render_widget(paragraph, area);
+
+        let last_line = self.raw_input.rsplit('\n').next().unwrap_or("");
+        let row = (line_count - 1 - scroll) as u16;
+        frame.set_cursor_position((area.x + last_line.chars().count() as u16 + 1, area.y + row + 1));
+    }
+
+    fn handle_insert_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.typing = false;
+                self.last_keystroke = None;
+                self.parse_json();
+                return;
             }
-        } else if let Some(arr) = value.as_array() {
-            for (i, v) in arr.iter().enumerate() {
-                let new_path = if path == "root" { format!("[{}]", i) } else { format!("{}[{}]", path, i) };
-                self.build_tree_recursive(v, &format!("[{}]", i), depth + 1, &new_path);
+            KeyCode::Enter => self.raw_input.push('\n'),
+            KeyCode::Tab => self.raw_input.push_str("  "),
+            KeyCode::Backspace => {
+                self.raw_input.pop();
             }
+            KeyCode::Char(c) => self.raw_input.push(c),
+            _ => return,
         }
+        self.last_keystroke = Some(Instant::now());
     }
 
-    fn toggle_node(&mut self) {
-        if self.selected_node < self.json_tree.len() {
-            let node = &mut self.json_tree[self.selected_node];
-            if node.value.is_object() || node.value.is_array() {
-                node.expanded = !node.expanded;
+    /// Copies the selected node's value once navigation has paused, so scrolling through the
+    /// tree with copy-on-select on doesn't write to the clipboard on every step.
+    pub fn run_debounced_copy(&mut self) -> Result<()> {
+        if let Some(moved) = self.pending_copy {
+            if moved.elapsed() >= COPY_ON_SELECT_DEBOUNCE {
+                self.pending_copy = None;
+                self.copy_node_value_to_clipboard()?;
             }
         }
+        Ok(())
     }
 
-    fn move_selection_up(&mut self) {
-        let visible_nodes = self.get_visible_nodes();
-        if !visible_nodes.is_empty() {
-            let current_visible_index = visible_nodes.iter().position(|node| {
-                self.json_tree.iter().position(|n| std::ptr::eq(*node, n)) == Some(self.selected_node)
-            }).unwrap_or(0);
-            
-            if current_visible_index > 0 {
-                let new_visible_index = current_visible_index - 1;
-                if let Some(new_node) = visible_nodes.get(new_visible_index) {
-                    if let Some(new_index) = self.json_tree.iter().position(|n| std::ptr::eq(*new_node, n)) {
-                        self.selected_node = new_index;
-                    }
-                }
-            }
+    /// How long to wait for input before the next tick. Ticks only matter while a file is
+    /// watched or a debounce is pending; otherwise only a key press can change anything, so
+    /// the loop wakes up rarely.
+    pub fn poll_interval(&self) -> Duration {
+        if self.file_watcher.is_some()
+            || self.last_keystroke.is_some()
+            || self.pending_copy.is_some()
+            || self.status.is_some()
+        {
+            self.poll_interval
+        } else {
+            self.idle_poll_interval
         }
     }
 
-    fn move_selection_down(&mut self) {
-        let visible_nodes = self.get_visible_nodes();
-        if !visible_nodes.is_empty() {
-            let current_visible_index = visible_nodes.iter().position(|node| {
-                self.json_tree.iter().position(|n| std::ptr::eq(*node, n)) == Some(self.selected_node)
-            }).unwrap_or(0);
-            
-            if current_visible_index < visible_nodes.len() - 1 {
-                let new_visible_index = current_visible_index + 1;
-                if let Some(new_node) = visible_nodes.get(new_visible_index) {
-                    if let Some(new_index) = self.json_tree.iter().position(|n| std::ptr::eq(*new_node, n)) {
-                        self.selected_node = new_index;
-                    }
-                }
-            }
+    /// Shows a success notice. An earlier error about the same document is now stale, so
+    /// it's dropped; a parse error stays while the document is still invalid.
+    fn set_status(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), Instant::now()));
+        if self.is_valid {
+            self.error_message.clear();
         }
     }
 
-    pub fn render(&self, frame: &mut Frame, area: Rect) {
-        // Full screen - either raw or tree view
-        match self.view_mode {
-            ViewMode::Raw => self.render_raw_preview(frame, area),
-            ViewMode::Tree => self.render_tree_view(frame, area),
+    /// Routes the outcome of an action to the status line or the error message.
+    fn report(&mut self, outcome: std::result::Result<String, String>) {
+        match outcome {
+            Ok(message) => self.set_status(message),
+            Err(error) => self.error_message = error,
+        }
+    }
+
+    pub fn expire_status(&mut self) {
+        if self.status.as_ref().is_some_and(|(_, set)| set.elapsed() >= STATUS_TIMEOUT) {
+            self.status = None;
+        }
+    }
+
+    /// Re-parses the buffer once typing has paused, so large documents don't stall each keystroke.
+    pub fn run_debounced_parse(&mut self) {
+        if let Some(last) = self.last_keystroke {
+            if last.elapsed() >= TYPING_DEBOUNCE {
+                self.last_keystroke = None;
+                self.parse_json();
+            }
         }
     }
 
     fn render_raw_preview(&self, frame: &mut Frame, area: Rect) {
-        let preview_title = if self.is_valid {
-            "JSON Viewer - 'p': paste, 'n': neovim, 't': tree, 'c': copy, 'C': copy minified, 'j/k': scroll, 'q': quit"
-        } else if !self.error_message.is_empty() && self.error_message.contains("Edit this file:") {
-            "File Created - 'p': paste, 'n': neovim, 't': tree view, 'q': quit"
-        } else if !self.error_message.is_empty() {
-            "JSON Viewer - 'p': paste, 'n': neovim, 't': tree view, 'q': quit"
+        // Inner height between the borders, used to keep the visual cursor on screen
+        self.raw_view_height.set(area.height.saturating_sub(2) as usize);
+
+        let (mode, hints) = if self.visual.as_ref().is_some_and(|v| v.anchor.is_some()) {
+            ("VISUAL", hints::VISUAL_EXTEND)
+        } else if self.visual.is_some() {
+            ("VISUAL", hints::VISUAL_MOVE)
+        } else if self.is_valid && self.show_minified {
+            ("JSON Viewer (minified)", hints::MINIFIED)
+        } else if self.is_valid {
+            ("JSON Viewer", hints::VALID_RAW)
+        } else if self.error_message.is_empty() && matches!(self.notice, Some((Notice::Editing, _))) {
+            ("File Created", hints::TEMP_FILE)
         } else {
-            "JSON Viewer - 'p': paste, 'n': neovim, 't': tree view, 'q': quit"
+            ("JSON Viewer", hints::EMPTY_RAW)
         };
+        let preview_title = format!("{} - {}", mode, self.keymap.hints(hints));
 
+        // Compact size next to the pretty one, to judge the payload before copying it
+        let size_note = if self.is_valid && self.show_minified && self.visual.is_none() {
+            format!(
+                "[{} minified, {} pretty] ",
+                format_bytes(self.minified_json.len()),
+                format_bytes(self.formatted_json.len())
+            )
+        } else if let (true, Some(search)) = (self.is_valid && !self.show_minified && self.visual.is_none(), &self.search) {
+            let keys = self.keymap.hints(&[(&[Action::NextMatch, Action::PrevMatch], "next/prev")]);
+            format!("[{}, {}] ", search.label(), keys)
+        } else {
+            String::new()
+        };
         let preview_block = Block::default()
-            .title(preview_title)
+            .title(format!("{}{}{}", self.document_label(), size_note, preview_title))
             .borders(Borders::ALL);
 
-        let preview_content = if self.is_valid {
-            &self.formatted_json
+        let empty_hint;
+        let preview_content = if self.is_valid && self.show_minified {
+            self.masked.as_ref().map_or(self.minified_json.as_str(), |m| m.minified.as_str())
+        } else if self.is_valid {
+            self.displayed_pretty()
         } else if !self.error_message.is_empty() {
             &self.error_message
+        } else if let Some((_, message)) = &self.notice {
+            message
         } else {
-            "Press 'p' to paste JSON from clipboard or 'n' to create new JSON in Neovim"
+            empty_hint = format!(
+                "No JSON yet - {}",
+                self.keymap.hints(&[
+                    (&[Action::Paste], "paste from clipboard"),
+                    (&[Action::InsertMode], "type it"),
+                    (&[Action::OpenEditor], "edit it in your editor"),
+                ])
+            );
+            &empty_hint
         };
 
         let preview_color = if self.is_valid {
             Color::Green
-        } else if !self.error_message.is_empty() && self.error_message.contains("Edit this file:") {
-            Color::Yellow
         } else if !self.error_message.is_empty() {
             Color::Red
         } else {
-            Color::Cyan
+            match self.notice {
+                Some((Notice::Validated, _)) => Color::Green,
+                Some((Notice::Editing, _)) => Color::Yellow,
+                None => Color::Cyan,
+            }
         };
 
-        let preview_paragraph = Paragraph::new(preview_content)
-            .block(preview_block)
+        let inner_width = area.width.saturating_sub(2) as usize;
+        let visible = || self.visible_raw_lines(self.displayed_pretty(), inner_width);
+        let preview_text = if let (true, Some(visual)) = (self.is_valid && !self.show_minified, &self.visual) {
+            visual.highlight(self.displayed_pretty(), Style::default().fg(Color::Green))
+        } else if let (true, Some(search)) = (self.is_valid && !self.show_minified, &self.search) {
+            search.highlight(self.displayed_pretty(), visible())
+        } else if self.is_valid && !self.show_minified && self.show_annotations {
+            self.annotated_lines(visible())
+        } else if let (false, Some(position)) = (self.is_valid, self.error_position) {
+            self.error_lines(position)
+        } else if self.is_valid && !self.show_minified {
+            highlight::lines(self.displayed_pretty(), visible())
+        } else if self.is_valid {
+            Text::from(highlight::window(preview_content, self.horizontal_scroll, inner_width))
+        } else {
+            Text::from(preview_content)
+        };
+
+        // The minified form is a single long line, so scroll it sideways instead of wrapping
+        if self.is_valid && self.show_minified {
+            self.raw_total_lines.set(0);
+            // Only the columns in view were taken from the line, so it is drawn unscrolled
+            let preview_paragraph = Paragraph::new(preview_text)
+                .style(Style::default().fg(preview_color))
+                .block(preview_block);
+            frame.render_widget(preview_paragraph, area);
+            return;
+        }
+
+        let width = area.width.saturating_sub(2);
+        let key = text_key(&preview_text, width);
+        let mut cache = self.raw_rows.borrow_mut();
+        if !matches!(&*cache, Some((cached, _)) if *cached == key) {
+            *cache = Some((key, wrapped_rows(&preview_text, width)));
+        }
+        let rows = cache.as_ref().map_or(&[0][..], |(_, rows)| rows);
+        let total_lines = rows[rows.len() - 1];
+        self.raw_total_lines.set(total_lines);
+
+        // Only the lines in view are handed to the paragraph, which keeps its scroll within
+        // the first of them rather than the whole document
+        let view_height = self.raw_view_height.get();
+        let starts = &rows[..rows.len() - 1];
+        let first = starts.partition_point(|&row| row <= self.scroll_offset).saturating_sub(1);
+        let last = starts.partition_point(|&row| row < self.scroll_offset + view_height.max(1));
+        let skip = self.scroll_offset.saturating_sub(starts.get(first).copied().unwrap_or(0));
+        let mut preview_text = preview_text;
+        preview_text.lines.truncate(last.max(first));
+        preview_text.lines.drain(..first.min(preview_text.lines.len()));
+        drop(cache);
+        let preview_paragraph = Paragraph::new(preview_text)
+            .style(Style::default().fg(preview_color))
             .wrap(Wrap { trim: false })
-            .scroll((self.scroll_offset as u16, 0))
-            .style(Style::default().fg(preview_color));
+            .block(preview_block)
+            .scroll((u16::try_from(skip).unwrap_or(u16::MAX), 0));
         frame.render_widget(preview_paragraph, area);
+
+        if total_lines > view_height {
+            let mut state = ScrollbarState::new(total_lines.saturating_sub(view_height)).position(self.scroll_offset);
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None);
+            frame.render_stateful_widget(scrollbar, area.inner(Margin { vertical: 1, horizontal: 0 }), &mut state);
+        }
+    }
+
+    /// Logical lines of `text` that can show at the current scroll offset when wrapped to
+    /// `width`. Rows are estimated per line, so the range keeps a screen of slack each side.
+    fn visible_raw_lines(&self, text: &str, width: usize) -> std::ops::Range<usize> {
+        let height = self.raw_view_height.get();
+        let mut row = 0;
+        let mut first = 0;
+        for (i, line) in text.lines().enumerate() {
+            row += line.width().div_ceil(width.max(1)).max(1);
+            if row > self.scroll_offset {
+                first = i;
+                break;
+            }
+        }
+        first.saturating_sub(height)..first + 2 * height
+    }
+
+    /// Furthest the raw view can scroll while its last line is still at the bottom, from the
+    /// wrapped line count of the last render.
+    fn max_raw_scroll(&self) -> usize {
+        self.raw_total_lines.get().saturating_sub(self.raw_view_height.get())
+    }
+
+    /// `line X of Y (Z%)` for the raw view, from the wrapped line count of the last render.
+    fn scroll_position_label(&self) -> Option<String> {
+        let total = self.raw_total_lines.get();
+        if total == 0 {
+            return None;
+        }
+        let view_height = self.raw_view_height.get();
+        let line = (self.scroll_offset + 1).min(total);
+        let percent = if total <= view_height {
+            100
+        } else {
+            (self.scroll_offset + view_height).min(total) * 100 / total
+        };
+        Some(format!("line {} of {} ({}%)", line, total, percent))
+    }
+
+    /// Builds the formatted JSON with a faint `// N items` comment after every container
+    /// opening. The comments are display-only and never end up in `formatted_json`. Lines in
+    /// `visible` are syntax-colored.
+    fn annotated_lines(&self, visible: std::ops::Range<usize>) -> Text<'_> {
+        let mut sizes = Vec::new();
+        let value = self.masked.as_ref().map(|m| &m.value).or(self.parsed_value.as_ref());
+        if let Some(value) = value {
+            collect_container_sizes(value, &mut sizes);
+        }
+        let mut sizes = sizes.into_iter();
+
+        let lines: Vec<Line> = self
+            .displayed_pretty()
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut styled = if visible.contains(&i) { highlight::line(line) } else { Line::from(line) };
+                // Non-empty containers are the only lines the pretty printer ends with an opener
+                if line.ends_with('{') || line.ends_with('[') {
+                    if let Some(size) = sizes.next() {
+                        styled.push_span(Span::styled(format!("  // {}", size), Style::default().fg(Color::DarkGray)));
+                    }
+                }
+                styled
+            })
+            .collect();
+        Text::from(lines)
+    }
+
+    /// Shows the error followed by the numbered raw input with the offending line highlighted.
+    fn error_lines(&self, (error_line, error_column): (usize, usize)) -> Text<'_> {
+        let mut lines = vec![
+            Line::styled(
+                format!("✗ Error at line {}, column {} - {}", error_line, error_column, self.error_message),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Line::from(""),
+        ];
+        for (i, line) in self.raw_input.lines().enumerate() {
+            let content = format!("{:>4} │ {}", i + 1, line);
+            if i + 1 == error_line {
+                lines.push(Line::styled(
+                    content,
+                    Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+                ));
+                lines.push(Line::styled(
+                    format!("     │ {}^", caret_indent(line, error_column)),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ));
+            } else {
+                lines.push(Line::styled(content, Style::default().fg(Color::Gray)));
+            }
+        }
+        Text::from(lines)
     }
 
     fn render_tree_view(&self, frame: &mut Frame, area: Rect) {
-        let tree_title = "JSON Tree - 'p': paste, 'n': neovim, 't': raw, 'c': copy, 'C': copy minified, Space: expand, ↑/↓ j/k: navigate, 'q': quit";
+        self.tree_area.set(area);
+        let mut modes = String::new();
+        if self.copy_on_select {
+            modes.push_str("[copy-on-select] ");
+        }
+        if self.number_base != NumberBase::Decimal {
+            modes.push_str(&format!("[integers in {}] ", self.number_base.label()));
+        }
+        if let Some(ref search) = self.tree_search {
+            let keys = self.keymap.hints(&[(&[Action::NextMatch, Action::PrevMatch], "next/prev")]);
+            modes.push_str(&format!("[{}, {}] ", search.label(), keys));
+        }
+        let tree_title = format!("JSON Tree - {}", self.keymap.hints(hints::TREE));
         let tree_block = Block::default()
-            .title(tree_title)
+            .title(format!("{}{}{}", self.document_label(), modes, tree_title))
             .borders(Borders::ALL);
 
         if !self.is_valid || self.json_tree.is_empty() {
@@ -361,34 +3356,64 @@ impl JsonUtils {
             return;
         }
 
-        let visible_nodes = self.get_visible_nodes();
-        let items: Vec<ListItem> = visible_nodes
+        let visible = self.visible_indices();
+        let height = area.height.saturating_sub(2) as usize;
+        self.tree_view_height.set(height);
+        let selected_row = self.selected_row(&visible);
+        let offset = scroll_into_view(self.tree_offset.get(), selected_row, height, visible.len());
+        self.tree_offset.set(offset);
+
+        // A sensitive key hides its own value and everything nested under it
+        let mut masked_depth: Option<usize> = None;
+        let masked_flags: Vec<bool> = visible
             .iter()
-            .enumerate()
-            .map(|(_i, node)| {
+            .map(|&index| {
+                let node = &self.json_tree[index];
+                if masked_depth.is_some_and(|depth| node.depth <= depth) {
+                    masked_depth = None;
+                }
+                if masked_depth.is_none() && self.masked.is_some() && self.masker.is_sensitive(&node.key) {
+                    masked_depth = Some(node.depth);
+                }
+                masked_depth.is_some()
+            })
+            .collect();
+
+        // Only rows on screen are built, so a long expanded array costs no more than a short one
+        let window = offset..(offset + height).min(visible.len());
+        let items: Vec<ListItem> = visible[window.clone()]
+            .iter()
+            .zip(&masked_flags[window])
+            .map(|(&index, &masked)| {
+                let node = &self.json_tree[index];
                 let indent = "  ".repeat(node.depth);
-                let icon = if node.value.is_object() || node.value.is_array() {
+                let icon = if node.container {
                     if node.expanded { "▼" } else { "▶" }
                 } else {
                     " "
                 };
                 
-                let value_preview = match &node.value {
-                    Value::Object(obj) => format!("{{ {} keys }}", obj.len()),
-                    Value::Array(arr) => format!("[ {} items ]", arr.len()),
+                let value_preview = match node.value(self.document()) {
+                    _ if masked => mask::MASK.to_string(),
+                    Value::Object(obj) => format!("{{ {} keys, {} }}", obj.len(), format_bytes(node.byte_size(self.document()))),
+                    Value::Array(arr) => format!("[ {} items, {} ]", arr.len(), format_bytes(node.byte_size(self.document()))),
+                    Value::String(s) if node.stringified => format!("\"{}\" (JSON text)", s),
                     Value::String(s) => format!("\"{}\"", s),
-                    Value::Number(n) => n.to_string(),
+                    Value::Number(n) => self.number_base.format(n),
                     Value::Bool(b) => b.to_string(),
                     Value::Null => "null".to_string(),
                 };
 
                 let display_key = if node.key.is_empty() { "root".to_string() } else { node.key.clone() };
-                let content = format!("{}{} {}: {}", indent, icon, display_key, value_preview);
+                let marker = if self.bookmarks.contains(&node.path) { "★ " } else { "" };
+                let content = format!("{}{} {}{}: {}", indent, icon, marker, display_key, value_preview);
                 
-                // Check if this visible node is the currently selected node
-                let is_selected = self.json_tree.iter().position(|n| std::ptr::eq(*node, n)) == Some(self.selected_node);
+                let is_selected = index == self.selected_node;
+                let is_match = self.tree_search.as_ref().is_some_and(|search| search.contains(&node.path));
                 let style = if is_selected {
                     Style::default().bg(Color::Blue).fg(Color::White)
+                } else if is_match {
+                    Style::default().bg(Color::Yellow).fg(Color::Black)
                 } else {
                     Style::default().fg(Color::White)
                 };
@@ -402,104 +3427,785 @@ impl JsonUtils {
             .highlight_symbol(">> ");
 
         frame.render_widget(tree_list, area);
-    }
 
-    fn get_visible_nodes(&self) -> Vec<&JsonTreeNode> {
-        let mut visible = Vec::new();
-        let mut skip_depth = None;
+        if visible.len() > height {
+            let mut state = ScrollbarState::new(visible.len().saturating_sub(height)).position(offset);
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None);
+            frame.render_stateful_widget(scrollbar, area.inner(Margin { vertical: 1, horizontal: 0 }), &mut state);
+        }
+    }
 
-        for node in &self.json_tree {
-            if let Some(depth) = skip_depth {
-                if node.depth > depth {
+    /// Indices of the nodes the tree shows, in order: every node not inside a collapsed one.
+    /// Worked out once per layout and reused until `tree_changed`.
+    fn visible_indices(&self) -> Ref<'_, [usize]> {
+        let mut cache = self.visible_rows.borrow_mut();
+        if cache.is_none() {
+            let mut visible = Vec::new();
+            let mut skip_depth = None;
+            for (index, node) in self.json_tree.iter().enumerate() {
+                if skip_depth.is_some_and(|depth| node.depth > depth) {
                     continue;
-                } else {
-                    skip_depth = None;
                 }
+                skip_depth = (node.container && !node.expanded).then_some(node.depth);
+                visible.push(index);
             }
+            *cache = Some(visible);
+        }
+        drop(cache);
+        Ref::map(self.visible_rows.borrow(), |cache| cache.as_deref().unwrap_or_default())
+    }
+
+    /// Drops the cached visible rows; called whenever nodes are added, removed or expanded.
+    fn tree_changed(&self) {
+        self.visible_rows.take();
+    }
 
-            visible.push(node);
+    /// Row of the selected node among `visible`, or of the nearest row above it when the
+    /// selection is hidden inside a collapsed node. The list is in tree order, so this is a
+    /// binary search.
+    fn selected_row(&self, visible: &[usize]) -> usize {
+        visible.binary_search(&self.selected_node).unwrap_or_else(|row| row.saturating_sub(1))
+    }
+
+    /// Moves the selection `rows` visible rows down (or up, when negative), stopping at
+    /// either end.
+    fn move_selection_by(&mut self, rows: isize) {
+        let visible = self.visible_indices();
+        let Some(last) = visible.len().checked_sub(1) else {
+            return;
+        };
+        let target = visible[self.selected_row(&visible).saturating_add_signed(rows).min(last)];
+        drop(visible);
+        self.selected_node = target;
+    }
 
-            if (node.value.is_object() || node.value.is_array()) && !node.expanded {
-                skip_depth = Some(node.depth);
+    /// Clicking a tree row selects it, and toggles it if it's an object or array; the wheel
+    /// scrolls the raw view.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.typing || self.prompt.is_some() || self.show_help || self.stats_overlay.is_some() {
+            return;
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollUp if self.view_mode == ViewMode::Raw => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(MOUSE_SCROLL_LINES);
+            }
+            MouseEventKind::ScrollDown if self.view_mode == ViewMode::Raw => {
+                self.scroll_offset = (self.scroll_offset + MOUSE_SCROLL_LINES).min(self.max_raw_scroll());
+            }
+            MouseEventKind::Down(MouseButton::Left) if self.view_mode == ViewMode::Tree => {
+                let area = self.tree_area.get();
+                // Rows start inside the top border
+                let inner = area.inner(Margin { vertical: 1, horizontal: 1 });
+                if !inner.contains(Position::new(mouse.column, mouse.row)) {
+                    return;
+                }
+                let row = (mouse.row - inner.y) as usize + self.tree_offset.get();
+                let Some(&index) = self.visible_indices().get(row) else {
+                    return;
+                };
+                self.selected_node = index;
+                self.toggle_node();
+                if self.copy_on_select {
+                    self.pending_copy = Some(Instant::now());
+                }
             }
+            _ => {}
         }
+    }
+
+    /// Whether an action that only makes sense in some states applies now, or `None` for
+    /// an action that always runs when its key is pressed.
+    fn applies(&self, action: Action) -> Option<bool> {
+        let in_tree = self.view_mode == ViewMode::Tree;
+        let in_raw = self.view_mode == ViewMode::Raw;
+        match action {
+            Action::EditValue => Some(in_tree && self.selected_leaf().is_some()),
+            // The tree has no text to select, so there `v` copies the node's value
+            Action::CopyValue => Some(in_tree),
+            // While a search is active, `n` steps through it as in vim; clearing the search
+            // gives the key back to the editor
+            Action::NextMatch => Some(if in_tree { self.tree_search.is_some() } else { in_raw && self.search.is_some() }),
+            _ => None,
+        }
+    }
 
-        visible
+    /// The action `key` runs now. Where a key is shared, an action that only applies in
+    /// some states wins while it applies, and otherwise the key's ordinary action runs.
+    fn resolve_action(&self, key: &KeyEvent) -> Option<Action> {
+        let actions = self.keymap.actions_for(key);
+        let first = |applies: Option<bool>| actions.iter().copied().find(|&action| self.applies(action) == applies);
+        first(Some(true)).or_else(|| first(None))
     }
 
     pub fn handle_event(&mut self, event: Event) -> Result<bool> {
+        if let Event::Mouse(mouse) = event {
+            self.handle_mouse(mouse);
+            return Ok(true);
+        }
         if let Event::Key(key) = event {
-            match key.code {
-                KeyCode::Char('q') if key.kind == KeyEventKind::Press => return Ok(false),
-                KeyCode::Char('p') if key.kind == KeyEventKind::Press => {
-                    self.paste_from_clipboard()?;
+            if key.kind != KeyEventKind::Press {
+                return Ok(true);
+            }
+            tracing::debug!("json_utils key: {:?} {:?}", key.code, key.modifiers);
+            if self.typing {
+                self.handle_insert_key(key);
+                return Ok(true);
+            }
+            if self.prompt.is_some() {
+                self.handle_prompt_key(key);
+                return Ok(true);
+            }
+            if self.show_help {
+                self.show_help = false;
+                return Ok(true);
+            }
+            if self.stats_overlay.take().is_some() {
+                return Ok(true);
+            }
+            let Some(action) = self.resolve_action(&key) else {
+                return Ok(true);
+            };
+
+            let in_tree = self.view_mode == ViewMode::Tree;
+            let in_raw = self.view_mode == ViewMode::Raw;
+            if in_raw && self.visual.is_some() {
+                self.handle_visual_action(action)?;
+                return Ok(true);
+            }
+
+            match action {
+                Action::Quit => return Ok(false),
+                Action::Paste => self.paste_from_clipboard()?,
+                Action::ValidateOnly => self.validate_clipboard_only()?,
+                Action::PasteXml => self.paste_xml_from_clipboard()?,
+                Action::PasteYaml => self.paste_yaml_from_clipboard()?,
+                Action::PasteToml => self.paste_toml_from_clipboard()?,
+                Action::EditValue => {
+                    if let Some(value) = self.selected_leaf() {
+                        let mut prompt = Prompt::new(PromptKind::EditValue(ValueType::of(value)));
+                        prompt.input = match value {
+                            Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        };
+                        self.prompt = Some(prompt);
+                    }
                 }
-                KeyCode::Char('e') if key.kind == KeyEventKind::Press => {
-                    self.create_temp_file_for_editing()?;
+                Action::CreateTempFile => self.create_temp_file_for_editing()?,
+                Action::NextMatch if in_raw => self.cycle_match(true),
+                Action::PrevMatch if in_raw && self.search.is_some() => self.cycle_match(false),
+                Action::NextMatch => self.cycle_tree_match(true),
+                Action::PrevMatch if in_tree && self.tree_search.is_some() => self.cycle_tree_match(false),
+                Action::OpenEditor => self.open_in_editor()?,
+                Action::ToggleView => {
+                    self.view_mode = match self.view_mode {
+                        ViewMode::Raw => ViewMode::Tree,
+                        ViewMode::Tree | ViewMode::Converted => ViewMode::Raw,
+                    };
                 }
-                KeyCode::Char('n') if key.kind == KeyEventKind::Press => {
-                    self.open_in_neovim()?;
+                Action::NavUp | Action::NavDown if self.diff_view().is_some() => {
+                    if let Some(view) = self.converted.as_mut().and_then(|converted| converted.diff.as_mut()) {
+                        view.selected = if action == Action::NavUp {
+                            view.selected.saturating_sub(1)
+                        } else {
+                            (view.selected + 1).min(view.changes.len().saturating_sub(1))
+                        };
+                    }
                 }
-                KeyCode::Char('t') if key.kind == KeyEventKind::Press => {
-                    self.view_mode = if self.view_mode == ViewMode::Tree {
-                        ViewMode::Raw
-                    } else {
-                        ViewMode::Tree
-                    };
+                Action::ScrollLeft if self.diff_view().is_some() => {
+                    self.horizontal_scroll = self.horizontal_scroll.saturating_sub(8);
                 }
-                KeyCode::Up | KeyCode::Char('k') if key.kind == KeyEventKind::Press => {
-                    if self.view_mode == ViewMode::Tree {
-                        self.move_selection_up();
-                    } else {
-                        if self.scroll_offset > 0 {
-                            self.scroll_offset -= 1;
+                Action::ScrollRight if self.diff_view().is_some() => self.horizontal_scroll += 8,
+                Action::NavUp => {
+                    if in_tree {
+                        self.step_selection(false);
+                        if self.copy_on_select {
+                            self.pending_copy = Some(Instant::now());
                         }
+                    } else if self.scroll_offset > 0 {
+                        self.scroll_offset -= 1;
                     }
                 }
-                KeyCode::Down | KeyCode::Char('j') if key.kind == KeyEventKind::Press => {
-                    if self.view_mode == ViewMode::Tree {
-                        self.move_selection_down();
+                Action::NavDown => {
+                    if in_tree {
+                        self.step_selection(true);
+                        if self.copy_on_select {
+                            self.pending_copy = Some(Instant::now());
+                        }
+                    } else if in_raw {
+                        self.scroll_offset = (self.scroll_offset + 1).min(self.max_raw_scroll());
                     } else {
                         self.scroll_offset += 1;
                     }
                 }
-                KeyCode::Char(' ') if key.kind == KeyEventKind::Press && self.view_mode == ViewMode::Tree => {
-                    self.toggle_node();
+                Action::PageUp if in_raw => {
+                    self.scroll_offset = self.scroll_offset.saturating_sub(self.raw_view_height.get().max(1));
+                }
+                Action::PageDown if in_raw => {
+                    let page = self.raw_view_height.get().max(1);
+                    self.scroll_offset = (self.scroll_offset + page).min(self.max_raw_scroll());
+                }
+                Action::ScrollTop if in_raw => self.scroll_offset = 0,
+                Action::ScrollBottom if in_raw => self.scroll_offset = self.max_raw_scroll(),
+                Action::PageUp | Action::PageDown | Action::ScrollTop | Action::ScrollBottom if in_tree => {
+                    let page = self.tree_view_height.get().max(1) as isize;
+                    self.move_selection_by(match action {
+                        Action::PageUp => -page,
+                        Action::PageDown => page,
+                        Action::ScrollTop => isize::MIN,
+                        _ => isize::MAX,
+                    });
+                    if self.copy_on_select {
+                        self.pending_copy = Some(Instant::now());
+                    }
+                }
+                Action::Search if self.flat_view().is_some() => {
+                    let mut prompt = Prompt::new(PromptKind::FilterFlat);
+                    prompt.input = self.flat_view().map(|flat| flat.filter.clone()).unwrap_or_default();
+                    self.prompt = Some(prompt);
+                }
+                Action::Search if in_raw && self.is_valid && !self.show_minified => {
+                    let mut prompt = Prompt::new(PromptKind::SearchText);
+                    prompt.input = self.search.as_ref().map(|search| search.query.clone()).unwrap_or_default();
+                    self.prompt = Some(prompt);
+                }
+                Action::Search if in_tree => {
+                    let mut prompt = Prompt::new(PromptKind::SearchTree);
+                    prompt.input = self.tree_search.as_ref().map(|search| search.query.clone()).unwrap_or_default();
+                    self.prompt = Some(prompt);
+                }
+                Action::GoToPath if in_tree => {
+                    let mut prompt = Prompt::new(PromptKind::GoToPath);
+                    prompt.input = self.path_query.clone();
+                    self.prompt = Some(prompt);
+                }
+                Action::Bookmark if in_tree => self.toggle_bookmark(),
+                Action::NextBookmark if in_tree => self.jump_to_bookmark(true),
+                Action::PrevBookmark if in_tree => self.jump_to_bookmark(false),
+                Action::ToggleNode if in_tree => self.toggle_node(),
+                Action::CopyPath if in_tree => self.copy_path_to_clipboard()?,
+                Action::CopyJqPath if in_tree => self.copy_jq_path_to_clipboard()?,
+                Action::CompareClipboard if self.is_valid => self.compare_with_clipboard(),
+                Action::CompareFile if self.is_valid => {
+                    self.prompt = Some(Prompt::new(PromptKind::CompareFile));
+                }
+                Action::CopyLeaves if self.is_valid => self.copy_leaf_values(false)?,
+                Action::CopyLeavesWithPaths if self.is_valid => self.copy_leaf_values(true)?,
+                Action::PasteCsv => self.paste_csv_from_clipboard(true)?,
+                Action::PasteCsvStrings => self.paste_csv_from_clipboard(false)?,
+                Action::ToggleFragmentMode => {
+                    self.fragment_mode = !self.fragment_mode;
+                    if !self.raw_input.is_empty() {
+                        self.parse_json();
+                    }
+                    let status = format!(
+                        "Fragment mode {}",
+                        if self.fragment_mode { "on: incomplete input is wrapped in {} or []" } else { "off" }
+                    );
+                    self.set_status(status);
+                }
+                Action::CycleIndent => {
+                    self.indent = self.indent.next();
+                    config::store("json", "indent", self.indent.config_value().into());
+                    self.reformat();
+                    self.set_status(format!("Indenting with {}", self.indent.label()));
+                }
+                Action::ToggleLenientMode => {
+                    self.lenient_mode = !self.lenient_mode;
+                    if !self.raw_input.is_empty() {
+                        self.parse_json();
+                    }
+                    let status = format!(
+                        "Lenient parsing {}",
+                        if self.lenient_mode { "on: JSON5 and JSONC are read as JSON" } else { "off" }
+                    );
+                    self.set_status(status);
+                }
+                Action::CycleNumberBase if in_tree => {
+                    self.number_base = self.number_base.next();
+                    self.set_status(format!("Showing integers in {}", self.number_base.label()));
+                }
+                Action::DeleteNode if in_tree => self.request_delete(),
+                Action::CopySubtreeMinified if in_tree => self.copy_subtree_minified()?,
+                Action::MoveElementUp if in_tree => self.move_array_element(true),
+                Action::MoveElementDown if in_tree => self.move_array_element(false),
+                Action::ToggleMinified if in_raw => {
+                    self.show_minified = !self.show_minified;
+                    self.horizontal_scroll = 0;
+                }
+                Action::ScrollLeft if in_raw && self.show_minified => {
+                    self.horizontal_scroll = self.horizontal_scroll.saturating_sub(8);
+                }
+                Action::ScrollRight if in_raw && self.show_minified => {
+                    let max_scroll = self.minified_json.chars().count().saturating_sub(1);
+                    self.horizontal_scroll = (self.horizontal_scroll + 8).min(max_scroll);
+                }
+                Action::CopyValue => self.copy_node_value_to_clipboard()?,
+                Action::VisualSelect if in_raw && self.is_valid && !self.show_minified => {
+                    let last_line = self.displayed_pretty().lines().count().saturating_sub(1);
+                    self.visual = Some(VisualSelection::new(self.scroll_offset.min(last_line)));
+                }
+                Action::ToggleSplit => {
+                    self.split_view = !self.split_view;
+                    if self.split_view {
+                        self.view_mode = ViewMode::Tree;
+                    }
+                }
+                Action::SortKeys if self.flat_view().is_some() => self.toggle_flat_sort(),
+                Action::SortKeys if self.is_valid => self.sort_keys(),
+                Action::SortArraysBy if self.is_valid => {
+                    self.prompt = Some(Prompt::new(PromptKind::SortArraysBy));
+                }
+                Action::LoadSchema => {
+                    let mut prompt = Prompt::new(PromptKind::LoadSchema);
+                    if let Some((ref path, _)) = self.schema {
+                        prompt.input = path.display().to_string();
+                    }
+                    self.prompt = Some(prompt);
+                }
+                Action::SchemaScrollDown if self.schema_scroll + 1 < self.schema_violations.len() => {
+                    self.schema_scroll += 1;
+                }
+                Action::SchemaScrollUp => self.schema_scroll = self.schema_scroll.saturating_sub(1),
+                Action::OpenFile => self.prompt = Some(Prompt::new(PromptKind::OpenFile)),
+                Action::ExportFile | Action::ExportMinified if self.is_valid => {
+                    let minified = action == Action::ExportMinified;
+                    let mut prompt = Prompt::new(PromptKind::ExportFile { minified });
+                    prompt.input = DEFAULT_EXPORT_FILE.to_string();
+                    self.prompt = Some(prompt);
+                }
+                Action::ExportCsv if self.is_valid => {
+                    let mut prompt = Prompt::new(PromptKind::ExportCsv);
+                    prompt.input = DEFAULT_CSV_FILE.to_string();
+                    self.prompt = Some(prompt);
+                }
+                Action::CopyTempPath => self.copy_temp_file_path()?,
+                Action::RevealTempFile => self.reveal_temp_file(),
+                Action::ForgetTempFile => self.forget_temp_file(),
+                Action::Help => self.show_help = true,
+                Action::ShowStats if self.is_valid => self.stats_overlay = self.parsed_value.as_ref().map(Stats::collect),
+                Action::ToggleWrap => {
+                    self.wrap_navigation = !self.wrap_navigation;
+                    self.set_status(format!(
+                        "Wrap-around navigation {}",
+                        if self.wrap_navigation { "on" } else { "off" }
+                    ));
+                }
+                Action::FindRepeated if self.is_valid => self.find_repeated_subtrees(),
+                Action::PatchFromClipboard if self.is_valid => self.patch_from_clipboard(),
+                Action::ApplyPatch if self.is_valid => self.apply_patch_from_clipboard(),
+                Action::Flatten if self.is_valid => self.flatten_document(),
+                Action::UnwrapStringified if self.is_valid => self.unwrap_stringified(),
+                Action::Canonicalize if self.is_valid => self.copy_canonical()?,
+                Action::CopyRustStructs if self.is_valid => self.copy_rust_structs()?,
+                Action::CopyTypeScript if self.is_valid => {
+                    let mut prompt = Prompt::new(PromptKind::TypeScriptRoot);
+                    prompt.input = self.root_type_name.clone();
+                    self.prompt = Some(prompt);
                 }
-                KeyCode::Enter if key.kind == KeyEventKind::Press && self.view_mode == ViewMode::Tree => {
-                    self.toggle_node();
+                Action::ExtractValues if self.is_valid => {
+                    self.prompt = Some(Prompt::new(PromptKind::ExtractValues));
                 }
-                KeyCode::Char('c') if key.kind == KeyEventKind::Press => {
-                    self.copy_to_clipboard()?;
+                Action::ExpandAll if self.view_mode == ViewMode::Tree => self.expand_all(),
+                Action::CollapseAll if self.view_mode == ViewMode::Tree => self.collapse_all(),
+                Action::ExpandToDepth if self.view_mode == ViewMode::Tree && !self.json_tree.is_empty() => {
+                    let mut prompt = Prompt::new(PromptKind::ExpandToDepth);
+                    prompt.input = self.shown_depth().to_string();
+                    self.prompt = Some(prompt);
                 }
-                KeyCode::Char('C') if key.kind == KeyEventKind::Press => {
-                    self.copy_minified_to_clipboard()?;
+                Action::ExpandLevel if in_tree && !self.json_tree.is_empty() => self.step_depth(true),
+                Action::CollapseLevel if in_tree && !self.json_tree.is_empty() => self.step_depth(false),
+                Action::ExpandSubtree if in_tree => self.set_subtree_expansion(true),
+                Action::CollapseSubtree if in_tree => self.set_subtree_expansion(false),
+                Action::ToggleCopyOnSelect => {
+                    self.copy_on_select = !self.copy_on_select;
+                    self.pending_copy = None;
+                    self.set_status(format!(
+                        "Copy-on-select {}",
+                        if self.copy_on_select { "on" } else { "off" }
+                    ));
                 }
-                KeyCode::Esc if key.kind == KeyEventKind::Press => return Ok(false),
+                Action::ToggleMask => self.toggle_masking(),
+                Action::Undo => self.undo(),
+                Action::Redo => self.redo(),
+                Action::CopyRedacted => self.copy_redacted_to_clipboard()?,
+                Action::InsertMode if in_raw => {
+                    self.record_undo();
+                    self.typing = true;
+                    self.visual = None;
+                }
+                Action::ToggleAnnotations if in_raw => {
+                    self.show_annotations = !self.show_annotations;
+                }
+                Action::ToXml => self.convert_to_xml(),
+                Action::ToYaml => self.convert_to_yaml(),
+                Action::ToToml => self.convert_to_toml(),
+                Action::Copy => self.copy_to_clipboard()?,
+                Action::CopyMinified => self.copy_minified_to_clipboard()?,
                 _ => {}
             }
+
+            if self.split_view && self.view_mode == ViewMode::Tree {
+                self.sync_raw_scroll();
+            }
         }
         Ok(true)
     }
+
+    /// Scrolls the raw pane so the selected tree node's first line sits near the top.
+    fn sync_raw_scroll(&mut self) {
+        if self.selected_node < self.json_tree.len() {
+            self.scroll_offset = self.node_line(self.selected_node).saturating_sub(SCROLL_CONTEXT_LINES);
+        }
+    }
+}
+
+/// The editor from `$VISUAL`, then `$EDITOR`, falling back to `nvim`, split into the program
+/// and its leading arguments so values like `code --wait` work.
+fn editor_command() -> (String, Vec<String>) {
+    let command = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "nvim".to_string());
+    let mut parts = command.split_whitespace().map(str::to_string);
+    let program = parts.next().unwrap_or_else(|| "nvim".to_string());
+    (program, parts.collect())
+}
+
+/// Parses a bare fragment such as `"a": 1, "b": 2` or `1, 2, 3` by wrapping it in an object
+/// or an array, tolerating a trailing comma left over from copying out of a larger document.
+fn wrap_fragment(input: &str) -> Option<(&'static str, Value)> {
+    let fragment = input.trim().trim_end_matches(',');
+    [("{}", '{', '}'), ("[]", '[', ']')].into_iter().find_map(|(name, open, close)| {
+        serde_json::from_str(&format!("{}{}{}", open, fragment, close))
+            .ok()
+            .map(|value| (name, value))
+    })
+}
+
+/// `path` moved from under `from` to under `to`, or `None` when it isn't inside `from`.
+fn rebase_path(path: &str, from: &str, to: &str) -> Option<String> {
+    let rest = path.strip_prefix(from)?;
+    (rest.is_empty() || rest.starts_with('.') || rest.starts_with('[')).then(|| format!("{}{}", to, rest))
+}
+
+/// `value` pretty-printed, each level indented by `indent`.
+fn to_pretty(value: &Value, indent: Indent) -> serde_json::Result<String> {
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.text().as_bytes());
+    value.serialize(&mut serde_json::Serializer::with_formatter(&mut out, formatter))?;
+    // serde_json only writes valid UTF-8
+    Ok(String::from_utf8(out).unwrap_or_default())
+}
+
+/// Path of a child as the tree shows it, e.g. `items[0].name`. `key` is `[i]` for an array
+/// item.
+fn child_path(parent: &str, key: &str, in_array: bool) -> String {
+    match (parent == "root", in_array) {
+        (true, _) => key.to_string(),
+        (false, true) => format!("{}{}", parent, key),
+        (false, false) => format!("{}.{}", parent, key),
+    }
+}
+
+/// Whether `path` is `prefix` or lies under it.
+fn is_path_prefix(prefix: &str, path: &str) -> bool {
+    path.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+}
+
+/// Adds the node for `value` to `out`, followed by its children when `expanded` says it
+/// starts out expanded.
+fn push_subtree(
+    value: &Value,
+    key: String,
+    pointer: String,
+    path: String,
+    depth: usize,
+    expanded: &dyn Fn(&str, usize) -> bool,
+    out: &mut Vec<JsonTreeNode>,
+) {
+    let expand = expanded(&path, depth);
+    let node = JsonTreeNode::new(value, key, pointer, path, depth, expand);
+    let index = out.len();
+    let load = node.expanded;
+    out.push(node);
+    if load {
+        out[index].loaded = true;
+        let (pointer, path) = (out[index].pointer.clone(), out[index].path.clone());
+        push_children(&pointer, &path, depth, value, expanded, out);
+    }
+}
+
+/// Adds the children of the container `value`, found at `pointer` and `path`, to `out`.
+fn push_children(
+    pointer: &str,
+    path: &str,
+    depth: usize,
+    value: &Value,
+    expanded: &dyn Fn(&str, usize) -> bool,
+    out: &mut Vec<JsonTreeNode>,
+) {
+    match value {
+        Value::Object(obj) => {
+            for (k, v) in obj {
+                let child_pointer = format!("{}/{}", pointer, k.replace('~', "~0").replace('/', "~1"));
+                push_subtree(v, k.clone(), child_pointer, child_path(path, k, false), depth + 1, expanded, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                let key = format!("[{}]", i);
+                let child_path = child_path(path, &key, true);
+                push_subtree(v, key, format!("{}/{}", pointer, i), child_path, depth + 1, expanded, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Calls `visit` with the path, key and value of `value` and everything under it, in the
+/// order the tree lists them. `path` is the value's path, empty for the root, and is
+/// restored before returning. Array items have an empty key.
+fn walk_nodes(value: &Value, path: &mut String, key: &str, visit: &mut dyn FnMut(&str, &str, &Value)) {
+    visit(if path.is_empty() { "root" } else { path }, key, value);
+    let len = path.len();
+    match value {
+        Value::Object(obj) => {
+            for (k, v) in obj {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(k);
+                walk_nodes(v, path, k, visit);
+                path.truncate(len);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                path.push_str(&format!("[{}]", i));
+                walk_nodes(v, path, "", visit);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Number of nodes the tree would have with everything expanded.
+fn count_nodes(value: &Value) -> usize {
+    1 + match value {
+        Value::Object(obj) => obj.values().map(count_nodes).sum(),
+        Value::Array(items) => items.iter().map(count_nodes).sum(),
+        _ => 0,
+    }
+}
+
+/// First row to show so that `row` is on screen, moving `offset` as little as possible.
+fn scroll_into_view(offset: usize, row: usize, height: usize, len: usize) -> usize {
+    let height = height.max(1);
+    let offset = if row < offset {
+        row
+    } else if row >= offset + height {
+        row + 1 - height
+    } else {
+        offset
+    };
+    offset.min(len.saturating_sub(height))
+}
+
+/// Levels of nested non-empty containers in `value`, which is the depth the tree needs to
+/// be expanded to for every node to show.
+fn nesting_depth(value: &Value) -> usize {
+    match value {
+        Value::Object(obj) if !obj.is_empty() => 1 + obj.values().map(nesting_depth).max().unwrap_or(0),
+        Value::Array(items) if !items.is_empty() => 1 + items.iter().map(nesting_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Lines `value` takes up in `to_string_pretty` output: one for a scalar or empty
+/// container, plus a closing line for a non-empty container.
+fn pretty_lines(value: &Value) -> usize {
+    match value {
+        Value::Object(obj) if !obj.is_empty() => 2 + obj.values().map(pretty_lines).sum::<usize>(),
+        Value::Array(items) if !items.is_empty() => 2 + items.iter().map(pretty_lines).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+/// Pasted text parsed as JSON, with errors worded for the status line.
+fn pasted_json(text: &str) -> std::result::Result<Value, String> {
+    serde_json::from_str(text.trim_start_matches('\u{feff}')).map_err(|e| format!("Pasted text is not valid JSON: {}", e))
+}
+
+/// Resolves a leading `~/` to the home directory, as a shell would.
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map_or_else(|| path.into(), |home| home.join(rest)),
+        None => path.into(),
+    }
+}
+
+/// Completes the last component of `input` against the directory it names, as far as
+/// the matching entries agree, with a `/` after a directory. Also returns the matching
+/// names, so an ambiguous completion can list them. Hidden entries only match once the
+/// component starts with `.`.
+fn complete_path(input: &str) -> (String, Vec<String>) {
+    let (dir, partial) = match input.rfind('/') {
+        Some(slash) => input.split_at(slash + 1),
+        None => ("", input),
+    };
+    let entries = match fs::read_dir(expand_home(if dir.is_empty() { "." } else { dir })) {
+        Ok(entries) => entries,
+        Err(_) => return (input.to_string(), Vec::new()),
+    };
+    let mut matches: Vec<(String, bool)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let is_dir = entry.path().is_dir();
+            Some((name, is_dir))
+        })
+        .filter(|(name, _)| name.starts_with(partial) && (partial.starts_with('.') || !name.starts_with('.')))
+        .collect();
+    matches.sort();
+
+    let Some((first, _)) = matches.first() else {
+        return (input.to_string(), Vec::new());
+    };
+    let mut common = first.len();
+    for (name, _) in &matches[1..] {
+        common = first.bytes().zip(name.bytes()).take(common).take_while(|(a, b)| a == b).count();
+    }
+    while !first.is_char_boundary(common) {
+        common -= 1;
+    }
+    let mut completed = format!("{}{}", dir, &first[..common]);
+    if let [(_, true)] = matches.as_slice() {
+        completed.push('/');
+    }
+    (completed, matches.into_iter().map(|(name, _)| name).collect())
+}
+
+/// Whitespace that puts a caret under byte column `column` (1-based, as serde_json reports
+/// it) of `line`. Tabs are kept so the caret lines up however the terminal expands them.
+fn caret_indent(line: &str, column: usize) -> String {
+    let mut end = column.saturating_sub(1).min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    line[..end]
+        .chars()
+        .map(|c| if c == '\t' { "\t".to_string() } else { " ".repeat(c.width().unwrap_or(0)) })
+        .collect()
+}
+
+/// Human-readable byte count, e.g. `512 B`, `4.2 KB`, `1.3 MB`.
+pub(crate) fn format_bytes(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    let size = bytes as f64;
+    if size >= MB {
+        format!("{:.1} MB", size / MB)
+    } else if size >= KB {
+        format!("{:.1} KB", size / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+fn is_jq_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Collects the size labels of non-empty containers in the same pre-order the pretty printer
+/// emits their opening lines.
+/// Hash of the text of `text` and the width it is wrapped to, which is all the wrapped
+/// rows depend on.
+fn text_key(text: &Text, width: u16) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    width.hash(&mut hasher);
+    for line in &text.lines {
+        for span in &line.spans {
+            span.content.hash(&mut hasher);
+        }
+        hasher.write_u8(b'\n');
+    }
+    hasher.finish()
+}
+
+/// Running row totals of `text` wrapped to `width`: entry `i` is the rows above line `i`
+/// and the last entry is the row count of the whole text.
+fn wrapped_rows(text: &Text, width: u16) -> Vec<usize> {
+    let mut rows = Vec::with_capacity(text.lines.len() + 1);
+    let mut total = 0;
+    rows.push(total);
+    for line in &text.lines {
+        total += Paragraph::new(line.clone()).wrap(Wrap { trim: false }).line_count(width);
+        rows.push(total);
+    }
+    rows
+}
+
+fn collect_container_sizes(value: &Value, sizes: &mut Vec<String>) {
+    match value {
+        Value::Object(obj) if !obj.is_empty() => {
+            let noun = if obj.len() == 1 { "key" } else { "keys" };
+            sizes.push(format!("{} {}", obj.len(), noun));
+            for v in obj.values() {
+                collect_container_sizes(v, sizes);
+            }
+        }
+        Value::Array(arr) if !arr.is_empty() => {
+            let noun = if arr.len() == 1 { "item" } else { "items" };
+            sizes.push(format!("{} {}", arr.len(), noun));
+            for v in arr {
+                collect_container_sizes(v, sizes);
+            }
+        }
+        _ => {}
+    }
 }
 
 pub fn run_json_utils() -> Result<()> {
-    let mut terminal = ratatui::init();
     let mut json_utils = JsonUtils::new();
+    json_utils.restore_session();
+    run(json_utils)
+}
+
+/// Opens the viewer on `text` instead of the saved session.
+pub fn run_json_utils_with_input(text: String) -> Result<()> {
+    let mut json_utils = JsonUtils::new();
+    json_utils.load_text(text);
+    run(json_utils)
+}
+
+fn run(mut json_utils: JsonUtils) -> Result<()> {
+    let mut terminal = ratatui::init();
+    crossterm::execute!(std::io::stdout(), EnableMouseCapture)?;
 
     loop {
         json_utils.check_file_changes()?;
+        json_utils.run_debounced_parse();
+        json_utils.run_debounced_copy()?;
+        json_utils.expire_status();
 
         if json_utils.needs_terminal_reinit {
             terminal = ratatui::init();
+            crossterm::execute!(std::io::stdout(), EnableMouseCapture)?;
             json_utils.needs_terminal_reinit = false;
         }
 
         terminal.draw(|frame| {
             json_utils.render(frame, frame.area());
+            crate::color::adapt_buffer(frame.buffer_mut());
         })?;
 
-        if event::poll(Duration::from_millis(100))? {
+        if event::poll(json_utils.poll_interval())? {
             let event = event::read()?;
             if !json_utils.handle_event(event)? {
                 break;
@@ -507,6 +4213,9 @@ pub fn run_json_utils() -> Result<()> {
         }
     }
 
+    json_utils.save_session();
+    json_utils.store_default_view();
+    crossterm::execute!(std::io::stdout(), DisableMouseCapture)?;
     ratatui::restore();
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,349 @@
+//! JSON Patch (RFC 6902): generating a patch between two documents and applying one.
+//!
+//! Generated patches follow the same comparison as the structural diff: objects key by key,
+//! arrays index by index. Extra array elements are added in order and surplus ones removed
+//! from the end backwards, so every index is valid at the point its operation runs. Only
+//! `add`, `remove` and `replace` are generated; applying also supports `move`, `copy` and
+//! `test`. A patch is applied to a copy of the document, so one that fails part way leaves
+//! the document as it was.
+
+use serde_json::{json, Map, Value};
+
+use super::diff;
+
+/// `key` escaped as one reference token of a JSON Pointer.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn walk(pointer: &str, old: &Value, new: &Value, ops: &mut Vec<Value>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, old_child) in a {
+                let child = format!("{}/{}", pointer, escape(key));
+                match b.get(key) {
+                    Some(new_child) => walk(&child, old_child, new_child, ops),
+                    None => ops.push(json!({"op": "remove", "path": child})),
+                }
+            }
+            for (key, new_child) in b {
+                if !a.contains_key(key) {
+                    let child = format!("{}/{}", pointer, escape(key));
+                    ops.push(json!({"op": "add", "path": child, "value": new_child}));
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (i, (old_child, new_child)) in a.iter().zip(b).enumerate() {
+                walk(&format!("{}/{}", pointer, i), old_child, new_child, ops);
+            }
+            for (i, new_child) in b.iter().enumerate().skip(a.len()) {
+                ops.push(json!({"op": "add", "path": format!("{}/{}", pointer, i), "value": new_child}));
+            }
+            for i in (b.len()..a.len()).rev() {
+                ops.push(json!({"op": "remove", "path": format!("{}/{}", pointer, i)}));
+            }
+        }
+        _ if diff::same(old, new) => {}
+        _ => ops.push(json!({"op": "replace", "path": pointer, "value": new})),
+    }
+}
+
+/// A patch that turns `old` into `new`, as a JSON array of operations.
+pub fn generate(old: &Value, new: &Value) -> Value {
+    let mut ops = Vec::new();
+    walk("", old, new, &mut ops);
+    Value::Array(ops)
+}
+
+/// Splits a JSON Pointer into its unescaped reference tokens.
+fn tokens(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("'{}' is not a JSON Pointer (it must start with '/')", pointer));
+    };
+    Ok(rest.split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+/// Index named by `token` in an array of `len` elements. `-` means one past the end, which
+/// only `add` accepts.
+fn array_index(token: &str, len: usize, allow_end: bool) -> Result<usize, String> {
+    if token == "-" && allow_end {
+        return Ok(len);
+    }
+    // Leading zeros (and signs) aren't allowed in array indices
+    let digits = token.bytes().all(|byte| byte.is_ascii_digit()) && (token == "0" || !token.starts_with('0'));
+    match token.parse::<usize>() {
+        Ok(i) if digits && (i < len || (allow_end && i == len)) => Ok(i),
+        Ok(_) if digits => Err(format!("index {} is out of bounds", token)),
+        _ => Err(format!("'{}' is not an array index", token)),
+    }
+}
+
+/// The container holding the last token of `pointer`, and that token.
+fn parent<'a>(document: &'a mut Value, pointer: &str) -> Result<(&'a mut Value, String), String> {
+    let mut tokens = tokens(pointer)?;
+    let Some(last) = tokens.pop() else {
+        return Err("the root has no parent".to_string());
+    };
+    let mut target = document;
+    for token in &tokens {
+        target = match target {
+            Value::Object(obj) => obj.get_mut(token),
+            Value::Array(items) => {
+                let i = array_index(token, items.len(), false)?;
+                items.get_mut(i)
+            }
+            _ => None,
+        }
+        .ok_or_else(|| format!("{} does not exist", pointer))?;
+    }
+    Ok((target, last))
+}
+
+fn get<'a>(document: &'a Value, pointer: &str) -> Result<&'a Value, String> {
+    let tokens = tokens(pointer)?;
+    let mut target = document;
+    for token in &tokens {
+        target = match target {
+            Value::Object(obj) => obj.get(token),
+            Value::Array(items) => items.get(array_index(token, items.len(), false)?),
+            _ => None,
+        }
+        .ok_or_else(|| format!("{} does not exist", pointer))?;
+    }
+    Ok(target)
+}
+
+fn add(document: &mut Value, pointer: &str, value: Value) -> Result<(), String> {
+    if pointer.is_empty() {
+        *document = value;
+        return Ok(());
+    }
+    let (container, last) = parent(document, pointer)?;
+    match container {
+        Value::Object(obj) => {
+            obj.insert(last, value);
+        }
+        Value::Array(items) => {
+            let i = array_index(&last, items.len(), true)?;
+            items.insert(i, value);
+        }
+        _ => return Err(format!("the parent of {} is not an object or array", pointer)),
+    }
+    Ok(())
+}
+
+fn remove(document: &mut Value, pointer: &str) -> Result<Value, String> {
+    let (container, last) = parent(document, pointer)?;
+    match container {
+        Value::Object(obj) => obj.shift_remove(&last).ok_or_else(|| format!("{} does not exist", pointer)),
+        Value::Array(items) => {
+            let i = array_index(&last, items.len(), false)?;
+            Ok(items.remove(i))
+        }
+        _ => Err(format!("{} does not exist", pointer)),
+    }
+}
+
+/// Reads a string member of an operation, e.g. its `path`.
+fn member<'a>(op: &'a Map<String, Value>, name: &str) -> Result<&'a str, String> {
+    op.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("missing \"{}\"", name))
+}
+
+fn apply_one(document: &mut Value, op: &Value) -> Result<(), String> {
+    let Some(op) = op.as_object() else {
+        return Err("not an object".to_string());
+    };
+    let path = member(op, "path")?;
+    let value = || op.get("value").cloned().ok_or_else(|| "missing \"value\"".to_string());
+    match member(op, "op")? {
+        "add" => add(document, path, value()?),
+        "remove" if path.is_empty() => Err("can't remove the root".to_string()),
+        "remove" => remove(document, path).map(drop),
+        "replace" => {
+            let value = value()?;
+            get(document, path)?;
+            // Written in place so a replaced member keeps its position in the object
+            if let Some(target) = document.pointer_mut(path) {
+                *target = value;
+            }
+            Ok(())
+        }
+        "move" => {
+            let from = member(op, "from")?;
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(format!("can't move {} into itself", from));
+            }
+            let moved = if from.is_empty() { document.take() } else { remove(document, from)? };
+            add(document, path, moved)
+        }
+        "copy" => {
+            let copied = get(document, member(op, "from")?)?.clone();
+            add(document, path, copied)
+        }
+        "test" => {
+            let expected = value()?;
+            if diff::same(get(document, path)?, &expected) {
+                Ok(())
+            } else {
+                Err(format!("test failed: {} is not {}", display(path), expected))
+            }
+        }
+        other => Err(format!("unknown op \"{}\"", other)),
+    }
+}
+
+fn display(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "the root"
+    } else {
+        pointer
+    }
+}
+
+/// `document` with `patch` (an array of operations) applied, or what went wrong, naming
+/// the operation that failed.
+pub fn apply(document: &Value, patch: &Value) -> Result<Value, String> {
+    let Some(ops) = patch.as_array() else {
+        return Err("a JSON Patch is an array of operations".to_string());
+    };
+    let mut patched = document.clone();
+    for (i, op) in ops.iter().enumerate() {
+        apply_one(&mut patched, op).map_err(|e| format!("operation {}: {}", i + 1, e))?;
+    }
+    Ok(patched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patched(document: Value, patch: Value) -> Value {
+        apply(&document, &patch).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    #[test]
+    fn add_inserts_members_and_elements() {
+        let document = json!({"a": [1, 3]});
+        assert_eq!(patched(document.clone(), json!([{"op": "add", "path": "/b", "value": 2}])), json!({"a": [1, 3], "b": 2}));
+        assert_eq!(patched(document.clone(), json!([{"op": "add", "path": "/a/1", "value": 2}])), json!({"a": [1, 2, 3]}));
+        assert_eq!(patched(document.clone(), json!([{"op": "add", "path": "/a/-", "value": 4}])), json!({"a": [1, 3, 4]}));
+        assert_eq!(patched(document.clone(), json!([{"op": "add", "path": "/a/2", "value": 4}])), json!({"a": [1, 3, 4]}));
+        assert_eq!(patched(document.clone(), json!([{"op": "add", "path": "", "value": null}])), json!(null));
+        assert!(apply(&document, &json!([{"op": "add", "path": "/a/3", "value": 4}])).is_err());
+        assert!(apply(&document, &json!([{"op": "add", "path": "/a/01", "value": 4}])).is_err());
+        assert!(apply(&document, &json!([{"op": "add", "path": "/x/y", "value": 4}])).is_err());
+    }
+
+    #[test]
+    fn remove_and_replace_need_an_existing_target() {
+        let document = json!({"a": {"b": 1, "c": 2}, "list": [1, 2, 3]});
+        assert_eq!(
+            patched(document.clone(), json!([{"op": "remove", "path": "/a/b"}, {"op": "remove", "path": "/list/0"}])),
+            json!({"a": {"c": 2}, "list": [2, 3]})
+        );
+        assert_eq!(
+            patched(document.clone(), json!([{"op": "replace", "path": "/list/2", "value": "x"}])),
+            json!({"a": {"b": 1, "c": 2}, "list": [1, 2, "x"]})
+        );
+        assert_eq!(patched(document.clone(), json!([{"op": "replace", "path": "", "value": 1}])), json!(1));
+        for op in [
+            json!({"op": "remove", "path": "/a/missing"}),
+            json!({"op": "remove", "path": "/list/-"}),
+            json!({"op": "remove", "path": ""}),
+            json!({"op": "replace", "path": "/missing", "value": 1}),
+            json!({"op": "replace", "path": "/list/3", "value": 1}),
+        ] {
+            assert!(apply(&document, &json!([op])).is_err(), "{}", op);
+        }
+    }
+
+    #[test]
+    fn replace_keeps_the_member_in_place() {
+        let result = patched(json!({"a": 1, "b": 2, "c": 3}), json!([{"op": "replace", "path": "/b", "value": 20}]));
+        let keys: Vec<&String> = result.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn move_and_copy_take_from_another_path() {
+        let document = json!({"a": {"b": 1}, "list": [1, 2]});
+        assert_eq!(
+            patched(document.clone(), json!([{"op": "move", "from": "/a/b", "path": "/list/0"}])),
+            json!({"a": {}, "list": [1, 1, 2]})
+        );
+        assert_eq!(
+            patched(document.clone(), json!([{"op": "move", "from": "/list/0", "path": "/list/-"}])),
+            json!({"a": {"b": 1}, "list": [2, 1]})
+        );
+        assert_eq!(
+            patched(document.clone(), json!([{"op": "copy", "from": "/a", "path": "/c"}])),
+            json!({"a": {"b": 1}, "c": {"b": 1}, "list": [1, 2]})
+        );
+        assert!(apply(&document, &json!([{"op": "move", "from": "/a", "path": "/a/b/c"}])).is_err());
+        assert!(apply(&document, &json!([{"op": "copy", "from": "/missing", "path": "/c"}])).is_err());
+    }
+
+    #[test]
+    fn test_compares_the_value_at_a_path() {
+        let document = json!({"a": [1, {"b": "x"}], "n": 1});
+        assert_eq!(patched(document.clone(), json!([{"op": "test", "path": "/a/1", "value": {"b": "x"}}])), document);
+        assert_eq!(patched(document.clone(), json!([{"op": "test", "path": "/n", "value": 1.0}])), document);
+        let error = apply(&document, &json!([{"op": "test", "path": "/a/0", "value": 2}])).unwrap_err();
+        assert_eq!(error, "operation 1: test failed: /a/0 is not 2");
+    }
+
+    #[test]
+    fn escaped_tokens_name_keys_with_slashes_and_tildes() {
+        let document = json!({"a/b": 1, "m~n": 2, "~1": 3});
+        assert_eq!(
+            patched(
+                document.clone(),
+                json!([
+                    {"op": "replace", "path": "/a~1b", "value": 10},
+                    {"op": "remove", "path": "/m~0n"},
+                    {"op": "test", "path": "/~01", "value": 3},
+                ])
+            ),
+            json!({"a/b": 10, "~1": 3})
+        );
+        assert_eq!(
+            generate(&document, &json!({"a/b": 1, "m~n": 2, "~1": 3, "x/~y": 4})),
+            json!([{"op": "add", "path": "/x~1~0y", "value": 4}])
+        );
+    }
+
+    #[test]
+    fn a_failing_operation_applies_nothing() {
+        let document = json!({"a": 1});
+        let patch = json!([
+            {"op": "add", "path": "/b", "value": 2},
+            {"op": "remove", "path": "/missing"},
+        ]);
+        assert_eq!(apply(&document, &patch).unwrap_err(), "operation 2: /missing does not exist");
+        assert!(apply(&document, &json!([{"op": "frobnicate", "path": "/a"}])).is_err());
+        assert!(apply(&document, &json!({"op": "add"})).is_err());
+    }
+
+    #[test]
+    fn generated_patches_turn_the_old_document_into_the_new() {
+        let pairs = [
+            (json!({"a": 1, "b": [1, 2, 3], "c": {"d": true}}), json!({"a": 2, "b": [1, 5], "e": null})),
+            (json!([1, 2]), json!([1, 2, [3], {"x": 4}])),
+            (json!([{"id": 1}, {"id": 2}, 3, 4]), json!([{"id": 2}])),
+            (json!({"a": [1]}), json!({"a": {"0": 1}})),
+            (json!("scalar"), json!({"now": "object"})),
+            (json!({"same": [1, {"x": 1}]}), json!({"same": [1, {"x": 1}]})),
+        ];
+        for (old, new) in pairs {
+            let patch = generate(&old, &new);
+            assert_eq!(apply(&old, &patch).unwrap(), new, "{} -> {} via {}", old, new, patch);
+        }
+        assert_eq!(generate(&json!({"a": [1]}), &json!({"a": [1]})), json!([]));
+    }
+}
@@ -0,0 +1,805 @@
+//! JSON Schema validation of the document.
+//!
+//! Covers the keywords of draft 4 through 2020-12 that API payloads are usually checked
+//! with: `type`, `enum`, `const`, the object, array, string and number bounds (including
+//! draft 4's boolean `exclusiveMinimum`/`exclusiveMaximum`), `pattern`, `format`,
+//! `contains`, `propertyNames`, `dependentRequired`/`dependentSchemas`/`dependencies`,
+//! `if`/`then`/`else`, the `allOf`/`anyOf`/`oneOf`/`not` combinators and local `$ref`s
+//! (`#/...`). Annotations such as `title` or `description` are accepted and not checked.
+//!
+//! A schema is rejected when it's loaded, before any payload is checked, if a keyword this
+//! validator implements has a value of the wrong kind or a `$ref` doesn't resolve. Unknown
+//! keywords are ignored, as the specification requires, and so are `format`s not listed
+//! in [`FORMATS`] and `pattern`s the `regex` crate can't compile (ECMA-262 lookarounds and
+//! backreferences). Their locations are kept in [`Schema::unchecked`], so the caller can say
+//! which parts of the schema weren't checked rather than passing over them silently.
+
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Schemas applied to the same value in a row (through `$ref` or a combinator) before the
+/// schema is taken to loop, as with `{"$ref": "#"}`.
+const MAX_SAME_VALUE_DEPTH: usize = 64;
+
+const TYPES: &[&str] = &["null", "boolean", "object", "array", "number", "string", "integer"];
+
+/// Keywords that only describe or identify a schema and have nothing to check.
+const ANNOTATIONS: &[&str] = &[
+    "$schema", "$id", "id", "$anchor", "$comment", "$vocabulary", "title", "description", "default", "examples",
+    "deprecated", "readOnly", "writeOnly", "contentEncoding", "contentMediaType", "contentSchema",
+];
+
+/// Keywords checked by [`Schema::validate`], besides the annotations.
+const KEYWORDS: &[&str] = &[
+    "type", "enum", "const", "$ref", "$defs", "definitions", "required", "minProperties", "maxProperties",
+    "properties", "patternProperties", "additionalProperties", "propertyNames", "dependentRequired",
+    "dependentSchemas", "dependencies", "minItems", "maxItems", "uniqueItems", "prefixItems", "items",
+    "additionalItems", "contains", "minContains", "maxContains", "minLength", "maxLength", "pattern", "format",
+    "minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum", "multipleOf", "allOf", "anyOf", "oneOf", "not",
+    "if", "then", "else",
+];
+
+/// Values of `format` that are checked.
+const FORMATS: &[&str] = &["date-time", "date", "time", "email", "hostname", "ipv4", "ipv6", "uri", "uuid", "regex"];
+
+pub struct Schema {
+    root: Value,
+    /// Compiled `pattern` and `patternProperties` regexes by source.
+    patterns: HashMap<String, Regex>,
+    /// Schema locations of keywords, formats and patterns that are ignored.
+    unchecked: Vec<String>,
+}
+
+/// What walking a schema collects besides its problems.
+#[derive(Default)]
+struct Compiled {
+    patterns: HashMap<String, Regex>,
+    unchecked: Vec<String>,
+}
+
+/// One way the document fails the schema.
+pub struct Violation {
+    /// JSON Pointer to the failing value, empty for the document itself.
+    pub instance_path: String,
+    pub message: String,
+}
+
+impl Schema {
+    /// Checks `root` is a usable schema, describing the first problem otherwise.
+    pub fn compile(root: Value) -> Result<Self, String> {
+        let mut compiled = Compiled::default();
+        check(&root, &root, "#", &mut compiled)?;
+        Ok(Self {
+            root,
+            patterns: compiled.patterns,
+            unchecked: compiled.unchecked,
+        })
+    }
+
+    /// Locations in the schema, like `#/properties/id/unevaluatedProperties`, of what the
+    /// schema asks for but isn't checked.
+    pub fn unchecked(&self) -> &[String] {
+        &self.unchecked
+    }
+
+    /// Every violation in `instance`, in document order.
+    pub fn validate(&self, instance: &Value) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        self.validate_at(&self.root, instance, "", &mut violations);
+        violations
+    }
+
+    fn validate_at(&self, schema: &Value, instance: &Value, path: &str, out: &mut Vec<Violation>) {
+        self.validate_nested(schema, instance, path, 0, out);
+    }
+
+    /// `depth` counts schemas already applied to this same value.
+    fn validate_nested(&self, schema: &Value, instance: &Value, path: &str, depth: usize, out: &mut Vec<Violation>) {
+        if depth > MAX_SAME_VALUE_DEPTH {
+            out.push(violation(path, "schema references loop without reaching a check".to_string()));
+            return;
+        }
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                out.push(violation(path, "no value is allowed here".to_string()));
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            if let Some(target) = resolve(&self.root, reference) {
+                self.validate_nested(target, instance, path, depth + 1, out);
+            }
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let names: Vec<&str> = match expected {
+                Value::String(name) => vec![name],
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !names.iter().any(|name| has_type(instance, name)) {
+                out.push(violation(
+                    path,
+                    format!("expected {}, found {}", names.join(" or "), type_name(instance)),
+                ));
+            }
+        }
+        if let Some(Value::Array(allowed)) = schema.get("enum") {
+            if !allowed.contains(instance) {
+                out.push(violation(path, format!("{} is not one of {}", instance, Value::Array(allowed.clone()))));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if instance != expected {
+                out.push(violation(path, format!("expected {}, found {}", expected, instance)));
+            }
+        }
+
+        match instance {
+            Value::Object(object) => self.validate_object(schema, object, path, out),
+            Value::Array(items) => self.validate_array(schema, items, path, out),
+            Value::String(text) => self.validate_string(schema, text, path, out),
+            Value::Number(number) => {
+                if let Some(number) = number.as_f64() {
+                    validate_number(schema, number, path, out);
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(Value::Array(schemas)) = schema.get("allOf") {
+            for subschema in schemas {
+                self.validate_nested(subschema, instance, path, depth + 1, out);
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+            if !schemas.iter().any(|subschema| self.is_valid(subschema, instance, depth)) {
+                out.push(violation(path, "doesn't match any schema in anyOf".to_string()));
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+            let matching = schemas.iter().filter(|subschema| self.is_valid(subschema, instance, depth)).count();
+            if matching != 1 {
+                out.push(violation(path, format!("matches {} schemas in oneOf, expected exactly 1", matching)));
+            }
+        }
+        if let Some(subschema) = schema.get("not") {
+            if self.is_valid(subschema, instance, depth) {
+                out.push(violation(path, "matches the schema in not".to_string()));
+            }
+        }
+        if let Some(condition) = schema.get("if") {
+            let branch = if self.is_valid(condition, instance, depth) { "then" } else { "else" };
+            if let Some(subschema) = schema.get(branch) {
+                self.validate_nested(subschema, instance, path, depth + 1, out);
+            }
+        }
+    }
+
+    fn is_valid(&self, schema: &Value, instance: &Value, depth: usize) -> bool {
+        let mut violations = Vec::new();
+        self.validate_nested(schema, instance, "", depth + 1, &mut violations);
+        violations.is_empty()
+    }
+
+    fn validate_object(&self, schema: &Map<String, Value>, object: &Map<String, Value>, path: &str, out: &mut Vec<Violation>) {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    out.push(violation(path, format!("missing required property '{}'", key)));
+                }
+            }
+        }
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64) {
+            if (object.len() as u64) < min {
+                out.push(violation(path, format!("has {} properties, expected at least {}", object.len(), min)));
+            }
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64) {
+            if object.len() as u64 > max {
+                out.push(violation(path, format!("has {} properties, expected at most {}", object.len(), max)));
+            }
+        }
+
+        if let Some(names) = schema.get("propertyNames") {
+            for key in object.keys() {
+                if !self.is_valid(names, &Value::String(key.clone()), 0) {
+                    out.push(violation(path, format!("property name '{}' doesn't match propertyNames", key)));
+                }
+            }
+        }
+        // `dependencies` is the draft 4-7 spelling of both, told apart by the value's kind
+        let dependencies = ["dependentRequired", "dependentSchemas", "dependencies"]
+            .into_iter()
+            .filter_map(|keyword| schema.get(keyword).and_then(Value::as_object))
+            .flatten();
+        for (key, dependency) in dependencies.filter(|(key, _)| object.contains_key(key.as_str())) {
+            match dependency {
+                Value::Array(required) => {
+                    let names = required.iter().filter_map(Value::as_str);
+                    for missing in names.filter(|name| !object.contains_key(*name)) {
+                        out.push(violation(path, format!("property '{}' requires property '{}'", key, missing)));
+                    }
+                }
+                subschema => self.validate_at(subschema, &Value::Object(object.clone()), path, out),
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let pattern_properties = schema.get("patternProperties").and_then(Value::as_object);
+        for (key, value) in object {
+            let child_path = format!("{}/{}", path, escape_pointer(key));
+            let mut matched = false;
+            if let Some(subschema) = properties.and_then(|properties| properties.get(key)) {
+                matched = true;
+                self.validate_at(subschema, value, &child_path, out);
+            }
+            for (pattern, subschema) in pattern_properties.into_iter().flatten() {
+                match self.patterns.get(pattern) {
+                    Some(regex) if regex.is_match(key) => {
+                        matched = true;
+                        self.validate_at(subschema, value, &child_path, out);
+                    }
+                    Some(_) => {}
+                    // An unchecked pattern might have matched, so additionalProperties can't apply
+                    None => matched = true,
+                }
+            }
+            if matched {
+                continue;
+            }
+            match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    out.push(violation(path, format!("property '{}' is not allowed", key)));
+                }
+                Some(subschema) => self.validate_at(subschema, value, &child_path, out),
+                None => {}
+            }
+        }
+    }
+
+    fn validate_array(&self, schema: &Map<String, Value>, items: &[Value], path: &str, out: &mut Vec<Violation>) {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < min {
+                out.push(violation(path, format!("has {} items, expected at least {}", items.len(), min)));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if items.len() as u64 > max {
+                out.push(violation(path, format!("has {} items, expected at most {}", items.len(), max)));
+            }
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            for (i, item) in items.iter().enumerate() {
+                if let Some(first) = items[..i].iter().position(|earlier| earlier == item) {
+                    out.push(violation(path, format!("items {} and {} are equal", first, i)));
+                    break;
+                }
+            }
+        }
+
+        if let Some(subschema) = schema.get("contains") {
+            let matching = items.iter().filter(|item| self.is_valid(subschema, item, 0)).count() as u64;
+            let min = schema.get("minContains").and_then(Value::as_u64).unwrap_or(1);
+            if matching < min {
+                out.push(violation(path, format!("{} items match contains, expected at least {}", matching, min)));
+            }
+            if let Some(max) = schema.get("maxContains").and_then(Value::as_u64).filter(|&max| matching > max) {
+                out.push(violation(path, format!("{} items match contains, expected at most {}", matching, max)));
+            }
+        }
+
+        // Draft 2020-12 spells tuples `prefixItems` + `items`; older drafts use an `items`
+        // array + `additionalItems`
+        let (prefix, rest) = match (schema.get("prefixItems"), schema.get("items")) {
+            (Some(Value::Array(prefix)), rest) => (prefix.as_slice(), rest),
+            (_, Some(Value::Array(prefix))) => (prefix.as_slice(), schema.get("additionalItems")),
+            (_, rest) => (&[][..], rest),
+        };
+        for (i, item) in items.iter().enumerate() {
+            let subschema = prefix.get(i).or(rest);
+            if let Some(subschema) = subschema {
+                self.validate_at(subschema, item, &format!("{}/{}", path, i), out);
+            }
+        }
+    }
+
+    fn validate_string(&self, schema: &Map<String, Value>, text: &str, path: &str, out: &mut Vec<Violation>) {
+        let length = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if length < min {
+                out.push(violation(path, format!("is {} characters, expected at least {}", length, min)));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if length > max {
+                out.push(violation(path, format!("is {} characters, expected at most {}", length, max)));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            if self.patterns.get(pattern).is_some_and(|regex| !regex.is_match(text)) {
+                out.push(violation(path, format!("doesn't match pattern '{}'", pattern)));
+            }
+        }
+        if let Some(format) = schema.get("format").and_then(Value::as_str) {
+            if !format::matches(format, text) {
+                out.push(violation(path, format!("is not a valid {}", format)));
+            }
+        }
+    }
+}
+
+fn validate_number(schema: &Map<String, Value>, number: f64, path: &str, out: &mut Vec<Violation>) {
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+    // Draft 4 makes `minimum`/`maximum` exclusive with a boolean flag instead of a number
+    let exclusive = |keyword: &str| schema.get(keyword) == Some(&Value::Bool(true));
+    if exclusive("exclusiveMinimum") {
+        if let Some(min) = bound("minimum").filter(|&min| number <= min) {
+            out.push(violation(path, format!("{} is not greater than {}", number, min)));
+        }
+    } else if let Some(min) = bound("minimum").filter(|&min| number < min) {
+        out.push(violation(path, format!("{} is less than the minimum {}", number, min)));
+    }
+    if exclusive("exclusiveMaximum") {
+        if let Some(max) = bound("maximum").filter(|&max| number >= max) {
+            out.push(violation(path, format!("{} is not less than {}", number, max)));
+        }
+    } else if let Some(max) = bound("maximum").filter(|&max| number > max) {
+        out.push(violation(path, format!("{} is greater than the maximum {}", number, max)));
+    }
+    if let Some(min) = bound("exclusiveMinimum").filter(|&min| number <= min) {
+        out.push(violation(path, format!("{} is not greater than {}", number, min)));
+    }
+    if let Some(max) = bound("exclusiveMaximum").filter(|&max| number >= max) {
+        out.push(violation(path, format!("{} is not less than {}", number, max)));
+    }
+    if let Some(divisor) = bound("multipleOf") {
+        let quotient = number / divisor;
+        if (quotient - quotient.round()).abs() > 1e-9 {
+            out.push(violation(path, format!("{} is not a multiple of {}", number, divisor)));
+        }
+    }
+}
+
+fn violation(path: &str, message: String) -> Violation {
+    Violation {
+        instance_path: path.to_string(),
+        message,
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Escapes a key as a JSON Pointer reference token (RFC 6901).
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Follows a local `$ref` like `#/$defs/address` from the schema root.
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    if pointer.is_empty() {
+        return Some(root);
+    }
+    root.pointer(pointer)
+}
+
+/// Walks a schema checking its keywords, compiling every regex on the way.
+fn check(schema: &Value, root: &Value, at: &str, compiled: &mut Compiled) -> Result<(), String> {
+    let schema = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(schema) => schema,
+        other => return Err(format!("{}: a schema must be an object or a boolean, found {}", at, type_name(other))),
+    };
+    let invalid = |keyword: &str, expected: &str| Err(format!("{}/{}: expected {}", at, keyword, expected));
+
+    for (keyword, value) in schema {
+        let here = format!("{}/{}", at, keyword);
+        let known = KEYWORDS.contains(&keyword.as_str()) || ANNOTATIONS.contains(&keyword.as_str());
+        // `x-` keywords are OpenAPI extensions, which never constrain the payload
+        if !known && !keyword.starts_with("x-") {
+            compiled.unchecked.push(here);
+            continue;
+        }
+        match keyword.as_str() {
+            "type" => {
+                let names: Vec<&Value> = match value {
+                    Value::Array(names) => names.iter().collect(),
+                    single => vec![single],
+                };
+                for name in names {
+                    if !name.as_str().is_some_and(|name| TYPES.contains(&name)) {
+                        return Err(format!("{}: unknown type {}", here, name));
+                    }
+                }
+            }
+            "$ref" => {
+                let Some(reference) = value.as_str() else {
+                    return invalid(keyword, "a string");
+                };
+                if !reference.starts_with('#') {
+                    return Err(format!("{}: only local references (#/...) are supported, found '{}'", here, reference));
+                }
+                if resolve(root, reference).is_none() {
+                    return Err(format!("{}: '{}' doesn't resolve", here, reference));
+                }
+            }
+            "pattern" => {
+                let Some(pattern) = value.as_str() else {
+                    return invalid(keyword, "a string");
+                };
+                compile_pattern(pattern, &here, compiled);
+            }
+            "format" => {
+                let Some(format) = value.as_str() else {
+                    return invalid(keyword, "a string");
+                };
+                if !FORMATS.contains(&format) {
+                    compiled.unchecked.push(format!("{} ({})", here, format));
+                }
+            }
+            "enum" | "required" | "allOf" | "anyOf" | "oneOf" | "prefixItems" if !value.is_array() => {
+                return invalid(keyword, "an array");
+            }
+            "minLength" | "maxLength" | "minItems" | "maxItems" | "minProperties" | "maxProperties" | "minContains"
+            | "maxContains"
+                if !value.is_u64() =>
+            {
+                return invalid(keyword, "a non-negative integer");
+            }
+            // Draft 4 spells the exclusive bounds as booleans next to `minimum`/`maximum`
+            "exclusiveMinimum" | "exclusiveMaximum" if !(value.is_number() || value.is_boolean()) => {
+                return invalid(keyword, "a number or a boolean");
+            }
+            "minimum" | "maximum" | "multipleOf" if !value.is_number() => {
+                return invalid(keyword, "a number");
+            }
+            "dependentRequired" => {
+                let names = |names: &Value| names.as_array().is_some_and(|names| names.iter().all(Value::is_string));
+                let all_names = value.as_object().is_some_and(|dependencies| dependencies.values().all(names));
+                if !all_names {
+                    return invalid(keyword, "an object of property name arrays");
+                }
+            }
+            "multipleOf" if value.as_f64().is_some_and(|divisor| divisor <= 0.0) => {
+                return invalid(keyword, "a number greater than 0");
+            }
+            _ => {}
+        }
+
+        match keyword.as_str() {
+            "properties" | "patternProperties" | "$defs" | "definitions" | "dependentSchemas" | "dependencies" => {
+                let Some(subschemas) = value.as_object() else {
+                    return invalid(keyword, "an object");
+                };
+                for (name, subschema) in subschemas {
+                    // A `dependencies` entry can also be a list of required names
+                    if keyword == "dependencies" && subschema.is_array() {
+                        if !subschema.as_array().into_iter().flatten().all(Value::is_string) {
+                            return invalid(keyword, "property name arrays or schemas");
+                        }
+                        continue;
+                    }
+                    if keyword == "patternProperties" {
+                        compile_pattern(name, &here, compiled);
+                    }
+                    check(subschema, root, &format!("{}/{}", here, escape_pointer(name)), compiled)?;
+                }
+            }
+            "allOf" | "anyOf" | "oneOf" | "prefixItems" => {
+                for (i, subschema) in value.as_array().into_iter().flatten().enumerate() {
+                    check(subschema, root, &format!("{}/{}", here, i), compiled)?;
+                }
+            }
+            "items" if value.is_array() => {
+                for (i, subschema) in value.as_array().into_iter().flatten().enumerate() {
+                    check(subschema, root, &format!("{}/{}", here, i), compiled)?;
+                }
+            }
+            "items" | "additionalItems" | "additionalProperties" | "not" | "contains" | "propertyNames" | "if"
+            | "then" | "else" => check(value, root, &here, compiled)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Compiles `pattern` for later matching. Schemas write ECMA-262 regexes, and the few
+/// features of those the `regex` crate lacks leave the pattern unchecked.
+fn compile_pattern(pattern: &str, at: &str, compiled: &mut Compiled) {
+    if compiled.patterns.contains_key(pattern) {
+        return;
+    }
+    match Regex::new(pattern) {
+        Ok(regex) => {
+            compiled.patterns.insert(pattern.to_string(), regex);
+        }
+        Err(e) => {
+            tracing::debug!("pattern '{}' at {} not compiled: {}", pattern, at, e);
+            compiled.unchecked.push(format!("{} ({})", at, pattern));
+        }
+    }
+}
+
+/// Checks for the `format` names in [`FORMATS`], following the RFCs they refer to closely
+/// enough for API payloads.
+mod format {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    pub fn matches(format: &str, text: &str) -> bool {
+        match format {
+            "date-time" => text.split_once(['T', 't']).is_some_and(|(day, time)| date(day) && self::time(time)),
+            "date" => date(text),
+            "time" => time(text),
+            "email" => text.rsplit_once('@').is_some_and(|(local, domain)| {
+                !local.is_empty() && !local.contains(char::is_whitespace) && hostname(domain)
+            }),
+            "hostname" => hostname(text),
+            "ipv4" => text.parse::<Ipv4Addr>().is_ok(),
+            "ipv6" => text.parse::<Ipv6Addr>().is_ok(),
+            "uri" => uri(text),
+            "uuid" => uuid(text),
+            "regex" => regex::Regex::new(text).is_ok(),
+            _ => true,
+        }
+    }
+
+    /// `text` is `len` ASCII digits, read as a number.
+    fn digits(text: &str, len: usize) -> Option<u32> {
+        (text.len() == len && text.bytes().all(|byte| byte.is_ascii_digit())).then(|| text.parse().ok())?
+    }
+
+    /// `YYYY-MM-DD` naming a real day (RFC 3339 full-date).
+    fn date(text: &str) -> bool {
+        let mut parts = text.split('-');
+        let (Some(year), Some(month), Some(day), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return false;
+        };
+        let (Some(year), Some(month), Some(day)) = (digits(year, 4), digits(month, 2), digits(day, 2)) else {
+            return false;
+        };
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return false,
+        };
+        (1..=days).contains(&day)
+    }
+
+    /// `HH:MM:SS[.fraction]` followed by `Z` or a `+HH:MM`/`-HH:MM` offset (RFC 3339 full-time).
+    fn time(text: &str) -> bool {
+        let (clock, offset) = match text.find(['Z', 'z', '+', '-']) {
+            Some(at) => text.split_at(at),
+            None => return false,
+        };
+        let hours = |text: &str| digits(text, 2).is_some_and(|h| h < 24);
+        let minutes = |text: &str| digits(text, 2).is_some_and(|m| m < 60);
+        let offset_ok = match offset {
+            "Z" | "z" => true,
+            _ => offset[1..].split_once(':').is_some_and(|(h, m)| hours(h) && minutes(m)),
+        };
+        let (clock, fraction) = clock.split_once('.').unwrap_or((clock, "0"));
+        let mut parts = clock.split(':');
+        let (Some(h), Some(m), Some(seconds), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return false;
+        };
+        offset_ok
+            && !fraction.is_empty()
+            && fraction.bytes().all(|byte| byte.is_ascii_digit())
+            && hours(h)
+            && minutes(m)
+            // 60 allows for a leap second
+            && digits(seconds, 2).is_some_and(|s| s <= 60)
+    }
+
+    /// Dot-separated labels of letters, digits and inner hyphens (RFC 1123).
+    fn hostname(text: &str) -> bool {
+        let text = text.strip_suffix('.').unwrap_or(text);
+        !text.is_empty()
+            && text.len() <= 253
+            && text.split('.').all(|label| {
+                (1..=63).contains(&label.len())
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+            })
+    }
+
+    /// An absolute URI: a scheme, a colon and no whitespace (RFC 3986).
+    fn uri(text: &str) -> bool {
+        text.split_once(':').is_some_and(|(scheme, _)| {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme.bytes().all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'-' | b'.'))
+        }) && !text.contains(|c: char| c.is_whitespace() || c.is_control())
+    }
+
+    /// `8-4-4-4-12` hex digits (RFC 4122).
+    fn uuid(text: &str) -> bool {
+        let groups: Vec<&str> = text.split('-').collect();
+        groups.len() == 5
+            && groups
+                .iter()
+                .zip([8, 4, 4, 4, 12])
+                .all(|(group, len)| group.len() == len && group.bytes().all(|byte| byte.is_ascii_hexdigit()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Violations of `instance` against `schema`, as `path: message`.
+    fn errors(schema: Value, instance: Value) -> Vec<String> {
+        let schema = Schema::compile(schema).expect("schema compiles");
+        schema.validate(&instance).into_iter().map(|v| format!("{}: {}", v.instance_path, v.message)).collect()
+    }
+
+    fn valid(schema: Value, instance: Value) -> bool {
+        errors(schema, instance).is_empty()
+    }
+
+    #[test]
+    fn basic_keywords() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {"id": {"type": "integer"}, "kind": {"enum": ["a", "b"]}},
+        });
+        assert!(valid(schema.clone(), json!({"id": 1, "kind": "a"})));
+        assert_eq!(errors(schema.clone(), json!({"id": "1"})).len(), 1);
+        assert_eq!(errors(schema.clone(), json!({"kind": "c"})).len(), 2);
+        assert!(!valid(schema, json!([])));
+    }
+
+    #[test]
+    fn if_then_else_picks_a_branch() {
+        let schema = json!({
+            "if": {"properties": {"kind": {"const": "card"}}},
+            "then": {"required": ["number"]},
+            "else": {"required": ["iban"]},
+        });
+        assert!(valid(schema.clone(), json!({"kind": "card", "number": "4111"})));
+        assert!(!valid(schema.clone(), json!({"kind": "card", "iban": "DE00"})));
+        assert!(valid(schema.clone(), json!({"kind": "bank", "iban": "DE00"})));
+        assert!(!valid(schema, json!({"kind": "bank"})));
+    }
+
+    #[test]
+    fn contains_counts_matching_items() {
+        let schema = json!({"contains": {"type": "string"}});
+        assert!(valid(schema.clone(), json!([1, "a"])));
+        assert!(!valid(schema, json!([1, 2])));
+
+        let bounded = json!({"contains": {"type": "string"}, "minContains": 2, "maxContains": 3});
+        assert!(!valid(bounded.clone(), json!(["a", 1])));
+        assert!(valid(bounded.clone(), json!(["a", "b", 1])));
+        assert!(!valid(bounded, json!(["a", "b", "c", "d"])));
+    }
+
+    #[test]
+    fn property_names_are_checked() {
+        let schema = json!({"propertyNames": {"pattern": "^[a-z]+$"}});
+        assert!(valid(schema.clone(), json!({"abc": 1})));
+        assert_eq!(errors(schema, json!({"abc": 1, "Abc": 2})), [": property name 'Abc' doesn't match propertyNames"]);
+    }
+
+    #[test]
+    fn dependencies_in_every_spelling() {
+        for schema in [
+            json!({"dependentRequired": {"card": ["expiry"]}}),
+            json!({"dependencies": {"card": ["expiry"]}}),
+            json!({"dependentSchemas": {"card": {"required": ["expiry"]}}}),
+            json!({"dependencies": {"card": {"required": ["expiry"]}}}),
+        ] {
+            assert!(valid(schema.clone(), json!({"other": 1})), "{}", schema);
+            assert!(valid(schema.clone(), json!({"card": 1, "expiry": 2})), "{}", schema);
+            assert!(!valid(schema.clone(), json!({"card": 1})), "{}", schema);
+        }
+    }
+
+    #[test]
+    fn formats_are_checked() {
+        let cases = [
+            ("date-time", "2024-02-29T12:30:00.5+01:00", "2023-02-29T12:30:00Z"),
+            ("date", "2000-02-29", "1900-02-29"),
+            ("time", "23:59:60Z", "24:00:00Z"),
+            ("email", "someone@example.com", "someone@"),
+            ("hostname", "api.example.com", "-bad.example.com"),
+            ("ipv4", "192.168.0.1", "256.0.0.1"),
+            ("ipv6", "::1", "1::2::3"),
+            ("uri", "https://example.com/a?b", "example.com"),
+            ("uuid", "123e4567-e89b-12d3-a456-426614174000", "123e4567e89b12d3a456426614174000"),
+            ("regex", "^a+$", "(unclosed"),
+        ];
+        for (format, good, bad) in cases {
+            assert!(valid(json!({"format": format}), json!(good)), "{} {}", format, good);
+            assert!(!valid(json!({"format": format}), json!(bad)), "{} {}", format, bad);
+        }
+        // Non-strings aren't subject to formats
+        assert!(valid(json!({"format": "date"}), json!(1)));
+    }
+
+    #[test]
+    fn draft_4_boolean_exclusive_bounds() {
+        let schema = json!({"minimum": 0, "exclusiveMinimum": true, "maximum": 10, "exclusiveMaximum": true});
+        assert!(valid(schema.clone(), json!(5)));
+        assert!(!valid(schema.clone(), json!(0)));
+        assert!(!valid(schema, json!(10)));
+
+        let inclusive = json!({"minimum": 0, "exclusiveMinimum": false});
+        assert!(valid(inclusive, json!(0)));
+        assert!(!valid(json!({"exclusiveMinimum": 0}), json!(0)));
+    }
+
+    #[test]
+    fn unknown_keywords_are_ignored_and_listed() {
+        let schema = json!({
+            "type": "object",
+            "nullable": true,
+            "unevaluatedProperties": false,
+            "properties": {"a": {"$dynamicRef": "#meta", "format": "iri-reference"}},
+        });
+        let compiled = Schema::compile(schema.clone()).expect("unknown keywords don't stop the schema loading");
+        assert_eq!(
+            compiled.unchecked(),
+            ["#/nullable", "#/unevaluatedProperties", "#/properties/a/$dynamicRef", "#/properties/a/format (iri-reference)"]
+        );
+        assert!(valid(schema, json!({"a": "x", "b": 1})));
+
+        let annotated = json!({"$schema": "http://json-schema.org/draft-04/schema#", "title": "t", "x-order": 1});
+        assert!(Schema::compile(annotated).unwrap().unchecked().is_empty());
+    }
+
+    #[test]
+    fn ecma_only_patterns_are_listed_rather_than_rejected() {
+        let schema = json!({
+            "properties": {"password": {"pattern": "^(?=.*\\d).{8,}$"}},
+            "patternProperties": {"^(?!x-)": {"type": "string"}},
+            "additionalProperties": false,
+        });
+        let compiled = Schema::compile(schema.clone()).expect("lookarounds don't stop the schema loading");
+        assert_eq!(compiled.unchecked().len(), 2);
+        // Neither the pattern nor additionalProperties can be judged for these keys
+        assert!(valid(schema, json!({"password": "short", "other": 1})));
+    }
+
+    #[test]
+    fn malformed_keywords_are_schema_errors() {
+        for schema in [
+            json!({"minContains": -1}),
+            json!({"type": "text"}),
+            json!({"$ref": "#/$defs/missing"}),
+            json!({"required": "id"}),
+            json!({"properties": {"a": 1}}),
+        ] {
+            assert!(Schema::compile(schema.clone()).is_err(), "{}", schema);
+        }
+    }
+}
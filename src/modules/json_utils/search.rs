@@ -0,0 +1,174 @@
+//! Substring search over the raw view's displayed text, and over the tree's nodes.
+//!
+//! Like vim's `smartcase`, a query without uppercase letters matches regardless of case and
+//! one with uppercase letters matches exactly. Case folding is ASCII-only so match offsets
+//! stay valid byte positions in the original text. Matches never span lines.
+
+use super::highlight;
+use ratatui::prelude::*;
+use std::ops::Range;
+use std::collections::HashSet;
+
+pub struct TextSearch {
+    pub query: String,
+    /// `(line, start byte, end byte)` of every match, in document order.
+    matches: Vec<(usize, usize, usize)>,
+    current: usize,
+}
+
+impl TextSearch {
+    pub fn new(text: &str, query: &str) -> Self {
+        let ignore_case = ignores_case(query);
+        let needle = if ignore_case { query.to_ascii_lowercase() } else { query.to_string() };
+        let mut matches = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let haystack = if ignore_case { line.to_ascii_lowercase() } else { line.to_string() };
+            matches.extend(haystack.match_indices(&needle).map(|(start, _)| (i, start, start + needle.len())));
+        }
+        Self {
+            query: query.to_string(),
+            matches,
+            current: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    /// Line of the current match.
+    pub fn current_line(&self) -> Option<usize> {
+        self.matches.get(self.current).map(|&(line, _, _)| line)
+    }
+
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = self.current.checked_sub(1).unwrap_or(self.matches.len() - 1);
+        }
+    }
+
+    /// `match X of Y`, or `no matches`.
+    pub fn label(&self) -> String {
+        if self.matches.is_empty() {
+            format!("no matches for '{}'", self.query)
+        } else {
+            format!("match {} of {} for '{}'", self.current + 1, self.matches.len(), self.query)
+        }
+    }
+
+    /// Splits the text into syntax-colored lines with every match highlighted and the current
+    /// one reversed. Only lines in `visible` are colored, as with [`highlight::lines`].
+    pub fn highlight<'a>(&self, text: &'a str, visible: Range<usize>) -> Text<'a> {
+        let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+        let current_style = match_style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+        let mut pending = self.matches.iter().enumerate().peekable();
+
+        let lines: Vec<Line> = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut marks = Vec::new();
+                while let Some((index, &(_, start, end))) = pending.next_if(|(_, m)| m.0 == i) {
+                    let style = if index == self.current { current_style } else { match_style };
+                    marks.push((start..end, style));
+                }
+                if visible.contains(&i) {
+                    highlight::line_with_marks(line, &marks)
+                } else {
+                    Line::from(line)
+                }
+            })
+            .collect();
+        Text::from(lines)
+    }
+}
+
+fn ignores_case(query: &str) -> bool {
+    !query.chars().any(|c| c.is_uppercase())
+}
+
+/// Tree nodes whose key or scalar value contains the query, by index into the tree.
+pub struct TreeSearch {
+    pub query: String,
+    ignore_case: bool,
+    needle: String,
+    /// Matching node paths with their position in document order, in that order.
+    matches: Vec<(usize, String)>,
+    paths: HashSet<String>,
+    current: usize,
+}
+
+impl TreeSearch {
+    pub fn new(query: &str) -> Self {
+        let ignore_case = ignores_case(query);
+        Self {
+            query: query.to_string(),
+            ignore_case,
+            needle: if ignore_case { query.to_ascii_lowercase() } else { query.to_string() },
+            matches: Vec::new(),
+            paths: HashSet::new(),
+            current: 0,
+        }
+    }
+
+    /// Records the node at `path` if its key or scalar value text matches. Nodes are given
+    /// in document order, `ordinal` counting from the root.
+    pub fn check(&mut self, ordinal: usize, path: &str, key: &str, value: Option<&str>) {
+        let contains = |text: &str| {
+            if self.ignore_case {
+                text.to_ascii_lowercase().contains(&self.needle)
+            } else {
+                text.contains(&self.needle)
+            }
+        };
+        if contains(key) || value.is_some_and(contains) {
+            self.matches.push((ordinal, path.to_string()));
+            self.paths.insert(path.to_string());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.paths.contains(path)
+    }
+
+    /// Path of the current match.
+    pub fn current(&self) -> Option<&str> {
+        self.matches.get(self.current).map(|(_, path)| path.as_str())
+    }
+
+    /// Makes the first match at or after `ordinal` current, wrapping to the first match.
+    pub fn start_from(&mut self, ordinal: usize) {
+        self.current = self.matches.iter().position(|&(m, _)| m >= ordinal).unwrap_or(0);
+    }
+
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = self.current.checked_sub(1).unwrap_or(self.matches.len() - 1);
+        }
+    }
+
+    /// `match X of Y`, or `no matches`.
+    pub fn label(&self) -> String {
+        if self.matches.is_empty() {
+            format!("no matches for '{}'", self.query)
+        } else {
+            format!("match {} of {} for '{}'", self.current + 1, self.matches.len(), self.query)
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! The document and view saved on exit when `json.save_session` is on, restored on the next
+//! launch of the JSON viewer.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    pub raw_input: String,
+    /// `"raw"` or `"tree"`; converted output isn't saved, so it restores as raw.
+    pub view_mode: String,
+    pub scroll_offset: usize,
+    pub expanded_paths: Vec<String>,
+    pub selected_path: Option<String>,
+}
+
+fn file_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("dev-tools").join("session.json"))
+}
+
+impl Session {
+    /// The saved session, or `None` when there is none or it can't be read.
+    pub fn load() -> Option<Self> {
+        let path = file_path()?;
+        let contents = fs::read_to_string(&path).ok()?;
+        serde_json::from_str(&contents)
+            .map_err(|e| tracing::warn!("discarding unreadable session {}: {}", path.display(), e))
+            .ok()
+    }
+
+    pub fn save(&self) {
+        let Some(path) = file_path() else {
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, serde_json::to_string(self).unwrap_or_default()));
+        if let Err(e) = result {
+            tracing::warn!("failed to save session to {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn clear() {
+        if let Some(path) = file_path() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
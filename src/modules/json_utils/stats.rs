@@ -0,0 +1,97 @@
+//! Shape summary of a document for the statistics overlay: how many values of each type it
+//! holds, how deep it nests, and where its biggest arrays and longest strings are.
+
+use serde_json::Value;
+
+use super::is_jq_identifier;
+
+/// Entries kept in each of the "largest" lists.
+const TOP: usize = 5;
+
+#[derive(Default)]
+pub struct Stats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    /// Levels of containers around the deepest value, with the path to it.
+    pub max_depth: usize,
+    pub deepest_path: String,
+    /// jq path and element count of the largest arrays, largest first.
+    pub largest_arrays: Vec<(String, usize)>,
+    /// jq path and length in characters of the longest strings, longest first.
+    pub longest_strings: Vec<(String, usize)>,
+}
+
+impl Stats {
+    pub fn collect(value: &Value) -> Self {
+        let mut stats = Stats::default();
+        stats.walk(value, &mut String::new(), 0);
+        stats.deepest_path = jq(&stats.deepest_path);
+        for (path, _) in stats.largest_arrays.iter_mut().chain(&mut stats.longest_strings) {
+            *path = jq(path);
+        }
+        stats
+    }
+
+    fn walk(&mut self, value: &Value, path: &mut String, depth: usize) {
+        if depth > self.max_depth {
+            self.max_depth = depth;
+            self.deepest_path = path.clone();
+        }
+        let len = path.len();
+        match value {
+            Value::Object(obj) => {
+                self.objects += 1;
+                for (key, child) in obj {
+                    if is_jq_identifier(key) {
+                        path.push('.');
+                        path.push_str(key);
+                    } else {
+                        path.push_str(&format!(".{}", serde_json::to_string(key).unwrap_or_default()));
+                    }
+                    self.walk(child, path, depth + 1);
+                    path.truncate(len);
+                }
+            }
+            Value::Array(items) => {
+                self.arrays += 1;
+                keep_top(&mut self.largest_arrays, path, items.len());
+                for (i, child) in items.iter().enumerate() {
+                    path.push_str(&format!("[{}]", i));
+                    self.walk(child, path, depth + 1);
+                    path.truncate(len);
+                }
+            }
+            Value::String(s) => {
+                self.strings += 1;
+                keep_top(&mut self.longest_strings, path, s.chars().count());
+            }
+            Value::Number(_) => self.numbers += 1,
+            Value::Bool(_) => self.booleans += 1,
+            Value::Null => self.nulls += 1,
+        }
+    }
+}
+
+/// Adds `(path, size)` to `top` if it's among the `TOP` largest, keeping `top` sorted with
+/// the largest first. Ties keep the one seen first.
+fn keep_top(top: &mut Vec<(String, usize)>, path: &str, size: usize) {
+    if size == 0 || (top.len() == TOP && top.last().is_some_and(|&(_, smallest)| smallest >= size)) {
+        return;
+    }
+    let at = top.partition_point(|&(_, other)| other >= size);
+    top.insert(at, (path.to_string(), size));
+    top.truncate(TOP);
+}
+
+/// jq paths start with `.`, including the root itself and paths into a top-level array.
+fn jq(path: &str) -> String {
+    if path.is_empty() || path.starts_with('[') {
+        format!(".{}", path)
+    } else {
+        path.to_string()
+    }
+}
@@ -0,0 +1,384 @@
+//! Structural rewrites of a parsed document.
+
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use super::canonical;
+use super::is_jq_identifier;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+fn type_rank(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    }
+}
+
+/// Total order over JSON values: by type first, then by value within a type.
+pub fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (Value::Number(x), Value::Number(y)) => {
+            let (x, y) = (x.as_f64().unwrap_or(0.0), y.as_f64().unwrap_or(0.0));
+            x.partial_cmp(&y).unwrap_or(Ordering::Equal)
+        }
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Array(x), Value::Array(y)) => x.len().cmp(&y.len()),
+        (Value::Object(x), Value::Object(y)) => x.len().cmp(&y.len()),
+        _ => type_rank(a).cmp(&type_rank(b)),
+    }
+}
+
+/// Sorts every array whose elements are all objects containing `key` by that field, at any
+/// depth. Returns how many arrays were reordered; other arrays are left as they were.
+pub fn sort_arrays_by_key(value: &mut Value, key: &str) -> usize {
+    let mut sorted = 0;
+    match value {
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                sorted += sort_arrays_by_key(item, key);
+            }
+            let sortable = !items.is_empty()
+                && items.iter().all(|item| item.as_object().is_some_and(|obj| obj.contains_key(key)));
+            if sortable {
+                items.sort_by(|a, b| compare_values(&a[key], &b[key]));
+                sorted += 1;
+            }
+        }
+        Value::Object(obj) => {
+            for child in obj.values_mut() {
+                sorted += sort_arrays_by_key(child, key);
+            }
+        }
+        _ => {}
+    }
+    sorted
+}
+
+/// Rebuilds every object with its keys in byte order, at any depth. Goes through a
+/// `BTreeMap` explicitly so the result doesn't depend on how `serde_json::Map` is backed.
+/// Returns how many objects had their keys out of order.
+pub fn sort_keys(value: &mut Value) -> usize {
+    match value {
+        Value::Array(items) => items.iter_mut().map(sort_keys).sum(),
+        Value::Object(obj) => {
+            let unsorted = obj.keys().zip(obj.keys().skip(1)).any(|(a, b)| a > b);
+            let mut sorted: BTreeMap<String, Value> = std::mem::take(obj).into_iter().collect();
+            let nested: usize = sorted.values_mut().map(sort_keys).sum();
+            obj.extend(sorted);
+            usize::from(unsorted) + nested
+        }
+        _ => 0,
+    }
+}
+
+/// Whether both documents have the same containers at the same paths, whatever their
+/// scalars hold.
+pub fn same_shape(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, value)| b.get(key).is_some_and(|other| same_shape(value, other)))
+        }
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_shape(a, b)),
+        (Value::Object(_) | Value::Array(_), _) | (_, Value::Object(_) | Value::Array(_)) => false,
+        _ => true,
+    }
+}
+
+/// Every value stored under `key` at any depth, paired with its jq path, in document order.
+/// Matches inside a matched value are collected too.
+pub fn values_at_key(value: &Value, key: &str) -> Vec<(String, Value)> {
+    fn walk(value: &Value, key: &str, path: &mut String, found: &mut Vec<(String, Value)>) {
+        let len = path.len();
+        match value {
+            Value::Object(obj) => {
+                for (k, child) in obj {
+                    push_key(path, k);
+                    if k == key {
+                        found.push((path.clone(), child.clone()));
+                    }
+                    walk(child, key, path, found);
+                    path.truncate(len);
+                }
+            }
+            Value::Array(items) => {
+                for (i, child) in items.iter().enumerate() {
+                    path.push_str(&format!("[{}]", i));
+                    walk(child, key, path, found);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut found = Vec::new();
+    walk(value, key, &mut String::new(), &mut found);
+    for (path, _) in &mut found {
+        finish_path(path);
+    }
+    found
+}
+
+/// Every scalar in the document with its jq path, in document order.
+pub fn leaf_values(value: &Value) -> Vec<(String, &Value)> {
+    leaves(value, false)
+}
+
+/// Like [`leaf_values`], but empty objects and arrays are listed too, so the pairs together
+/// describe the whole document.
+pub fn flatten(value: &Value) -> Vec<(String, &Value)> {
+    leaves(value, true)
+}
+
+fn leaves(value: &Value, keep_empty: bool) -> Vec<(String, &Value)> {
+    fn walk<'a>(value: &'a Value, keep_empty: bool, path: &mut String, found: &mut Vec<(String, &'a Value)>) {
+        let len = path.len();
+        match value {
+            Value::Object(obj) if !(keep_empty && obj.is_empty()) => {
+                for (k, child) in obj {
+                    push_key(path, k);
+                    walk(child, keep_empty, path, found);
+                    path.truncate(len);
+                }
+            }
+            Value::Array(items) if !(keep_empty && items.is_empty()) => {
+                for (i, child) in items.iter().enumerate() {
+                    path.push_str(&format!("[{}]", i));
+                    walk(child, keep_empty, path, found);
+                    path.truncate(len);
+                }
+            }
+            leaf => {
+                let mut finished = path.clone();
+                finish_path(&mut finished);
+                found.push((finished, leaf));
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    walk(value, keep_empty, &mut String::new(), &mut found);
+    found
+}
+
+/// The object or array a string holds as JSON text, decoding repeatedly if the text is
+/// itself a JSON string (double-encoded). Strings holding a bare number, boolean or `null`
+/// are left alone: those are far more often meant as text.
+pub fn decode_stringified(s: &str) -> Option<Value> {
+    let text = s.trim();
+    let looks_like = |open: char, close: char| text.starts_with(open) && text.ends_with(close);
+    if looks_like('{', '}') || looks_like('[', ']') {
+        serde_json::from_str::<Value>(text).ok().filter(|value| value.is_object() || value.is_array())
+    } else if looks_like('"', '"') {
+        match serde_json::from_str::<Value>(text) {
+            Ok(Value::String(inner)) => decode_stringified(&inner),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// Replaces every string in `value` that holds JSON with the value it holds, including
+/// strings found inside the decoded values. Returns how many strings were unwrapped.
+pub fn unwrap_stringified(value: &mut Value) -> usize {
+    match value {
+        Value::String(s) => match decode_stringified(s) {
+            Some(mut decoded) => {
+                let nested = unwrap_stringified(&mut decoded);
+                *value = decoded;
+                1 + nested
+            }
+            None => 0,
+        },
+        Value::Object(obj) => obj.values_mut().map(unwrap_stringified).sum(),
+        Value::Array(items) => items.iter_mut().map(unwrap_stringified).sum(),
+        _ => 0,
+    }
+}
+
+/// Appends an object key to a jq path, quoting it when it isn't a plain identifier.
+fn push_key(path: &mut String, key: &str) {
+    if is_jq_identifier(key) {
+        path.push('.');
+        path.push_str(key);
+    } else {
+        path.push('.');
+        path.push_str(&serde_json::to_string(key).unwrap_or_default());
+    }
+}
+
+/// jq paths start with `.`, including the root itself and paths into a top-level array.
+fn finish_path(path: &mut String) {
+    if path.is_empty() || path.starts_with('[') {
+        path.insert(0, '.');
+    }
+}
+
+fn scalar_size(scalar: &Value) -> usize {
+    canonical::to_canonical_string(scalar).len()
+}
+
+/// Whether `a` and `b` have the same canonical form, without writing it out.
+fn canonically_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(k, child)| b.get(k).is_some_and(|other| canonically_equal(child, other)))
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| canonically_equal(a, b))
+        }
+        (Value::Object(_) | Value::Array(_), _) | (_, Value::Object(_) | Value::Array(_)) => false,
+        // Numbers can be written differently and still be the same, like 1.0 and 1
+        (a, b) => a == b || canonical::to_canonical_string(a) == canonical::to_canonical_string(b),
+    }
+}
+
+/// `true` when `path` points inside the subtree at `ancestor`.
+fn is_within(path: &str, ancestor: &str) -> bool {
+    let ancestor = if ancestor == "." { "" } else { ancestor };
+    path.strip_prefix(ancestor)
+        .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('['))
+}
+
+pub struct RepeatedSubtree {
+    pub paths: Vec<String>,
+    /// Length of the subtree's canonical serialization, as a rough measure of its weight.
+    pub size: usize,
+}
+
+/// Groups non-empty objects and arrays that occur more than once, comparing them in canonical
+/// form so key order doesn't matter. Copies nested inside a larger repeated subtree aren't
+/// reported separately. Largest subtrees come first.
+///
+/// Each subtree is hashed from its children's hashes on the way back up, so the document
+/// is walked once; subtrees are only compared in full when their hashes match.
+pub fn repeated_subtrees(value: &Value) -> Vec<RepeatedSubtree> {
+    struct Group<'a> {
+        value: &'a Value,
+        subtree: RepeatedSubtree,
+    }
+
+    /// Hash and canonical length of `value`, recording it under `groups` if it's a
+    /// non-empty container.
+    fn walk<'a>(value: &'a Value, path: &mut String, groups: &mut HashMap<u64, Vec<Group<'a>>>) -> (u64, usize) {
+        let mut hasher = DefaultHasher::new();
+        let len = path.len();
+        let size = match value {
+            Value::Object(obj) if !obj.is_empty() => {
+                let mut members = Vec::with_capacity(obj.len());
+                for (k, child) in obj {
+                    push_key(path, k);
+                    let (hash, size) = walk(child, path, groups);
+                    path.truncate(len);
+                    members.push((k, hash, size));
+                }
+                // Canonical order, so the hash doesn't depend on how the keys were written
+                members.sort_by(|(a, _, _), (b, _, _)| a.encode_utf16().cmp(b.encode_utf16()));
+                b'{'.hash(&mut hasher);
+                let mut size = 1 + members.len();
+                for (k, hash, child_size) in members {
+                    k.hash(&mut hasher);
+                    hash.hash(&mut hasher);
+                    size += scalar_size(&Value::String(k.clone())) + 1 + child_size;
+                }
+                size
+            }
+            Value::Array(items) if !items.is_empty() => {
+                b'['.hash(&mut hasher);
+                let mut size = 1 + items.len();
+                for (i, child) in items.iter().enumerate() {
+                    path.push_str(&format!("[{}]", i));
+                    let (hash, child_size) = walk(child, path, groups);
+                    path.truncate(len);
+                    hash.hash(&mut hasher);
+                    size += child_size;
+                }
+                size
+            }
+            scalar => {
+                let canonical = canonical::to_canonical_string(scalar);
+                canonical.hash(&mut hasher);
+                return (hasher.finish(), canonical.len());
+            }
+        };
+        let hash = hasher.finish();
+
+        let mut finished = path.clone();
+        finish_path(&mut finished);
+        let bucket = groups.entry(hash).or_default();
+        match bucket.iter_mut().find(|group| canonically_equal(group.value, value)) {
+            Some(group) => group.subtree.paths.push(finished),
+            None => bucket.push(Group {
+                value,
+                subtree: RepeatedSubtree { paths: vec![finished], size },
+            }),
+        }
+        (hash, size)
+    }
+
+    let mut groups = HashMap::new();
+    walk(value, &mut String::new(), &mut groups);
+    let groups = groups.into_values().flatten().map(|group| group.subtree);
+    let mut repeated: Vec<RepeatedSubtree> = groups.filter(|group| group.paths.len() > 1).collect();
+    repeated.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.paths.cmp(&b.paths)));
+
+    let mut reported: Vec<RepeatedSubtree> = Vec::new();
+    for group in repeated {
+        let nested = group.paths.iter().all(|path| {
+            reported
+                .iter()
+                .any(|kept| kept.paths.iter().any(|ancestor| is_within(path, ancestor)))
+        });
+        if !nested {
+            reported.push(group);
+        }
+    }
+    reported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn repeated_subtrees_ignore_key_order() {
+        let value = json!({
+            "a": {"x": 1, "y": [true, null]},
+            "b": [{"y": [true, null], "x": 1.0}],
+            "c": {"x": 2, "y": [true, null]},
+        });
+        let repeated = repeated_subtrees(&value);
+        assert_eq!(repeated.len(), 2);
+        assert_eq!(repeated[0].paths, [".a", ".b[0]"]);
+        assert_eq!(repeated[0].size, canonical::to_canonical_string(&value["a"]).len());
+        // The inner array repeats in .c too, beyond the copies inside the group above
+        assert_eq!(repeated[1].paths, [".a.y", ".b[0].y", ".c.y"]);
+        assert_eq!(repeated[1].size, "[true,null]".len());
+    }
+
+    #[test]
+    fn copies_inside_a_repeated_subtree_are_not_reported() {
+        let item = json!({"id": 1, "tags": ["a", "b"]});
+        let repeated = repeated_subtrees(&json!([item, item]));
+        assert_eq!(repeated.len(), 1);
+        assert_eq!(repeated[0].paths, [".[0]", ".[1]"]);
+    }
+
+    #[test]
+    fn equal_hashes_are_confirmed() {
+        assert!(canonically_equal(&json!({"a": [1, 2]}), &json!({"a": [1.0, 2]})));
+        assert!(!canonically_equal(&json!({"a": [1, 2]}), &json!({"a": [2, 1]})));
+        assert!(!canonically_equal(&json!({"a": 1}), &json!({"b": 1})));
+        assert!(!canonically_equal(&json!([[]]), &json!([{}])));
+        assert!(repeated_subtrees(&json!([{"a": 1}, {"a": "1"}])).is_empty());
+    }
+}
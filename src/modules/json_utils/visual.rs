@@ -0,0 +1,113 @@
+//! Cursor and range tracking for visual selection in the raw view.
+//!
+//! Positions are `(line, column)` pairs over the logical lines of the displayed text, with
+//! columns counted in chars so multibyte content is never split. Like vim's visual mode the
+//! selection is inclusive of both the anchor and the cursor.
+
+use ratatui::prelude::*;
+
+type Position = (usize, usize);
+
+pub struct VisualSelection {
+    pub cursor: Position,
+    pub anchor: Option<Position>,
+}
+
+fn line_len(text: &str, line: usize) -> usize {
+    text.lines().nth(line).map(|l| l.chars().count()).unwrap_or(0)
+}
+
+impl VisualSelection {
+    pub fn new(line: usize) -> Self {
+        Self {
+            cursor: (line, 0),
+            anchor: None,
+        }
+    }
+
+    pub fn move_vertical(&mut self, text: &str, down: bool) {
+        let line_count = text.lines().count();
+        let (line, column) = self.cursor;
+        let line = if down {
+            (line + 1).min(line_count.saturating_sub(1))
+        } else {
+            line.saturating_sub(1)
+        };
+        self.cursor = (line, column.min(line_len(text, line).saturating_sub(1)));
+    }
+
+    pub fn move_horizontal(&mut self, text: &str, right: bool) {
+        let (line, column) = self.cursor;
+        let column = if right {
+            (column + 1).min(line_len(text, line).saturating_sub(1))
+        } else {
+            column.saturating_sub(1)
+        };
+        self.cursor = (line, column);
+    }
+
+    /// Ordered `(start, end)` of the selection, or just the cursor when nothing is anchored.
+    fn range(&self) -> (Position, Position) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        if anchor <= self.cursor {
+            (anchor, self.cursor)
+        } else {
+            (self.cursor, anchor)
+        }
+    }
+
+    pub fn selected_text(&self, text: &str) -> String {
+        let ((start_line, start_col), (end_line, end_col)) = self.range();
+        let mut selected = Vec::new();
+        for (i, line) in text.lines().enumerate().take(end_line + 1).skip(start_line) {
+            let from = if i == start_line { start_col } else { 0 };
+            let part: String = if i == end_line {
+                line.chars().take(end_col + 1).skip(from).collect()
+            } else {
+                line.chars().skip(from).collect()
+            };
+            selected.push(part);
+        }
+        selected.join("\n")
+    }
+
+    /// Splits the text into lines with the selection highlighted and the cursor reversed.
+    pub fn highlight<'a>(&self, text: &'a str, base: Style) -> Text<'a> {
+        let ((start_line, start_col), (end_line, end_col)) = self.range();
+        let selection_style = Style::default().bg(Color::Blue).fg(Color::White);
+        let cursor_style = Style::default().add_modifier(Modifier::REVERSED);
+
+        let lines: Vec<Line> = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if self.anchor.is_none() && i != self.cursor.0 {
+                    return Line::styled(line, base);
+                }
+                if self.anchor.is_some() && (i < start_line || i > end_line) {
+                    return Line::styled(line, base);
+                }
+
+                let mut spans = Vec::new();
+                for (col, c) in line.chars().enumerate() {
+                    let selected = self.anchor.is_some()
+                        && (i > start_line || col >= start_col)
+                        && (i < end_line || col <= end_col);
+                    let style = if (i, col) == self.cursor {
+                        cursor_style
+                    } else if selected {
+                        selection_style
+                    } else {
+                        base
+                    };
+                    spans.push(Span::styled(c.to_string(), style));
+                }
+                if line.is_empty() && i == self.cursor.0 {
+                    spans.push(Span::styled(" ", cursor_style));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        Text::from(lines)
+    }
+}
@@ -1 +1,7 @@
-pub mod json_utils;
\ No newline at end of file
+pub mod base64_utils;
+pub mod file_utils;
+pub mod hash_utils;
+pub mod json_utils;
+pub mod regex_utils;
+pub mod time_utils;
+pub mod uuid_utils;
\ No newline at end of file
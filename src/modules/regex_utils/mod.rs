@@ -0,0 +1,308 @@
+use color_eyre::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+};
+use regex::Regex;
+use std::time::Duration;
+
+use crate::clipboard;
+
+const GROUP_COLORS: [Color; 4] = [Color::Green, Color::Magenta, Color::Cyan, Color::Red];
+
+#[derive(PartialEq)]
+enum InputMode {
+    Normal,
+    Editing,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Field {
+    Pattern,
+    TestString,
+}
+
+pub struct RegexUtils {
+    pattern: String,
+    test_string: String,
+    focus: Field,
+    input_mode: InputMode,
+    compiled: Option<Regex>,
+    error_message: String,
+    selected: usize,
+}
+
+impl RegexUtils {
+    pub fn new() -> Self {
+        Self {
+            pattern: String::new(),
+            test_string: String::new(),
+            focus: Field::Pattern,
+            input_mode: InputMode::Normal,
+            compiled: None,
+            error_message: String::new(),
+            selected: 0,
+        }
+    }
+
+    fn compile(&mut self) {
+        self.selected = 0;
+        if self.pattern.is_empty() {
+            self.compiled = None;
+            self.error_message.clear();
+            return;
+        }
+        match Regex::new(&self.pattern) {
+            Ok(regex) => {
+                self.compiled = Some(regex);
+                self.error_message.clear();
+            }
+            Err(e) => {
+                self.compiled = None;
+                self.error_message = format!("Invalid regex: {}", e);
+            }
+        }
+    }
+
+    fn field_mut(&mut self) -> &mut String {
+        match self.focus {
+            Field::Pattern => &mut self.pattern,
+            Field::TestString => &mut self.test_string,
+        }
+    }
+
+    fn enter_char(&mut self, c: char) {
+        self.field_mut().push(c);
+        self.compile();
+    }
+
+    fn delete_char(&mut self) {
+        self.field_mut().pop();
+        self.compile();
+    }
+
+    pub fn paste_from_clipboard(&mut self) -> Result<()> {
+        let mut clipboard = clipboard::open();
+        match clipboard.get_text() {
+            Ok(text) => {
+                *self.field_mut() = text;
+                self.compile();
+            }
+            Err(e) => {
+                self.error_message = format!("Failed to get clipboard: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Summaries of every match and its capture groups, one row per match.
+    fn match_rows(&self) -> Vec<String> {
+        let Some(ref regex) = self.compiled else {
+            return Vec::new();
+        };
+        let names: Vec<Option<&str>> = regex.capture_names().collect();
+        regex
+            .captures_iter(&self.test_string)
+            .enumerate()
+            .filter_map(|(i, caps)| {
+                let whole = caps.get(0)?;
+                let mut row = format!("#{} [{}..{}] {:?}", i + 1, whole.start(), whole.end(), whole.as_str());
+                for (group, name) in names.iter().enumerate().skip(1) {
+                    let label = name.map(str::to_string).unwrap_or_else(|| group.to_string());
+                    match caps.get(group) {
+                        Some(m) => row.push_str(&format!("  {}={:?}", label, m.as_str())),
+                        None => row.push_str(&format!("  {}=<none>", label)),
+                    }
+                }
+                Some(row)
+            })
+            .collect()
+    }
+
+    /// Splits the test string into spans, styling whole matches and overlaying each capture group.
+    fn highlighted_text(&self) -> Text<'_> {
+        let Some(ref regex) = self.compiled else {
+            return Text::from(self.test_string.as_str());
+        };
+
+        // 0 = unmatched, 1 = whole match, 2.. = capture group index + 1
+        let mut classes = vec![0usize; self.test_string.len()];
+        for caps in regex.captures_iter(&self.test_string) {
+            for (group, m) in caps.iter().enumerate() {
+                if let Some(m) = m {
+                    for class in &mut classes[m.start()..m.end()] {
+                        *class = group + 1;
+                    }
+                }
+            }
+        }
+
+        let style_for = |class: usize| match class {
+            0 => Style::default().fg(Color::White),
+            1 => Style::default().bg(Color::Yellow).fg(Color::Black),
+            n => Style::default().bg(GROUP_COLORS[(n - 2) % GROUP_COLORS.len()]).fg(Color::Black),
+        };
+
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for line in self.test_string.split('\n') {
+            let mut spans = Vec::new();
+            let mut run_start = 0;
+            let mut run_class = None;
+            for (i, _) in line.char_indices() {
+                let class = classes[offset + i];
+                if run_class != Some(class) {
+                    if let Some(previous) = run_class {
+                        spans.push(Span::styled(&line[run_start..i], style_for(previous)));
+                    }
+                    run_start = i;
+                    run_class = Some(class);
+                }
+            }
+            if let Some(previous) = run_class {
+                spans.push(Span::styled(&line[run_start..], style_for(previous)));
+            }
+            lines.push(Line::from(spans));
+            offset += line.len() + 1;
+        }
+        Text::from(lines)
+    }
+
+    fn field_block(&self, field: Field, title: &str) -> Block<'_> {
+        let style = if self.focus == field && self.input_mode == InputMode::Editing {
+            Style::default().fg(Color::Yellow)
+        } else if self.focus == field {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        Block::default()
+            .title(title.to_string())
+            .borders(Borders::ALL)
+            .border_style(style)
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(7),
+                Constraint::Min(1),
+                Constraint::Length(3),
+            ])
+            .split(area);
+
+        let pattern = Paragraph::new(self.pattern.as_str())
+            .block(self.field_block(Field::Pattern, "Pattern"))
+            .style(Style::default().fg(Color::White));
+        frame.render_widget(pattern, chunks[0]);
+
+        let test_string = Paragraph::new(self.highlighted_text())
+            .block(self.field_block(Field::TestString, "Test String"))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(test_string, chunks[1]);
+
+        if self.input_mode == InputMode::Editing {
+            let (area, text) = match self.focus {
+                Field::Pattern => (chunks[0], self.pattern.as_str()),
+                Field::TestString => (chunks[1], self.test_string.as_str()),
+            };
+            let last_line = text.rsplit('\n').next().unwrap_or("");
+            let row = text.matches('\n').count() as u16;
+            frame.set_cursor_position((
+                area.x + last_line.chars().count() as u16 + 1,
+                area.y + row + 1,
+            ));
+        }
+
+        let rows = self.match_rows();
+        let title = format!(
+            "Matches ({}) - 'i': edit, Tab: switch field, 'p': paste into field, ↑/↓ j/k: navigate, 'q': quit",
+            rows.len()
+        );
+        let items: Vec<ListItem> = rows
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let style = if i == self.selected {
+                    Style::default().bg(Color::Blue).fg(Color::White)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                ListItem::new(row).style(style)
+            })
+            .collect();
+        frame.render_widget(List::new(items).block(Block::default().title(title).borders(Borders::ALL)), chunks[2]);
+
+        let status = Paragraph::new(self.error_message.as_str())
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(Color::Red));
+        frame.render_widget(status, chunks[3]);
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Result<bool> {
+        if let Event::Key(key) = event {
+            if key.kind != KeyEventKind::Press {
+                return Ok(true);
+            }
+            match self.input_mode {
+                InputMode::Editing => match key.code {
+                    KeyCode::Esc => self.input_mode = InputMode::Normal,
+                    KeyCode::Enter if self.focus == Field::Pattern => self.input_mode = InputMode::Normal,
+                    KeyCode::Enter => self.enter_char('\n'),
+                    KeyCode::Tab => self.toggle_focus(),
+                    KeyCode::Backspace => self.delete_char(),
+                    KeyCode::Char(c) => self.enter_char(c),
+                    _ => {}
+                },
+                InputMode::Normal => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                    KeyCode::Char('i') => self.input_mode = InputMode::Editing,
+                    KeyCode::Tab => self.toggle_focus(),
+                    KeyCode::Char('p') => self.paste_from_clipboard()?,
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.selected = self.selected.saturating_sub(1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        let count = self.match_rows().len();
+                        self.selected = (self.selected + 1).min(count.saturating_sub(1));
+                    }
+                    _ => {}
+                },
+            }
+        }
+        Ok(true)
+    }
+
+    fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Field::Pattern => Field::TestString,
+            Field::TestString => Field::Pattern,
+        };
+    }
+}
+
+pub fn run_regex_utils() -> Result<()> {
+    let mut terminal = ratatui::init();
+    let mut regex_utils = RegexUtils::new();
+
+    loop {
+        terminal.draw(|frame| {
+            regex_utils.render(frame, frame.area());
+            crate::color::adapt_buffer(frame.buffer_mut());
+        })?;
+
+        if event::poll(Duration::from_millis(100))? {
+            let event = event::read()?;
+            if !regex_utils.handle_event(event)? {
+                break;
+            }
+        }
+    }
+
+    ratatui::restore();
+    Ok(())
+}
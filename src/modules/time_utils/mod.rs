@@ -0,0 +1,273 @@
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use color_eyre::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+};
+use std::time::Duration;
+
+use crate::clipboard;
+
+pub struct TimeUtils {
+    source: String,
+    representations: Vec<(&'static str, String)>,
+    selected: usize,
+    error_message: String,
+}
+
+/// Interprets pasted text as an epoch timestamp or a formatted datetime.
+///
+/// Bare numbers whose whole part has up to 10 digits are seconds and 13 digits are
+/// milliseconds; 11-12 digit values could plausibly be either and are rejected rather than
+/// guessed.
+fn parse_input(input: &str) -> Result<(DateTime<Utc>, String), String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Input is empty".to_string());
+    }
+
+    if let Ok(number) = input.parse::<i64>() {
+        let digits = number.unsigned_abs().to_string().len();
+        let parsed = if is_millis(digits)? {
+            DateTime::from_timestamp_millis(number).map(|dt| (dt, "Unix milliseconds".to_string()))
+        } else {
+            DateTime::from_timestamp(number, 0).map(|dt| (dt, "Unix seconds".to_string()))
+        };
+        return parsed.ok_or_else(|| "Timestamp out of range".to_string());
+    }
+
+    if let Ok(number) = input.parse::<f64>() {
+        // `nan` and `inf` parse as floats too, and would otherwise cast to a real timestamp
+        if !number.is_finite() {
+            return Err(format!("'{}' is not a finite timestamp", input));
+        }
+        let digits = (number.trunc().abs() as u64).to_string().len();
+        let parsed = if is_millis(digits)? {
+            DateTime::from_timestamp_millis(number.round() as i64)
+                .map(|dt| (dt, "Unix milliseconds (fractional)".to_string()))
+        } else {
+            DateTime::from_timestamp_millis((number * 1000.0).round() as i64)
+                .map(|dt| (dt, "Unix seconds (fractional)".to_string()))
+        };
+        return parsed.ok_or_else(|| "Timestamp out of range".to_string());
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Ok((dt.with_timezone(&Utc), "ISO-8601 / RFC 3339".to_string()));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc2822(input) {
+        return Ok((dt.with_timezone(&Utc), "RFC 2822".to_string()));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(input, format) {
+            return Ok((naive.and_utc(), "datetime without offset (assumed UTC)".to_string()));
+        }
+    }
+
+    Err(format!("Could not parse '{}' as a timestamp or datetime", input))
+}
+
+/// Whether an epoch value whose whole part has `digits` digits is in milliseconds rather than
+/// seconds, by the rule described on `parse_input`.
+fn is_millis(digits: usize) -> Result<bool, String> {
+    match digits {
+        0..=10 => Ok(false),
+        13 => Ok(true),
+        11 | 12 => Err(format!(
+            "Ambiguous timestamp: {} digits could be seconds or milliseconds",
+            digits
+        )),
+        _ => Err(format!("Unsupported timestamp length: {} digits (expected 10 or 13)", digits)),
+    }
+}
+
+fn relative(dt: DateTime<Utc>) -> String {
+    let delta = Utc::now().signed_duration_since(dt);
+    let seconds = delta.num_seconds().abs();
+    let amount = if seconds < 60 {
+        format!("{} seconds", seconds)
+    } else if seconds < 3600 {
+        format!("{} minutes", seconds / 60)
+    } else if seconds < 86_400 {
+        format!("{} hours", seconds / 3600)
+    } else {
+        format!("{} days", seconds / 86_400)
+    };
+    if delta.num_seconds() >= 0 {
+        format!("{} ago", amount)
+    } else {
+        format!("in {}", amount)
+    }
+}
+
+impl TimeUtils {
+    pub fn new() -> Self {
+        Self {
+            source: String::new(),
+            representations: Vec::new(),
+            selected: 0,
+            error_message: String::new(),
+        }
+    }
+
+    fn show(&mut self, dt: DateTime<Utc>, source: String) {
+        self.source = source;
+        self.representations = vec![
+            ("Seconds", dt.timestamp().to_string()),
+            ("Millis", dt.timestamp_millis().to_string()),
+            ("ISO-8601", dt.to_rfc3339()),
+            ("Local", dt.with_timezone(&Local).to_rfc3339()),
+            ("RFC-2822", dt.to_rfc2822()),
+            ("Relative", relative(dt)),
+        ];
+        self.selected = 0;
+        self.error_message.clear();
+    }
+
+    pub fn now(&mut self) {
+        self.show(Utc::now(), "current time".to_string());
+    }
+
+    pub fn paste_from_clipboard(&mut self) -> Result<()> {
+        let mut clipboard = clipboard::open();
+        match clipboard.get_text() {
+            Ok(text) => match parse_input(&text) {
+                Ok((dt, source)) => self.show(dt, source),
+                Err(e) => {
+                    self.error_message = e;
+                }
+            },
+            Err(e) => {
+                self.error_message = format!("Failed to get clipboard: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn copy_selected_to_clipboard(&mut self) -> Result<()> {
+        if let Some((label, value)) = self.representations.get(self.selected) {
+            self.error_message = clipboard::copy(value, format!("Copied {} to clipboard", label));
+        }
+        Ok(())
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(3)])
+            .split(area);
+
+        let title = if self.source.is_empty() {
+            "Time Tools - 'p': paste, 'n': now, 'c': copy, ↑/↓ j/k: navigate, 'q': quit".to_string()
+        } else {
+            format!("Time Tools ({}) - 'p': paste, 'n': now, 'c': copy, ↑/↓ j/k: navigate, 'q': quit", self.source)
+        };
+        let block = Block::default().title(title).borders(Borders::ALL);
+
+        if self.representations.is_empty() {
+            let hint = Paragraph::new("Press 'p' to convert a timestamp or datetime from the clipboard, or 'n' for now")
+                .block(block)
+                .style(Style::default().fg(Color::Cyan));
+            frame.render_widget(hint, chunks[0]);
+        } else {
+            let items: Vec<ListItem> = self
+                .representations
+                .iter()
+                .enumerate()
+                .map(|(i, (label, value))| {
+                    let style = if i == self.selected {
+                        Style::default().bg(Color::Blue).fg(Color::White)
+                    } else {
+                        Style::default().fg(Color::White)
+                    };
+                    ListItem::new(format!("{:<9} {}", label, value)).style(style)
+                })
+                .collect();
+            frame.render_widget(List::new(items).block(block), chunks[0]);
+        }
+
+        let status_color = if self.error_message.starts_with("Copied") {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        let status = Paragraph::new(self.error_message.as_str())
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(status_color));
+        frame.render_widget(status, chunks[1]);
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Result<bool> {
+        if let Event::Key(key) = event {
+            if key.kind != KeyEventKind::Press {
+                return Ok(true);
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                KeyCode::Char('p') => self.paste_from_clipboard()?,
+                KeyCode::Char('n') => self.now(),
+                KeyCode::Char('c') => self.copy_selected_to_clipboard()?,
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.selected = (self.selected + 1).min(self.representations.len().saturating_sub(1));
+                }
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+}
+
+pub fn run_time_utils() -> Result<()> {
+    let mut terminal = ratatui::init();
+    let mut time_utils = TimeUtils::new();
+
+    loop {
+        terminal.draw(|frame| {
+            time_utils.render(frame, frame.area());
+            crate::color::adapt_buffer(frame.buffer_mut());
+        })?;
+
+        if event::poll(Duration::from_millis(100))? {
+            let event = event::read()?;
+            if !time_utils.handle_event(event)? {
+                break;
+            }
+        }
+    }
+
+    ratatui::restore();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fractional_input_follows_the_digit_rule() {
+        let (dt, source) = parse_input("1700000000.5").unwrap();
+        assert_eq!(dt.timestamp_millis(), 1_700_000_000_500);
+        assert_eq!(source, "Unix seconds (fractional)");
+
+        let (dt, source) = parse_input("1700000000000.4").unwrap();
+        assert_eq!(dt.timestamp_millis(), 1_700_000_000_000);
+        assert_eq!(source, "Unix milliseconds (fractional)");
+
+        for input in ["12345678901.0", "123456789012.5", "-12345678901.0"] {
+            assert!(parse_input(input).unwrap_err().starts_with("Ambiguous timestamp"), "{}", input);
+        }
+        assert!(parse_input("1e30").unwrap_err().starts_with("Unsupported timestamp length"));
+    }
+
+    #[test]
+    fn non_finite_input_is_rejected() {
+        for input in ["nan", "NaN", "inf", "-infinity"] {
+            assert!(parse_input(input).unwrap_err().contains("not a finite timestamp"), "{}", input);
+        }
+    }
+}
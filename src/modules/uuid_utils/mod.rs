@@ -0,0 +1,179 @@
+use chrono::DateTime;
+use color_eyre::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+};
+use std::time::Duration;
+use uuid::{Uuid, Variant, Version};
+
+use crate::clipboard;
+
+pub struct UuidUtils {
+    current: Option<Uuid>,
+    details: Vec<(&'static str, String)>,
+    error_message: String,
+}
+
+fn version_name(version: Option<Version>) -> String {
+    match version {
+        Some(Version::Nil) => "nil".to_string(),
+        Some(Version::Mac) => "1 (time + MAC)".to_string(),
+        Some(Version::Dce) => "2 (DCE security)".to_string(),
+        Some(Version::Md5) => "3 (MD5 name-based)".to_string(),
+        Some(Version::Random) => "4 (random)".to_string(),
+        Some(Version::Sha1) => "5 (SHA-1 name-based)".to_string(),
+        Some(Version::SortMac) => "6 (reordered time)".to_string(),
+        Some(Version::SortRand) => "7 (Unix time-ordered)".to_string(),
+        Some(Version::Custom) => "8 (custom)".to_string(),
+        Some(Version::Max) => "max".to_string(),
+        Some(_) | None => "unknown".to_string(),
+    }
+}
+
+fn variant_name(variant: Variant) -> &'static str {
+    match variant {
+        Variant::NCS => "NCS (reserved)",
+        Variant::RFC4122 => "RFC 4122 / RFC 9562",
+        Variant::Microsoft => "Microsoft (reserved)",
+        Variant::Future => "Future (reserved)",
+        _ => "unknown",
+    }
+}
+
+impl UuidUtils {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            details: Vec::new(),
+            error_message: String::new(),
+        }
+    }
+
+    fn inspect(&mut self, uuid: Uuid) {
+        let mut details = vec![
+            ("UUID", uuid.hyphenated().to_string()),
+            ("Simple", uuid.simple().to_string()),
+            ("URN", uuid.urn().to_string()),
+            ("Version", version_name(uuid.get_version())),
+            ("Variant", variant_name(uuid.get_variant()).to_string()),
+        ];
+        if let Some(timestamp) = uuid.get_timestamp() {
+            let (secs, nanos) = timestamp.to_unix();
+            let datetime = DateTime::from_timestamp(secs as i64, nanos)
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_else(|| "out of range".to_string());
+            details.push(("Timestamp", format!("{} ({}.{:03} Unix seconds)", datetime, secs, nanos / 1_000_000)));
+        }
+        self.current = Some(uuid);
+        self.details = details;
+        self.error_message.clear();
+    }
+
+    pub fn generate_v4(&mut self) {
+        self.inspect(Uuid::new_v4());
+    }
+
+    pub fn generate_v7(&mut self) {
+        self.inspect(Uuid::now_v7());
+    }
+
+    pub fn paste_from_clipboard(&mut self) -> Result<()> {
+        let mut clipboard = clipboard::open();
+        match clipboard.get_text() {
+            Ok(text) => match Uuid::parse_str(text.trim()) {
+                Ok(uuid) => self.inspect(uuid),
+                Err(e) => {
+                    self.error_message = format!("Invalid UUID: {}", e);
+                }
+            },
+            Err(e) => {
+                self.error_message = format!("Failed to get clipboard: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn copy_to_clipboard(&mut self) -> Result<()> {
+        if let Some(uuid) = self.current {
+            self.error_message = clipboard::copy(&uuid.hyphenated().to_string(), "Copied UUID to clipboard".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(3)])
+            .split(area);
+
+        let block = Block::default()
+            .title("UUID Tools - '4': new v4, '7': new v7, 'p': paste & inspect, 'c': copy, 'q': quit")
+            .borders(Borders::ALL);
+
+        if self.details.is_empty() {
+            let hint = Paragraph::new("Press '4' or '7' to generate a UUID, or 'p' to inspect one from the clipboard")
+                .block(block)
+                .style(Style::default().fg(Color::Cyan));
+            frame.render_widget(hint, chunks[0]);
+        } else {
+            let items: Vec<ListItem> = self
+                .details
+                .iter()
+                .map(|(label, value)| ListItem::new(format!("{:<10} {}", label, value)).style(Style::default().fg(Color::White)))
+                .collect();
+            frame.render_widget(List::new(items).block(block), chunks[0]);
+        }
+
+        let status_color = if self.error_message.starts_with("Copied") {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        let status = Paragraph::new(self.error_message.as_str())
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(status_color));
+        frame.render_widget(status, chunks[1]);
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Result<bool> {
+        if let Event::Key(key) = event {
+            if key.kind != KeyEventKind::Press {
+                return Ok(true);
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                KeyCode::Char('4') => self.generate_v4(),
+                KeyCode::Char('7') => self.generate_v7(),
+                KeyCode::Char('p') => self.paste_from_clipboard()?,
+                KeyCode::Char('c') => self.copy_to_clipboard()?,
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+}
+
+pub fn run_uuid_utils() -> Result<()> {
+    let mut terminal = ratatui::init();
+    let mut uuid_utils = UuidUtils::new();
+
+    loop {
+        terminal.draw(|frame| {
+            uuid_utils.render(frame, frame.area());
+            crate::color::adapt_buffer(frame.buffer_mut());
+        })?;
+
+        if event::poll(Duration::from_millis(100))? {
+            let event = event::read()?;
+            if !uuid_utils.handle_event(event)? {
+                break;
+            }
+        }
+    }
+
+    ratatui::restore();
+    Ok(())
+}
@@ -0,0 +1,32 @@
+//! Rendering helpers shared by the main menu and the tools.
+
+use ratatui::{
+    layout::{Alignment, Rect},
+    prelude::*,
+    widgets::{Paragraph, Wrap},
+};
+
+/// Replaces the whole frame with a notice when it is smaller than `min_width` x
+/// `min_height`, returning `true` so the caller skips its normal layout. The notice goes
+/// away on its own once the terminal is resized back.
+pub fn render_too_small(frame: &mut Frame, min_width: u16, min_height: u16) -> bool {
+    let area = frame.area();
+    if area.width >= min_width && area.height >= min_height {
+        return false;
+    }
+    let message = format!(
+        "Terminal too small: {}x{}, need at least {}x{}",
+        area.width, area.height, min_width, min_height
+    );
+    let notice = Paragraph::new(message)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(Color::Yellow));
+    let middle = Rect {
+        y: area.y + area.height / 2,
+        height: area.height - area.height / 2,
+        ..area
+    };
+    frame.render_widget(notice, middle);
+    true
+}